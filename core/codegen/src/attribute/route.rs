@@ -7,7 +7,7 @@ use proc_macro_ext::{Diagnostics, StringLit};
 use syn_ext::{syn_to_diag, IdentExt};
 use self::syn::{Attribute, parse::Parser};
 
-use http_codegen::{Method, MediaType, RoutePath, DataSegment, Optional};
+use http_codegen::{Method, MediaType, RoutePath, RouteHost, DataSegment, Optional};
 use attribute::segments::{Source, Kind, Segment};
 use {ROUTE_FN_PREFIX, ROUTE_STRUCT_PREFIX, URI_MACRO_PREFIX, ROCKET_PARAM_PREFIX};
 
@@ -20,6 +20,7 @@ struct RouteAttribute {
     data: Option<SpanWrapped<DataSegment>>,
     format: Option<MediaType>,
     rank: Option<isize>,
+    host: Option<RouteHost>,
 }
 
 /// The raw, parsed `#[method]` (e.g, `get`, `put`, `post`, etc.) attribute.
@@ -30,6 +31,7 @@ struct MethodRouteAttribute {
     data: Option<SpanWrapped<DataSegment>>,
     format: Option<MediaType>,
     rank: Option<isize>,
+    host: Option<RouteHost>,
 }
 
 /// This structure represents the parsed `route` attribute and associated items.
@@ -78,6 +80,10 @@ fn parse_route(attr: RouteAttribute, function: syn::ItemFn) -> Result<Route> {
     dup_check(&mut segments, attr.path.path.iter().cloned(), &mut diags);
     attr.path.query.as_ref().map(|q| dup_check(&mut segments, q.iter().cloned(), &mut diags));
     dup_check(&mut segments, attr.data.clone().map(|s| s.value.0).into_iter(), &mut diags);
+    attr.host.as_ref().map(|h| {
+        let named = h.segments.iter().filter(|s| !s.is_wildcard()).cloned();
+        dup_check(&mut segments, named, &mut diags)
+    });
 
     // Check the validity of function arguments.
     let mut inputs = vec![];
@@ -143,9 +149,14 @@ fn param_expr(seg: &Segment, ident: &syn::Ident, ty: &syn::Type) -> TokenStream2
         #Outcome::Forward(#data)
     });
 
+    let raw_segment = match seg.source {
+        Source::Host => quote!(raw_host_label),
+        _ => quote!(raw_segment_str),
+    };
+
     let expr = match seg.kind {
         Kind::Single => quote_spanned! { span =>
-            match #req.raw_segment_str(#i) {
+            match #req.#raw_segment(#i) {
                 Some(__s) => match <#ty as #request::FromParam>::from_param(__s) {
                     Ok(__v) => __v,
                     Err(#error) => return #parse_error,
@@ -345,7 +356,7 @@ fn codegen_route(route: Route) -> Result<TokenStream> {
     for (ident, rocket_ident, ty) in &route.inputs {
         let fn_segment: Segment = ident.into();
         match route.segments.get(&fn_segment) {
-            Some(seg) if seg.source == Source::Path => {
+            Some(seg) if seg.source == Source::Path || seg.source == Source::Host => {
                 parameter_definitions.push(param_expr(seg, rocket_ident, &ty));
             }
             Some(seg) if seg.source == Source::Data => {
@@ -376,6 +387,7 @@ fn codegen_route(route: Route) -> Result<TokenStream> {
     let path = route.attribute.path.origin.0.to_string();
     let rank = Optional(route.attribute.rank);
     let format = Optional(route.attribute.format);
+    let host = Optional(route.attribute.host.map(|host| host.string));
 
     Ok(quote! {
         #user_handler_fn
@@ -406,6 +418,7 @@ fn codegen_route(route: Route) -> Result<TokenStream> {
                 handler: #generated_fn_name,
                 format: #format,
                 rank: #rank,
+                host: #host,
             };
     }.into())
 }
@@ -454,6 +467,7 @@ fn incomplete_route(
         data: method_attribute.data,
         format: method_attribute.format,
         rank: method_attribute.rank,
+        host: method_attribute.host,
     };
 
     codegen_route(parse_route(attribute, function)?)
@@ -4,7 +4,7 @@ use devise::syn;
 use proc_macro::{Span, Diagnostic};
 
 use http::uri::{UriPart, Path};
-use http::route::RouteSegment;
+use http::route::{RouteSegment, HostSegment};
 use proc_macro_ext::{Diagnostics, StringLit, PResult, DResult};
 
 crate use http::route::{Error, Kind, Source};
@@ -29,6 +29,17 @@ impl Segment {
         let (kind, index) = (segment.kind, segment.index);
         Segment { span, kind, source, index, name: segment.name.into_owned() }
     }
+
+    fn from_host(segment: HostSegment, span: Span) -> Segment {
+        let (kind, index) = (segment.kind, segment.index);
+        let name = segment.name.into_owned();
+        Segment { span, kind, source: Source::Host, index, name }
+    }
+
+    /// Whether this is the anonymous `*` label of a host pattern.
+    crate fn is_wildcard(&self) -> bool {
+        self.source == Source::Host && self.name == "*"
+    }
 }
 
 impl<'a> From<&'a syn::Ident> for Segment {
@@ -112,6 +123,18 @@ fn into_diagnostic(
                 .help("a multi-segment param must be the final component")
                 .span_note(multi_span, "multi-segment param is here")
         }
+        Error::BadConstraint(constraint) if constraint.starts_with("regex(") => {
+            seg_span.error(format!("`{}` is not a valid regular expression constraint", constraint))
+                .note("regular expressions cannot contain '/', '?', '#', '<', or '>'")
+        }
+        Error::BadConstraint(constraint) => {
+            seg_span.error(format!("unknown constraint `{}`", constraint))
+                .help("constraints are `int`, `uint`, `alpha`, `alnum`, and `regex(..)`")
+        }
+        Error::Constrained => {
+            seg_span.error("only single-segment path parameters can be constrained")
+                .help("remove the constraint from this parameter")
+        }
     }
 }
 
@@ -147,3 +170,32 @@ crate fn parse_segments<P: UriPart>(
 
     diags.err_or(segments)
 }
+
+crate fn parse_host_segments(string: &str, span: Span) -> DResult<Vec<Segment>> {
+    let mut segments = vec![];
+    let mut diags = Diagnostics::new();
+
+    // Static labels are lowercased when parsed, so we split the labels here to
+    // keep hold of the original strings and their spans.
+    let labels = string.split('.').filter(|s| !s.is_empty());
+    for (i, label) in labels.enumerate() {
+        let label_span = subspan(label, string, span);
+        match HostSegment::parse_one(label) {
+            Ok(mut segment) => {
+                segment.index = Some(i);
+                segments.push(Segment::from_host(segment, label_span));
+            }
+            Err(Error::Uri) => {
+                diags.push(label_span.error("host label contains invalid characters")
+                    .help("labels may only contain letters, digits, and '-'"));
+            }
+            Err(Error::Malformed) if label.ends_with("..>") => {
+                diags.push(label_span.error("host parameters cannot span several labels")
+                    .help("host parameters must be of the form '<param>'"));
+            }
+            Err(error) => diags.push(into_diagnostic(label, string, span, &error)),
+        }
+    }
+
+    diags.err_or(segments)
+}
@@ -3,7 +3,8 @@ use proc_macro2::TokenStream as TokenStream2;
 use devise::{FromMeta, MetaItem, Result, ext::Split2};
 use http::{self, ext::IntoOwned};
 use http::uri::{Path, Query};
-use attribute::segments::{parse_segments, parse_data_segment, Segment, Kind};
+use attribute::segments::{parse_segments, parse_data_segment, parse_host_segments};
+use attribute::segments::{Segment, Kind};
 
 use proc_macro_ext::StringLit;
 
@@ -41,6 +42,12 @@ crate struct RoutePath {
     crate query: Option<Vec<Segment>>,
 }
 
+#[derive(Debug)]
+crate struct RouteHost {
+    crate string: String,
+    crate segments: Vec<Segment>,
+}
+
 impl FromMeta for Status {
     fn from_meta(meta: MetaItem) -> Result<Self> {
         let num = usize::from_meta(meta)?;
@@ -117,12 +124,12 @@ impl ToTokens for MediaType {
 }
 
 const VALID_METHODS_STR: &str = "`GET`, `PUT`, `POST`, `DELETE`, `HEAD`, \
-    `PATCH`, `OPTIONS`";
+    `PATCH`, `OPTIONS`, `CONNECT`, or an uppercase extension method";
 
 const VALID_METHODS: &[http::Method] = &[
     http::Method::Get, http::Method::Put, http::Method::Post,
     http::Method::Delete, http::Method::Head, http::Method::Patch,
-    http::Method::Options,
+    http::Method::Options, http::Method::Connect,
 ];
 
 impl FromMeta for Method {
@@ -131,10 +138,17 @@ impl FromMeta for Method {
         let help_text = format!("method must be one of: {}", VALID_METHODS_STR);
 
         if let MetaItem::Ident(ident) = meta {
-            let method = ident.to_string().parse()
+            let name = ident.to_string();
+            let method: http::Method = name.parse()
                 .map_err(|_| span.error("invalid HTTP method").help(&*help_text))?;
 
-            if !VALID_METHODS.contains(&method) {
+            // Extension methods must be written as they're sent to catch typos
+            // of standard methods, like `Gett`, that would otherwise be valid.
+            if method.is_extension() && method.as_str() != name {
+                return Err(span.error("invalid HTTP method").help(&*help_text));
+            }
+
+            if !method.is_extension() && !VALID_METHODS.contains(&method) {
                 return Err(span.error("invalid HTTP method for route handlers")
                                .help(&*help_text));
             }
@@ -159,6 +173,15 @@ impl ToTokens for Method {
             http::Method::Trace => quote!(::rocket::http::Method::Trace),
             http::Method::Connect => quote!(::rocket::http::Method::Connect),
             http::Method::Patch => quote!(::rocket::http::Method::Patch),
+            http::Method::Extension(method) => {
+                let (len, name) = method._raw();
+                let name = name.iter();
+                quote! {
+                    ::rocket::http::Method::Extension(
+                        ::rocket::http::ExtensionMethod::_new_unchecked(#len, [#(#name),*])
+                    )
+                }
+            }
         };
 
         tokens.extend(method_tokens);
@@ -195,7 +218,7 @@ impl FromMeta for DataSegment {
         let span = string.subspan(1..(string.len() + 1));
 
         let segment = parse_data_segment(&string, span)?;
-        if segment.kind != Kind::Single {
+        if segment.kind != Kind::Single || string.contains(':') {
             return Err(span.error("malformed parameter")
                         .help("parameter must be of the form '<param>'"));
         }
@@ -231,6 +254,21 @@ impl FromMeta for RoutePath {
     }
 }
 
+impl FromMeta for RouteHost {
+    fn from_meta(meta: MetaItem) -> Result<Self> {
+        let string = StringLit::from_meta(meta)?;
+        let span = string.subspan(1..(string.len() + 1));
+
+        let segments = parse_host_segments(&string, span).map_err(|d| d.emit_head())?;
+        if segments.is_empty() {
+            return Err(string.span().error("host pattern cannot be empty")
+                        .help("a host pattern is of the form 'api.example.com'"));
+        }
+
+        Ok(RouteHost { string: string.to_string(), segments })
+    }
+}
+
 impl<T: ToTokens> ToTokens for Optional<T> {
     fn to_tokens(&self, tokens: &mut TokenStream2) {
         let opt_tokens = match self.0 {
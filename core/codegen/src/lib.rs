@@ -162,6 +162,37 @@ macro_rules! route_attribute {
         /// }
         /// ```
         ///
+        /// Routes for `CONNECT` requests, which ask for a tunnel, can only be
+        /// declared via `#[route]`. Such requests are routed as if their URI
+        /// were `/`.
+        ///
+        /// Routes for extension methods, such as WebDAV's `PROPFIND`, are also
+        /// declared via `#[route]`. The method is written in uppercase:
+        ///
+        /// ```rust
+        /// # #![feature(proc_macro_hygiene, decl_macro)]
+        /// # #[macro_use] extern crate rocket;
+        /// #
+        /// #[route(PROPFIND, path = "/files")]
+        /// fn properties() -> &'static str {
+        ///     "<multistatus xmlns='DAV:'/>"
+        /// }
+        /// ```
+        ///
+        /// Routes can be restricted to requests for a given host with `host`.
+        /// Host labels may be dynamic: `*` matches any one label, while a
+        /// `SINGLE_PARAM` also passes the label to the function:
+        ///
+        /// ```rust
+        /// # #![feature(proc_macro_hygiene, decl_macro)]
+        /// # #[macro_use] extern crate rocket;
+        /// #
+        /// #[get("/", host = "<tenant>.tenants.example.com")]
+        /// fn tenant(tenant: String) -> String {
+        ///     format!("Hello, {}!", tenant)
+        /// }
+        /// ```
+        ///
         /// [`#[delete]`]: attr.delete.html
         /// [`#[get]`]: attr.get.html
         /// [`#[head]`]: attr.head.html
@@ -178,7 +209,7 @@ macro_rules! route_attribute {
         /// ```text
         /// route := '"' path ('?' query)? '"' (',' parameter)*
         ///
-        /// path := ('/' segment)*
+        /// path := ('/' (segment | CONSTRAINED_PARAM))*
         ///
         /// query := segment ('&' segment)*
         ///
@@ -189,11 +220,24 @@ macro_rules! route_attribute {
         /// parameter := 'rank' '=' INTEGER
         ///            | 'format' '=' '"' MEDIA_TYPE '"'
         ///            | 'data' '=' '"' SINGLE_PARAM '"'
+        ///            | 'host' '=' '"' host '"'
+        ///
+        /// host := label ('.' label)*
+        ///
+        /// label := HOST_LABEL
+        ///        | SINGLE_PARAM
+        ///        | '*'
         ///
         /// SINGLE_PARAM := '<' IDENT '>'
         /// MULTI_PARAM := '<' IDENT '..>'
+        /// CONSTRAINED_PARAM := '<' IDENT ':' ' '* CONSTRAINT '>'
+        ///
+        /// CONSTRAINT := 'int' | 'uint' | 'alpha' | 'alnum'
+        ///             | 'regex(' REGEX ')'
         ///
         /// URI_SEG := valid, non-percent-encoded HTTP URI segment
+        /// HOST_LABEL := letters, digits, and '-'
+        /// REGEX := regular expression without '/', '?', '#', '<', or '>'
         /// MEDIA_TYPE := valid HTTP media type or known shorthand
         ///
         /// INTEGER := unsigned integer, as defined by Rust
@@ -204,6 +248,9 @@ macro_rules! route_attribute {
         ///
         /// ```text
         /// generic-route := METHOD ',' 'path' '=' route
+        ///
+        /// METHOD := standard HTTP method, except `TRACE`, or an uppercase
+        ///           extension method that is a valid identifier
         /// ```
         ///
         /// # Typing Requirements
@@ -234,10 +281,14 @@ macro_rules! route_attribute {
         /// |----------|-------------|-------------------|
         /// | path     | `<ident>`   | [`FromParam`]     |
         /// | path     | `<ident..>` | [`FromSegments`]  |
+        /// | host     | `<ident>`   | [`FromParam`]     |
         /// | query    | `<ident>`   | [`FromFormValue`] |
         /// | query    | `<ident..>` | [`FromQuery`]     |
         /// | data     | `<ident>`   | [`FromData`]      |
         ///
+        /// A `CONSTRAINED_PARAM` is treated like a path `<ident>`, but the route
+        /// only matches requests whose segment satisfies the constraint.
+        ///
         /// The type of each function argument that _does not_ have a
         /// corresponding dynamic parameter is required to implement the
         /// [`FromRequest`] trait.
@@ -271,11 +322,11 @@ macro_rules! route_attribute {
         ///            `Failure`. See [`FromRequest` Outcomes] for further
         ///            detail.
         ///
-        ///         2. Path and query parameters from left to right as declared
-        ///            in the function argument list.
+        ///         2. Path, host, and query parameters from left to right as
+        ///            declared in the function argument list.
         ///
-        ///            If a path or query parameter guard fails, the request is
-        ///            forwarded.
+        ///            If a path, host, or query parameter guard fails, the
+        ///            request is forwarded.
         ///
         ///         3. Data parameter, if any.
         ///
@@ -290,8 +341,8 @@ macro_rules! route_attribute {
         ///   2. A static structure used by [`routes!`] to generate a [`Route`].
         ///
         ///      The static structure (and resulting [`Route`]) is populated
-        ///      with the name (the function's name), path, query, rank, and
-        ///      format from the route attribute. The handler is set to the
+        ///      with the name (the function's name), path, query, rank,
+        ///      format, and host from the route attribute. The handler is set to the
         ///      generated handler.
         ///
         ///   3. A macro used by [`uri!`] to type-check and generate an
@@ -14,7 +14,7 @@ use rocket::http::ext::Normalize;
 use rocket::local::Client;
 use rocket::data::{self, Data, FromDataSimple};
 use rocket::request::Form;
-use rocket::http::{Status, RawStr, ContentType};
+use rocket::http::{Status, RawStr, ContentType, Method};
 
 // Use all of the code generation avaiable at once.
 
@@ -74,6 +74,31 @@ fn post2(
 fn test_unused_params(_unused_param: String, _unused_query: String, _unused_data: Data) {
 }
 
+#[route(PROPFIND, path = "/<path..>", data = "<simple>")]
+fn propfind(path: PathBuf, simple: Simple) -> String {
+    format!("{} ({})", path.normalized_str(), simple.0)
+}
+
+#[route(PURGE, path = "/<path..>")]
+fn purge(path: PathBuf) -> String {
+    format!("purged {}", path.normalized_str())
+}
+
+#[get("/user/<id: int>")]
+fn user_by_id(id: isize) -> String {
+    format!("user #{}", id)
+}
+
+#[get("/user/<name>")]
+fn user_by_name(name: String) -> String {
+    format!("user {}", name)
+}
+
+#[get("/tag/<tag: regex([a-z]{2,4})>/<n: uint>")]
+fn tag(tag: String, n: usize) -> String {
+    format!("{} x{}", tag, n)
+}
+
 #[test]
 fn test_full_route() {
     let rocket = rocket::ignite()
@@ -121,3 +146,44 @@ fn test_full_route() {
     assert_eq!(response.body_string().unwrap(), format!("({}, {}, {}, {}, {}, {}) ({})",
             sky, name, "A A", "inside", path, simple, expected_uri));
 }
+
+#[test]
+fn test_extension_method_routes() {
+    let rocket = rocket::ignite().mount("/", routes![propfind, purge]);
+    let client = Client::new(rocket).unwrap();
+
+    let propfind: Method = "PROPFIND".parse().unwrap();
+    let mut response = client.req(propfind, "/a/b").body("<prop/>").dispatch();
+    assert_eq!(response.body_string().unwrap(), "a/b (<prop/>)");
+
+    let mut response = client.req("purge".parse().unwrap(), "/c").dispatch();
+    assert_eq!(response.body_string().unwrap(), "purged c");
+
+    let response = client.req("MKCOL".parse().unwrap(), "/c").dispatch();
+    assert_eq!(response.status(), Status::MethodNotAllowed);
+    assert_eq!(response.headers().get_one("Allow"), Some("OPTIONS, PROPFIND, PURGE"));
+
+    let response = client.get("/c").dispatch();
+    assert_eq!(response.status(), Status::MethodNotAllowed);
+}
+
+#[test]
+fn test_constrained_routes() {
+    let rocket = rocket::ignite().mount("/", routes![user_by_id, user_by_name, tag]);
+    let client = Client::new(rocket).unwrap();
+
+    let mut response = client.get("/user/-12").dispatch();
+    assert_eq!(response.body_string().unwrap(), "user #-12");
+
+    let mut response = client.get("/user/bob").dispatch();
+    assert_eq!(response.body_string().unwrap(), "user bob");
+
+    let mut response = client.get("/tag/rust/3").dispatch();
+    assert_eq!(response.body_string().unwrap(), "rust x3");
+
+    let response = client.get("/tag/rustacean/3").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+
+    let response = client.get("/tag/rust/-3").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+}
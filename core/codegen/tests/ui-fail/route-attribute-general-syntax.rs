@@ -107,11 +107,11 @@ fn e12() {}
 
 // Check that route methods are validated properly.
 
-#[route(CONNECT, "/")] //~ ERROR invalid HTTP method for route
+#[route(TRACE, "/")] //~ ERROR invalid HTTP method for route
 //~^ HELP method must be one of
 fn f0() {}
 
-#[route(FIX, "/")] //~ ERROR invalid HTTP method
+#[route(Fix, "/")] //~ ERROR invalid HTTP method
 //~^ HELP method must be one of
 fn f1() {}
 
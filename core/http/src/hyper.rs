@@ -18,6 +18,8 @@ extern crate hyper;
 #[doc(hidden)] pub use self::hyper::error::Error;
 #[doc(hidden)] pub use self::hyper::uri::RequestUri;
 #[doc(hidden)] pub use self::hyper::http::h1;
+#[doc(hidden)] pub use self::hyper::http::should_keep_alive;
+#[doc(hidden)] pub use self::hyper::version::HttpVersion;
 #[doc(hidden)] pub use self::hyper::buffer;
 
 pub use self::hyper::mime;
@@ -20,6 +20,7 @@ extern crate time;
 extern crate indexmap;
 extern crate state;
 extern crate unicode_xid;
+extern crate regex;
 
 pub mod hyper;
 pub mod uri;
@@ -38,6 +39,7 @@ mod docify;
 mod known_media_types;
 mod cookies;
 mod method;
+mod protocol;
 mod media_type;
 mod content_type;
 mod status;
@@ -62,7 +64,8 @@ pub mod private {
     pub use cookies::{Key, CookieJar};
 }
 
-pub use method::Method;
+pub use method::{Method, ExtensionMethod};
+pub use protocol::Protocol;
 pub use content_type::ContentType;
 pub use accept::{Accept, QMediaType};
 pub use status::{Status, StatusClass};
@@ -1,13 +1,32 @@
 use std::fmt;
 use std::str::FromStr;
+use std::sync::Mutex;
+use std::collections::HashSet;
+
+use state::Storage;
 
 use {hyper, uncased::uncased_eq};
+use parse::checkers::is_valid_token;
 
 use self::Method::*;
 
-// TODO: Support non-standard methods, here and in codegen.
-
 /// Representation of HTTP methods.
+///
+/// Besides the standard methods, `Method` represents non-standard, _extension_
+/// methods such as WebDAV's `PROPFIND` or the `PURGE` method used by caches.
+/// An extension method is created by parsing its name:
+///
+/// ```rust
+/// # extern crate rocket;
+/// use rocket::http::Method;
+///
+/// let method: Method = "PROPFIND".parse().unwrap();
+/// assert_eq!(method.as_str(), "PROPFIND");
+/// assert!(method.is_extension());
+///
+/// let method: Method = "get".parse().unwrap();
+/// assert_eq!(method, Method::Get);
+/// ```
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum Method {
     Get,
@@ -18,7 +37,99 @@ pub enum Method {
     Head,
     Trace,
     Connect,
-    Patch
+    Patch,
+    Extension(ExtensionMethod)
+}
+
+/// The maximum length of the name of an extension method.
+const MAX_EXTENSION_LEN: usize = 31;
+
+/// The name of an extension method: an HTTP method other than the standard
+/// ones.
+///
+/// An `ExtensionMethod` is obtained by parsing a [`Method`] from a name that
+/// isn't that of a standard method. Names are at most 31 characters long,
+/// consist of the characters allowed in an HTTP token, and are stored in
+/// uppercase.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExtensionMethod {
+    len: u8,
+    name: [u8; MAX_EXTENSION_LEN],
+}
+
+impl ExtensionMethod {
+    fn new(name: &str) -> Option<ExtensionMethod> {
+        let valid = !name.is_empty() && name.len() <= MAX_EXTENSION_LEN;
+        if !valid || !name.chars().all(is_valid_token) {
+            return None;
+        }
+
+        let mut bytes = [0; MAX_EXTENSION_LEN];
+        bytes[..name.len()].copy_from_slice(name.as_bytes());
+        bytes.make_ascii_uppercase();
+        Some(ExtensionMethod { len: name.len() as u8, name: bytes })
+    }
+
+    /// WARNING: This is unstable! Do not use this method outside of Rocket!
+    #[doc(hidden)]
+    pub const fn _new_unchecked(len: u8, name: [u8; MAX_EXTENSION_LEN]) -> ExtensionMethod {
+        ExtensionMethod { len, name }
+    }
+
+    /// WARNING: This is unstable! Do not use this method outside of Rocket!
+    #[doc(hidden)]
+    pub fn _raw(&self) -> (u8, [u8; MAX_EXTENSION_LEN]) {
+        (self.len, self.name)
+    }
+
+    /// Returns the name of the extension method.
+    ///
+    /// Names are interned: the first call to `as_str()` for a given name
+    /// allocates a copy of it that lives for the remainder of the program.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// use rocket::http::Method;
+    ///
+    /// match "mkcol".parse::<Method>() {
+    ///     Ok(Method::Extension(method)) => assert_eq!(method.as_str(), "MKCOL"),
+    ///     _ => unreachable!("MKCOL is an extension method")
+    /// }
+    /// ```
+    pub fn as_str(&self) -> &'static str {
+        static NAMES: Storage<Mutex<HashSet<&'static str>>> = Storage::new();
+
+        let names = NAMES.get_or_set(|| Mutex::new(HashSet::new()));
+        let mut names = names.lock().expect("extension method names lock");
+        if let Some(&name) = names.get(self.name()) {
+            return name;
+        }
+
+        let name: &'static str = Box::leak(self.name().to_string().into_boxed_str());
+        names.insert(name);
+        name
+    }
+
+    #[inline]
+    fn name(&self) -> &str {
+        // The name was validated to be ASCII when `self` was created.
+        unsafe { ::std::str::from_utf8_unchecked(&self.name[..self.len as usize]) }
+    }
+}
+
+impl fmt::Debug for ExtensionMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.name().fmt(f)
+    }
+}
+
+impl fmt::Display for ExtensionMethod {
+    #[inline(always)]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.name().fmt(f)
+    }
 }
 
 impl Method {
@@ -35,7 +146,7 @@ impl Method {
             hyper::Method::Trace => Some(Trace),
             hyper::Method::Connect => Some(Connect),
             hyper::Method::Patch => Some(Patch),
-            hyper::Method::Extension(_) => None,
+            hyper::Method::Extension(ref name) => name.parse().ok(),
         }
     }
 
@@ -50,6 +161,9 @@ impl Method {
     ///
     ///   * `GET`, `HEAD`, `CONNECT`, `TRACE`, `OPTIONS`
     ///
+    /// Whether an extension method supports a payload depends on the method.
+    /// Because Rocket can't know, extension methods are assumed to support one.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -62,11 +176,30 @@ impl Method {
     #[inline]
     pub fn supports_payload(self) -> bool {
         match self {
-            Put | Post | Delete | Patch => true,
+            Put | Post | Delete | Patch | Extension(_) => true,
             Get | Head | Connect | Trace | Options => false,
         }
     }
 
+    /// Returns `true` if `self` is a non-standard, extension method.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// use rocket::http::Method;
+    ///
+    /// assert_eq!(Method::Get.is_extension(), false);
+    /// assert_eq!("PURGE".parse::<Method>().unwrap().is_extension(), true);
+    /// ```
+    #[inline]
+    pub fn is_extension(self) -> bool {
+        match self {
+            Extension(_) => true,
+            _ => false
+        }
+    }
+
     /// Returns the string representation of `self`.
     ///
     /// # Example
@@ -76,6 +209,7 @@ impl Method {
     /// use rocket::http::Method;
     ///
     /// assert_eq!(Method::Get.as_str(), "GET");
+    /// assert_eq!("propfind".parse::<Method>().unwrap().as_str(), "PROPFIND");
     /// ```
     #[inline]
     pub fn as_str(self) -> &'static str {
@@ -89,6 +223,7 @@ impl Method {
             Trace => "TRACE",
             Connect => "CONNECT",
             Patch => "PATCH",
+            Extension(method) => method.as_str(),
         }
     }
 }
@@ -98,6 +233,7 @@ impl FromStr for Method {
 
     // According to the RFC, method names are case-sensitive. But some old
     // clients don't follow this, so we just do a case-insensitive match here.
+    // For the same reason, extension methods are normalized to uppercase.
     fn from_str(s: &str) -> Result<Method, ()> {
         match s {
             x if uncased_eq(x, Get.as_str()) => Ok(Get),
@@ -109,7 +245,7 @@ impl FromStr for Method {
             x if uncased_eq(x, Trace.as_str()) => Ok(Trace),
             x if uncased_eq(x, Connect.as_str()) => Ok(Connect),
             x if uncased_eq(x, Patch.as_str()) => Ok(Patch),
-            _ => Err(()),
+            x => ExtensionMethod::new(x).map(Extension).ok_or(()),
         }
     }
 }
@@ -117,6 +253,24 @@ impl FromStr for Method {
 impl fmt::Display for Method {
     #[inline(always)]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.as_str().fmt(f)
+        match *self {
+            Extension(ref method) => method.fmt(f),
+            method => method.as_str().fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use super::Method;
+
+    #[test]
+    fn extension_names_are_interned() {
+        let name: &'static str = "propfind".parse::<Method>().unwrap().as_str();
+        assert_eq!(name, "PROPFIND");
+        assert!(ptr::eq(name, "PROPFIND".parse::<Method>().unwrap().as_str()));
+        assert_eq!("mkcol".parse::<Method>().unwrap().to_string(), "MKCOL");
     }
 }
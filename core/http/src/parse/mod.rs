@@ -1,6 +1,6 @@
 mod media_type;
 mod accept;
-mod checkers;
+crate mod checkers;
 mod indexed;
 
 pub use self::media_type::*;
@@ -35,18 +35,34 @@ crate fn origin<'a>(input: &mut RawInput<'a>) -> Result<'a, Origin<'a>> {
 
 #[parser]
 crate fn rocket_route_origin<'a>(input: &mut RawInput<'a>) -> Result<'a, Origin<'a>> {
-    (peek(b'/')?, path_and_query(is_pchar_or_rchar)?).1
+    (peek(b'/')?, path_and_query(route_char_matcher())?).1
+}
+
+/// Returns a matcher for the characters of a route URI. Outside of a `<...>`,
+/// these are the same as in any other URI. Inside of one, where a constraint
+/// like `regex([a-z]+)` may be written, any printable ASCII character except
+/// `?`, `#`, and `<` is accepted, and a `>` closes the parameter.
+fn route_char_matcher() -> impl FnMut(u8) -> bool + Clone {
+    let mut in_param = false;
+    move |c| match c {
+        b'>' if in_param => { in_param = false; true }
+        b'?' | b'#' | b'<' if in_param => false,
+        _ if in_param => c >= b' ' && c <= b'~',
+        b'<' => { in_param = true; true }
+        _ => is_pchar_or_rchar(c)
+    }
 }
 
 #[parser]
 fn path_and_query<'a, F>(input: &mut RawInput<'a>, is_good_char: F) -> Result<'a, Origin<'a>>
-    where F: Fn(u8) -> bool + Copy
+    where F: FnMut(u8) -> bool + Clone
 {
+    let mut is_query_char = is_good_char.clone();
     let path = take_while(is_good_char)?;
 
     // FIXME(rustc): We should be able to use `pear_try`, but rustc...is broken.
     let query = switch! {
-        eat(b'?') => Some(take_while(|c| is_good_char(c) || c == b'?')?),
+        eat(b'?') => Some(take_while(|c| is_query_char(c) || c == b'?')?),
         _ => None
     };
 
@@ -119,6 +119,20 @@ fn origin() {
     );
 }
 
+#[test]
+fn route_origin() {
+    let regex = "/<b: regex([a-z]{2}|x\\d)>";
+    let expected = Origin::new::<_, &str>("/<id: int>", None);
+    assert_eq!(route_origin_from_str("/<id: int>").unwrap(), expected);
+
+    let route = format!("{}?<c>", regex);
+    assert_eq!(route_origin_from_str(&route).unwrap(), Origin::new(regex, Some("<c>")));
+
+    assert!(route_origin_from_str("/a b").is_err());
+    assert!(route_origin_from_str("/a/{b}").is_err());
+    assert!(route_origin_from_str("/a?b c").is_err());
+}
+
 #[test]
 fn authority() {
     assert_parse_eq!(
@@ -132,6 +146,8 @@ fn authority() {
         "google.com:8000" => Authority::new(None, Raw("google.com"), Some(8000)),
         "[1::2::3]:80" => Authority::new(None, Bracketed("1::2::3"), Some(80)),
     );
+
+    assert_no_parse!("a{b}", "a|b", "a^b", "a\\b");
 }
 
 #[test]
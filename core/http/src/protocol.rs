@@ -0,0 +1,65 @@
+use std::fmt;
+
+use hyper;
+
+use self::Protocol::*;
+
+/// The version of the HTTP protocol a request was received over.
+///
+/// # Example
+///
+/// ```rust
+/// # extern crate rocket;
+/// use rocket::http::Protocol;
+///
+/// assert_eq!(Protocol::Http2.as_str(), "HTTP/2");
+/// assert!(Protocol::Http1_1 < Protocol::Http2);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Protocol {
+    /// HTTP/1.0.
+    Http1_0,
+    /// HTTP/1.1.
+    Http1_1,
+    /// HTTP/2, negotiated via ALPN or with prior knowledge.
+    Http2,
+}
+
+impl Protocol {
+    /// WARNING: This is unstable! Do not use this method outside of Rocket!
+    #[doc(hidden)]
+    pub fn from_hyp(version: hyper::HttpVersion) -> Protocol {
+        match version {
+            hyper::HttpVersion::Http09 | hyper::HttpVersion::Http10 => Http1_0,
+            hyper::HttpVersion::Http11 => Http1_1,
+            hyper::HttpVersion::Http20 => Http2,
+        }
+    }
+
+    /// Returns the string representation of `self` as it appears in a request
+    /// line.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// use rocket::http::Protocol;
+    ///
+    /// assert_eq!(Protocol::Http1_1.as_str(), "HTTP/1.1");
+    /// ```
+    #[inline]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Http1_0 => "HTTP/1.0",
+            Http1_1 => "HTTP/1.1",
+            Http2 => "HTTP/2",
+        }
+    }
+}
+
+impl fmt::Display for Protocol {
+    #[inline(always)]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
@@ -1,6 +1,7 @@
 use std::borrow::Cow;
 use std::marker::PhantomData;
 use unicode_xid::UnicodeXID;
+use regex::Regex;
 
 use ext::IntoOwned;
 use uri::{Origin, UriPart, Path, Query};
@@ -19,16 +20,85 @@ pub enum Kind {
 pub enum Source {
     Path,
     Query,
+    Host,
     Data,
     Unknown,
 }
 
+/// A constraint on the values that a dynamic path segment, written as
+/// `<name: constraint>`, matches.
+#[derive(Debug, Clone)]
+pub enum Constraint {
+    /// `int`: a decimal integer with an optional sign.
+    Int,
+    /// `uint`: a decimal integer without a sign.
+    Uint,
+    /// `alpha`: one or more ASCII letters.
+    Alpha,
+    /// `alnum`: one or more ASCII letters or digits.
+    Alnum,
+    /// `regex(re)`: a value that the regular expression `re` matches fully.
+    Regex(Regex),
+}
+
+impl Constraint {
+    /// Parses a constraint as written after the `:` in a dynamic segment.
+    /// Returns `None` if `string` isn't a known constraint or is a `regex`
+    /// constraint with an invalid regular expression.
+    pub fn parse(string: &str) -> Option<Constraint> {
+        match string {
+            "int" => Some(Constraint::Int),
+            "uint" => Some(Constraint::Uint),
+            "alpha" => Some(Constraint::Alpha),
+            "alnum" => Some(Constraint::Alnum),
+            _ if string.starts_with("regex(") && string.ends_with(')') => {
+                let regex = &string["regex(".len()..(string.len() - 1)];
+                Regex::new(&format!("^(?:{})$", regex)).ok().map(Constraint::Regex)
+            }
+            _ => None
+        }
+    }
+
+    /// Returns `true` if the percent-decoded segment `value` satisfies this
+    /// constraint.
+    pub fn is_satisfied_by(&self, value: &str) -> bool {
+        let is_digit = |b: u8| b.is_ascii_digit();
+        match *self {
+            Constraint::Int => {
+                let digits = value.trim_start_matches(|c| c == '+' || c == '-');
+                value.len() - digits.len() <= 1
+                    && !digits.is_empty() && digits.bytes().all(is_digit)
+            }
+            Constraint::Uint => !value.is_empty() && value.bytes().all(is_digit),
+            Constraint::Alpha => {
+                !value.is_empty() && value.bytes().all(|b| b.is_ascii_alphabetic())
+            }
+            Constraint::Alnum => {
+                !value.is_empty() && value.bytes().all(|b| b.is_ascii_alphanumeric())
+            }
+            Constraint::Regex(ref regex) => regex.is_match(value),
+        }
+    }
+
+    /// Returns `true` if no value satisfies both `self` and `other`. Regular
+    /// expressions are never considered to be disjoint from other constraints.
+    pub fn is_disjoint_from(&self, other: &Constraint) -> bool {
+        use self::Constraint::*;
+
+        match (self, other) {
+            (Int, Alpha) | (Alpha, Int) | (Uint, Alpha) | (Alpha, Uint) => true,
+            _ => false
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RouteSegment<'a, P: UriPart> {
     pub string: Cow<'a, str>,
     pub kind: Kind,
     pub name: Cow<'a, str>,
     pub index: Option<usize>,
+    pub constraint: Option<Constraint>,
     _part: PhantomData<P>,
 }
 
@@ -42,11 +112,35 @@ impl<'a, P: UriPart + 'static> IntoOwned for RouteSegment<'a, P> {
             kind: self.kind,
             name: IntoOwned::into_owned(self.name),
             index: self.index,
+            constraint: self.constraint,
             _part: PhantomData
         }
     }
 }
 
+/// A single, dot-separated label of a route's host pattern.
+#[derive(Debug, Clone)]
+pub struct HostSegment<'a> {
+    pub string: Cow<'a, str>,
+    pub kind: Kind,
+    pub name: Cow<'a, str>,
+    pub index: Option<usize>,
+}
+
+impl<'a> IntoOwned for HostSegment<'a> {
+    type Owned = HostSegment<'static>;
+
+    #[inline]
+    fn into_owned(self) -> Self::Owned {
+        HostSegment {
+            string: IntoOwned::into_owned(self.string),
+            kind: self.kind,
+            name: IntoOwned::into_owned(self.name),
+            index: self.index,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub enum Error<'a> {
     Empty,
@@ -55,11 +149,15 @@ pub enum Error<'a> {
     MissingClose,
     Malformed,
     Uri,
-    Trailing(&'a str)
+    Trailing(&'a str),
+    BadConstraint(&'a str),
+    Constrained,
 }
 
 pub type SResult<'a, P> = Result<RouteSegment<'a, P>, (&'a str, Error<'a>)>;
 
+pub type HResult<'a> = Result<HostSegment<'a>, (&'a str, Error<'a>)>;
+
 #[inline]
 fn is_ident_start(c: char) -> bool {
     ('a' <= c && c <= 'z')
@@ -93,6 +191,16 @@ impl<'a, P: UriPart> RouteSegment<'a, P> {
         if segment.starts_with('<') && segment.ends_with('>') {
             let mut kind = Kind::Single;
             let mut name = &segment[1..(segment.len() - 1)];
+
+            // A constraint follows the first `:` after a valid identifier.
+            let mut constraint_str = None;
+            if let Some(i) = name.find(':') {
+                if is_valid_ident(name[..i].trim_end_matches("..")) {
+                    constraint_str = Some(name[(i + 1)..].trim_start());
+                    name = &name[..i];
+                }
+            }
+
             if name.ends_with("..") {
                 kind = Kind::Multi;
                 name = &name[..(name.len() - 2)];
@@ -106,8 +214,16 @@ impl<'a, P: UriPart> RouteSegment<'a, P> {
                 return Err(Ignored);
             }
 
-            let name = name.into();
-            return Ok(RouteSegment { string, name, kind, index, _part: PhantomData });
+            let constraint = match constraint_str {
+                Some(_) if kind == Kind::Multi || P::DELIMITER != '/' => {
+                    return Err(Constrained);
+                }
+                Some(string) => Some(Constraint::parse(string).ok_or(BadConstraint(string))?),
+                None => None
+            };
+
+            let (name, _part) = (name.into(), PhantomData);
+            return Ok(RouteSegment { string, name, kind, index, constraint, _part });
         } else if segment.is_empty() {
             return Err(Empty);
         } else if segment.starts_with('<') && segment.len() > 1
@@ -123,6 +239,7 @@ impl<'a, P: UriPart> RouteSegment<'a, P> {
             string, index,
             name: segment.into(),
             kind: Kind::Static,
+            constraint: None,
             _part: PhantomData
         })
     }
@@ -161,3 +278,46 @@ impl<'a> RouteSegment<'a, Query> {
         uri.query().map(|q| Self::parse_many(q))
     }
 }
+
+impl<'a> HostSegment<'a> {
+    /// Parses a single label of a host pattern: a `<param>`, the anonymous
+    /// wildcard `*`, or a static label, which is lowercased.
+    pub fn parse_one(label: &'a str) -> Result<Self, Error> {
+        if label == "*" {
+            let (string, name) = (label.into(), label.into());
+            return Ok(HostSegment { string, name, kind: Kind::Single, index: None });
+        }
+
+        let segment = <RouteSegment<Path>>::parse_one(label)?;
+        let (string, name) = match segment.kind {
+            Kind::Multi => return Err(Malformed),
+            Kind::Single if segment.constraint.is_some() => return Err(Constrained),
+            Kind::Single => (segment.string, segment.name),
+            Kind::Static => {
+                if !label.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-') {
+                    return Err(Uri);
+                }
+
+                let string: Cow<str> = match label.bytes().any(|b| b.is_ascii_uppercase()) {
+                    true => label.to_ascii_lowercase().into(),
+                    false => label.into()
+                };
+
+                (string.clone(), string)
+            }
+        };
+
+        Ok(HostSegment { string, name, kind: segment.kind, index: None })
+    }
+
+    pub fn parse_many(host: &'a str) -> impl Iterator<Item = HResult<'a>> {
+        host.split('.')
+            .filter(|s| !s.is_empty())
+            .enumerate()
+            .map(|(i, label)| {
+                let mut parsed = Self::parse_one(label).map_err(|e| (label, e))?;
+                parsed.index = Some(i);
+                Ok(parsed)
+            })
+    }
+}
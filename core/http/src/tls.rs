@@ -1,5 +1,262 @@
 extern crate rustls;
 extern crate hyper_sync_rustls;
 
-pub use self::hyper_sync_rustls::{util, WrappedStream, ServerSession, TlsServer};
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, Shutdown};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+#[cfg(unix)] use std::os::unix::io::{AsRawFd, RawFd};
+
+use hyper::net::{HttpStream, NetworkStream};
+
+use self::rustls::{ServerConfig, ServerSession, Session, NoClientAuth};
+
+pub use self::hyper_sync_rustls::util;
 pub use self::rustls::{Certificate, PrivateKey};
+
+/// Accepts TLS connections, negotiating an application protocol via ALPN.
+pub struct TlsServer {
+    config: Arc<ServerConfig>,
+}
+
+impl TlsServer {
+    /// Creates a server that authenticates itself with `certs` and `key` and
+    /// offers `protocols`, in order of preference, to clients that use ALPN.
+    pub fn new(
+        certs: Vec<Certificate>,
+        key: PrivateKey,
+        protocols: &[&str]
+    ) -> Result<TlsServer, rustls::TLSError> {
+        let mut config = ServerConfig::new(NoClientAuth::new());
+        config.set_single_cert(certs, key)?;
+
+        let protocols: Vec<Vec<u8>> = protocols.iter().map(|p| p.as_bytes().to_vec()).collect();
+        config.set_protocols(&protocols);
+        Ok(TlsServer { config: Arc::new(config) })
+    }
+
+    /// Wraps `stream` in a TLS session. The handshake is performed lazily, on
+    /// first use of the returned stream.
+    pub fn accept(&self, stream: HttpStream) -> TlsStream {
+        TlsStream {
+            socket: stream,
+            session: Arc::new(Mutex::new(ServerSession::new(&self.config))),
+            reading: Arc::new(Mutex::new(())),
+        }
+    }
+}
+
+/// A server-side TLS stream. Clones refer to the same underlying session.
+///
+/// The session is only locked while data is passed through it, never while
+/// waiting on the socket, so a clone can write while another is blocked in a
+/// read.
+#[derive(Clone)]
+pub struct TlsStream {
+    socket: HttpStream,
+    session: Arc<Mutex<ServerSession>>,
+    // Held by the one clone that is reading from the socket at any one time.
+    reading: Arc<Mutex<()>>,
+}
+
+/// The size of the buffer that encrypted data is read into.
+const READ_BUF_SIZE: usize = 4096;
+
+impl TlsStream {
+    #[inline]
+    fn lock(&self) -> MutexGuard<ServerSession> {
+        self.session.lock().expect("tls lock")
+    }
+
+    /// Completes the handshake, if necessary, and returns the application
+    /// protocol that was negotiated via ALPN, if any.
+    pub fn alpn_protocol(&self) -> io::Result<Option<String>> {
+        let _reading = self.reading.lock().expect("tls read lock");
+        let mut session = self.lock();
+        while session.is_handshaking() {
+            session.complete_io(&mut &self.socket.0)?;
+        }
+
+        let protocol = session.get_alpn_protocol();
+        Ok(protocol.map(|p| String::from_utf8_lossy(p).into_owned()))
+    }
+
+    /// Returns `true` if the session holds neither received data that is yet
+    /// to be read nor written data that is yet to be sent. Readiness of the
+    /// underlying socket then reflects readiness of the stream.
+    pub fn is_drained(&self) -> bool {
+        let session = self.lock();
+        session.wants_read() && !session.wants_write()
+    }
+
+    // Passes `data`, received from the client, to `session` and sends
+    // whatever the session has to say in return, such as handshake messages.
+    fn receive(&self, session: &mut ServerSession, mut data: &[u8]) -> io::Result<()> {
+        while !data.is_empty() {
+            if session.read_tls(&mut data)? == 0 {
+                let msg = "TLS message exceeds buffer";
+                return Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+            }
+
+            if let Err(e) = session.process_new_packets() {
+                // Let the client know what went wrong, if we can.
+                let _ = self.send(session);
+                return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+            }
+        }
+
+        self.send(session)
+    }
+
+    // Writes all of the data that `session` has ready for the client.
+    fn send(&self, session: &mut ServerSession) -> io::Result<()> {
+        while session.wants_write() {
+            if session.write_tls(&mut &self.socket.0)? == 0 {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+impl AsRawFd for TlsStream {
+    #[inline]
+    fn as_raw_fd(&self) -> RawFd {
+        self.socket.0.as_raw_fd()
+    }
+}
+
+impl Read for TlsStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let _reading = self.reading.lock().expect("tls read lock");
+        let mut received = [0; READ_BUF_SIZE];
+        loop {
+            {
+                let mut session = self.lock();
+                let n = session.read(buf)?;
+                if n > 0 || buf.is_empty() {
+                    return Ok(n);
+                }
+            }
+
+            // Wait for more data without holding up writers.
+            let n = (&self.socket.0).read(&mut received)?;
+            if n == 0 {
+                return Ok(0);
+            }
+
+            let mut session = self.lock();
+            self.receive(&mut session, &received[..n])?;
+        }
+    }
+}
+
+impl Write for TlsStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut session = self.lock();
+        let n = session.write(buf)?;
+        self.send(&mut session)?;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut session = self.lock();
+        session.flush()?;
+        self.send(&mut session)
+    }
+}
+
+impl NetworkStream for TlsStream {
+    #[inline]
+    fn peer_addr(&mut self) -> io::Result<SocketAddr> {
+        self.socket.peer_addr()
+    }
+
+    #[inline]
+    fn set_read_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        self.socket.set_read_timeout(dur)
+    }
+
+    #[inline]
+    fn set_write_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        self.socket.set_write_timeout(dur)
+    }
+
+    fn close(&mut self, how: Shutdown) -> io::Result<()> {
+        // Let the client know we're done writing before the socket goes away.
+        if how != Shutdown::Read {
+            let mut session = self.lock();
+            session.send_close_notify();
+            self.send(&mut session)?;
+        }
+
+        self.socket.close(how)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate webpki;
+
+    use std::fs::File;
+    use std::io::{BufReader, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use hyper::net::{HttpStream, NetworkStream};
+
+    use super::{TlsServer, util};
+    use super::rustls::{ClientConfig, ClientSession, StreamOwned};
+
+    // The certificates of the TLS example.
+    macro_rules! private {
+        ($file:expr) => (
+            concat!(env!("CARGO_MANIFEST_DIR"), "/../../examples/tls/private/", $file)
+        )
+    }
+
+    #[test]
+    fn clones_write_while_another_reads() {
+        let certs = util::load_certs(private!("cert.pem")).unwrap();
+        let key = util::load_private_key(private!("key.pem")).unwrap();
+        let server = TlsServer::new(certs, key, &["http/1.1"]).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let client = thread::spawn(move || {
+            let mut config = ClientConfig::new();
+            let mut ca = BufReader::new(File::open(private!("ca_cert.pem")).unwrap());
+            config.root_store.add_pem_file(&mut ca).unwrap();
+            let name = webpki::DNSNameRef::try_from_ascii_str("localhost").unwrap();
+            let session = ClientSession::new(&Arc::new(config), name);
+            let mut stream = StreamOwned::new(session, TcpStream::connect(addr).unwrap());
+
+            // Only answer once the server has written while reading.
+            let mut greeting = [0; 5];
+            stream.read_exact(&mut greeting).unwrap();
+            assert_eq!(&greeting, b"Hello");
+            stream.write_all(b"world").unwrap();
+        });
+
+        let mut stream = server.accept(HttpStream(listener.accept().unwrap().0));
+        stream.alpn_protocol().unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+
+        let mut reader = stream.clone();
+        let reading = thread::spawn(move || {
+            let mut received = [0; 5];
+            reader.read_exact(&mut received).map(|_| received)
+        });
+
+        thread::sleep(Duration::from_millis(100));
+        stream.write_all(b"Hello").unwrap();
+        stream.flush().unwrap();
+
+        assert_eq!(&reading.join().unwrap().unwrap(), b"world");
+        client.join().unwrap();
+    }
+}
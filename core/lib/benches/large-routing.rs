@@ -0,0 +1,134 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+extern crate rocket;
+
+use rocket::{Request, Data, Route};
+use rocket::config::{Environment, Config, LoggingLevel};
+use rocket::handler::Outcome;
+use rocket::http::Method::*;
+
+fn ok<'r>(req: &'r Request, _: Data) -> Outcome<'r> {
+    Outcome::from(req, "ok")
+}
+
+// 800 routes: eight for each of 100 resources, as a large API might have.
+fn routes() -> Vec<Route> {
+    let mut routes = vec![];
+    for i in 0..100 {
+        routes.push(Route::new(Get, format!("/api/r{}", i), ok));
+        routes.push(Route::new(Post, format!("/api/r{}", i), ok));
+        routes.push(Route::new(Get, format!("/api/r{}/<id>", i), ok));
+        routes.push(Route::new(Put, format!("/api/r{}/<id>", i), ok));
+        routes.push(Route::new(Delete, format!("/api/r{}/<id>", i), ok));
+        routes.push(Route::new(Get, format!("/api/r{}/<id>/items", i), ok));
+        routes.push(Route::new(Get, format!("/api/r{}/<id>/items/<item>", i), ok));
+        routes.push(Route::new(Get, format!("/static/r{}/<path..>", i), ok));
+    }
+
+    routes
+}
+
+fn rocket() -> rocket::Rocket {
+    let config = Config::build(Environment::Production).log_level(LoggingLevel::Off);
+    rocket::custom(config.unwrap()).mount("/", routes())
+}
+
+mod benches {
+    extern crate test;
+
+    use super::rocket;
+    use self::test::Bencher;
+    use rocket::local::Client;
+
+    #[bench]
+    fn bench_launch_checks(b: &mut Bencher) {
+        // Launching checks all of the routes for collisions.
+        b.iter(|| Client::new(rocket()).unwrap());
+    }
+
+    #[bench]
+    fn bench_first_and_last_route(b: &mut Bencher) {
+        let client = Client::new(rocket()).unwrap();
+
+        let mut requests = vec![];
+        requests.push(client.get("/api/r0"));
+        requests.push(client.get("/api/r99"));
+        requests.push(client.delete("/api/r0/10"));
+        requests.push(client.delete("/api/r99/10"));
+
+        b.iter(|| {
+            for request in requests.iter_mut() {
+                request.mut_dispatch();
+            }
+        });
+    }
+
+    #[bench]
+    fn bench_dynamic_routes(b: &mut Bencher) {
+        let client = Client::new(rocket()).unwrap();
+
+        let mut requests = vec![];
+        requests.push(client.get("/api/r50/10/items"));
+        requests.push(client.get("/api/r50/10/items/20"));
+        requests.push(client.get("/static/r50/css/site.css"));
+
+        b.iter(|| {
+            for request in requests.iter_mut() {
+                request.mut_dispatch();
+            }
+        });
+    }
+
+    #[bench]
+    fn bench_not_found(b: &mut Bencher) {
+        let client = Client::new(rocket()).unwrap();
+
+        let mut requests = vec![];
+        requests.push(client.get("/api/r100"));
+        requests.push(client.get("/api/r50/10/other"));
+
+        b.iter(|| {
+            for request in requests.iter_mut() {
+                request.mut_dispatch();
+            }
+        });
+    }
+
+    #[bench]
+    fn bench_all_routes(b: &mut Bencher) {
+        let client = Client::new(rocket()).unwrap();
+
+        let mut requests = vec![];
+        for route in client.rocket().routes() {
+            let request = client.req(route.method, route.uri.path());
+            requests.push(request);
+        }
+
+        b.iter(|| {
+            for request in requests.iter_mut() {
+                request.mut_dispatch();
+            }
+        });
+    }
+
+    // The baseline for `bench_all_routes`: only finds the matching routes,
+    // without dispatching, by checking every route in turn as routing used to.
+    #[bench]
+    fn bench_all_routes_linear_scan(b: &mut Bencher) {
+        let client = Client::new(rocket()).unwrap();
+        let routes: Vec<_> = client.rocket().routes().collect();
+
+        let mut requests = vec![];
+        for route in &routes {
+            let request = client.req(route.method, route.uri.path());
+            requests.push(request);
+        }
+
+        b.iter(|| {
+            for request in requests.iter() {
+                let request = request.inner();
+                test::black_box(routes.iter().filter(|r| r.matches(request)).count());
+            }
+        });
+    }
+}
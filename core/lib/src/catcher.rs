@@ -166,10 +166,33 @@ pub mod defaults {
     use super::Catcher;
 
     use std::collections::HashMap;
+    use std::io::Cursor;
 
-    use request::Request;
-    use response::{self, content, status, Responder};
-    use http::Status;
+    use request::{Request, BadRequestInfo, BadRequestError};
+    use response::{self, content, status, Responder, Response};
+    use http::{Status, ContentType};
+
+    /// The default bad request handler: responds with a generic 400 page, a
+    /// 408 page if the client was too slow to send the request head, or a 431
+    /// page if the request head was too large to parse.
+    pub fn bad_request(info: &BadRequestInfo) -> Response<'static> {
+        let page = match *info.error() {
+            BadRequestError::Timeout => error_page_template!(408, "Request Timeout",
+                "The server timed out waiting for the request."),
+            BadRequestError::HeadersTooLarge => error_page_template!(431,
+                "Request Header Fields Too Large", "The server is unwilling to process
+                the request because either an individual header field, or all the
+                header fields collectively, are too large."),
+            _ => error_page_template!(400, "Bad Request", "The request could not be
+                understood by the server due to malformed syntax."),
+        };
+
+        Response::build()
+            .status(info.error().status())
+            .header(ContentType::HTML)
+            .sized_body(Cursor::new(page))
+            .finalize()
+    }
 
     pub fn get() -> HashMap<u16, Catcher> {
         default_catchers! {
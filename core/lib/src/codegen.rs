@@ -19,6 +19,8 @@ pub struct StaticRouteInfo {
     pub handler: StaticHandler,
     /// The route's rank, if any.
     pub rank: Option<isize>,
+    /// The route's host pattern, if any.
+    pub host: Option<&'static str>,
 }
 
 /// Information generated by the `catch` attribute during codegen.
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use config::{Result, Config, Value, Environment, Limits, LoggingLevel};
+use super::custom_values::check_limits;
 
 /// Structure following the builder pattern for building `Config` structures.
 #[derive(Clone)]
@@ -14,14 +15,34 @@ pub struct ConfigBuilder {
     pub port: u16,
     /// The number of workers to run in parallel.
     pub workers: u16,
+    /// Maximum number of connections waiting for a worker or unbounded if 0.
+    pub max_pending: u32,
     /// Keep-alive timeout in seconds or disabled if 0.
     pub keep_alive: u32,
+    /// Header read timeout in seconds or disabled if 0.
+    pub header_read_timeout: u32,
+    /// Read timeout in seconds or disabled if 0.
+    pub read_timeout: u32,
+    /// Write timeout in seconds or disabled if 0.
+    pub write_timeout: u32,
+    /// Shutdown grace period in seconds.
+    pub shutdown_grace: u32,
+    /// Whether to serve on a socket inherited via `LISTEN_FDS`.
+    pub listen_fds: bool,
+    /// Whether to offer HTTP/2 via ALPN on TLS connections.
+    pub http2: bool,
+    /// Whether to accept HTTP/2 with prior knowledge on plaintext sockets.
+    pub h2c: bool,
+    /// Whether to respond with `405` when only the method doesn't match.
+    pub auto_405: bool,
     /// How much information to log.
     pub log_level: LoggingLevel,
     /// The secret key.
     pub secret_key: Option<String>,
     /// TLS configuration (path to certificates file, path to private key file).
     pub tls: Option<(String, String)>,
+    /// Additional endpoints (address, port, TLS certificates and key paths).
+    pub endpoints: Vec<(String, u16, Option<(String, String)>)>,
     /// Size limits.
     pub limits: Limits,
     /// Any extra parameters that aren't part of Rocket's config.
@@ -56,10 +77,20 @@ impl ConfigBuilder {
             address: config.address,
             port: config.port,
             workers: config.workers,
+            max_pending: config.max_pending.unwrap_or(0),
             keep_alive: config.keep_alive.unwrap_or(0),
+            header_read_timeout: config.header_read_timeout.unwrap_or(0),
+            read_timeout: config.read_timeout.unwrap_or(0),
+            write_timeout: config.write_timeout.unwrap_or(0),
+            shutdown_grace: config.shutdown_grace,
+            listen_fds: config.listen_fds,
+            http2: config.http2,
+            h2c: config.h2c,
+            auto_405: config.auto_405,
             log_level: config.log_level,
             secret_key: None,
             tls: None,
+            endpoints: vec![],
             limits: config.limits,
             extras: config.extras,
             root: None,
@@ -122,6 +153,26 @@ impl ConfigBuilder {
         self
     }
 
+    /// Sets the maximum number of connections waiting for a worker to
+    /// `max_pending`. If `max_pending` is `0`, the number is unbounded.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .max_pending(128)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.max_pending, Some(128));
+    /// ```
+    #[inline]
+    pub fn max_pending(mut self, max_pending: u32) -> Self {
+        self.max_pending = max_pending;
+        self
+    }
+
     /// Sets the keep-alive timeout to `timeout` seconds. If `timeout` is `0`,
     /// keep-alive is disabled.
     ///
@@ -148,6 +199,166 @@ impl ConfigBuilder {
         self
     }
 
+    /// Sets the header read timeout to `timeout` seconds. If `timeout` is `0`,
+    /// the timeout is disabled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .header_read_timeout(20)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.header_read_timeout, Some(20));
+    /// ```
+    #[inline]
+    pub fn header_read_timeout(mut self, timeout: u32) -> Self {
+        self.header_read_timeout = timeout;
+        self
+    }
+
+    /// Sets the read timeout to `timeout` seconds. If `timeout` is `0`, the
+    /// timeout is disabled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .read_timeout(30)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.read_timeout, Some(30));
+    /// ```
+    #[inline]
+    pub fn read_timeout(mut self, timeout: u32) -> Self {
+        self.read_timeout = timeout;
+        self
+    }
+
+    /// Sets the write timeout to `timeout` seconds. If `timeout` is `0`, the
+    /// timeout is disabled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .write_timeout(0)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.write_timeout, None);
+    /// ```
+    #[inline]
+    pub fn write_timeout(mut self, timeout: u32) -> Self {
+        self.write_timeout = timeout;
+        self
+    }
+
+    /// Sets the shutdown grace period to `grace` seconds. If `grace` is `0`,
+    /// Rocket does not wait for in-flight requests on shutdown.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .shutdown_grace(30)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.shutdown_grace, 30);
+    /// ```
+    #[inline]
+    pub fn shutdown_grace(mut self, grace: u32) -> Self {
+        self.shutdown_grace = grace;
+        self
+    }
+
+    /// Sets whether to serve on a listening socket inherited via `LISTEN_FDS`
+    /// in the configuration being built.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .listen_fds(true)
+    ///     .unwrap();
+    ///
+    /// assert!(config.listen_fds);
+    /// ```
+    #[inline]
+    pub fn listen_fds(mut self, enabled: bool) -> Self {
+        self.listen_fds = enabled;
+        self
+    }
+
+    /// Sets whether to offer HTTP/2 via ALPN on TLS connections in the
+    /// configuration being built.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .http2(true)
+    ///     .unwrap();
+    ///
+    /// assert!(config.http2);
+    /// ```
+    #[inline]
+    pub fn http2(mut self, enabled: bool) -> Self {
+        self.http2 = enabled;
+        self
+    }
+
+    /// Sets whether to accept HTTP/2 with prior knowledge on plaintext sockets
+    /// in the configuration being built.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .h2c(true)
+    ///     .unwrap();
+    ///
+    /// assert!(config.h2c);
+    /// ```
+    #[inline]
+    pub fn h2c(mut self, enabled: bool) -> Self {
+        self.h2c = enabled;
+        self
+    }
+
+    /// Sets whether to respond with `405 Method Not Allowed` when only the
+    /// method of a request doesn't match in the configuration being built.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .auto_405(false)
+    ///     .unwrap();
+    ///
+    /// assert!(!config.auto_405);
+    /// ```
+    #[inline]
+    pub fn auto_405(mut self, enabled: bool) -> Self {
+        self.auto_405 = enabled;
+        self
+    }
+
     /// Sets the `log_level` in the configuration being built.
     ///
     /// # Example
@@ -224,6 +435,57 @@ impl ConfigBuilder {
         self
     }
 
+    /// Adds an additional endpoint at `address` and `port` to serve on in the
+    /// configuration being built.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .address("0.0.0.0")
+    ///     .endpoint("::", 8000)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.endpoints()[0].address, "::");
+    /// ```
+    pub fn endpoint<A: Into<String>>(mut self, address: A, port: u16) -> Self {
+        self.endpoints.push((address.into(), port, None));
+        self
+    }
+
+    /// Adds an additional endpoint at `address` and `port` to serve on using
+    /// TLS in the configuration being built. Certificates and the private key
+    /// are read from `certs_path` and `key_path` as in
+    /// [`tls()`](ConfigBuilder::tls()).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let mut config = Config::build(Environment::Staging)
+    ///     .endpoint("0.0.0.0", 80)
+    ///     .tls_endpoint("0.0.0.0", 443, "/path/to/certs.pem", "/path/to/key.pem")
+    /// # ; /*
+    ///     .unwrap();
+    /// # */
+    /// ```
+    pub fn tls_endpoint<A, C, K>(
+        mut self,
+        address: A,
+        port: u16,
+        certs_path: C,
+        key_path: K
+    ) -> Self
+        where A: Into<String>, C: Into<String>, K: Into<String>
+    {
+        let tls = Some((certs_path.into(), key_path.into()));
+        self.endpoints.push((address.into(), port, tls));
+        self
+    }
+
     /// Sets the `environment` in the configuration being built.
     ///
     /// # Example
@@ -290,7 +552,8 @@ impl ConfigBuilder {
     ///
     /// # Errors
     ///
-    /// If the address or secret key fail to parse, returns a `BadType` error.
+    /// If the address or secret key fail to parse, or if the "headers" limit is
+    /// greater than 100, returns a `BadType` error.
     ///
     /// # Example
     ///
@@ -317,9 +580,19 @@ impl ConfigBuilder {
         config.set_address(self.address)?;
         config.set_port(self.port);
         config.set_workers(self.workers);
+        config.set_max_pending(self.max_pending);
         config.set_keep_alive(self.keep_alive);
+        config.set_header_read_timeout(self.header_read_timeout);
+        config.set_read_timeout(self.read_timeout);
+        config.set_write_timeout(self.write_timeout);
+        config.set_shutdown_grace(self.shutdown_grace);
+        config.set_listen_fds(self.listen_fds);
+        config.set_http2(self.http2);
+        config.set_h2c(self.h2c);
+        config.set_auto_405(self.auto_405);
         config.set_log_level(self.log_level);
         config.set_extras(self.extras);
+        check_limits(&config, &self.limits)?;
         config.set_limits(self.limits);
 
         if let Some(root) = self.root {
@@ -330,6 +603,15 @@ impl ConfigBuilder {
             config.set_tls(&certs_path, &key_path)?;
         }
 
+        for (address, port, tls) in self.endpoints {
+            match tls {
+                Some((certs_path, key_path)) => {
+                    config.add_tls_endpoint(address, port, &certs_path, &key_path)?;
+                }
+                None => config.add_endpoint(address, port)?,
+            }
+        }
+
         if let Some(key) = self.secret_key {
             config.set_secret_key(key)?;
         }
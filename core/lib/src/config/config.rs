@@ -44,14 +44,36 @@ pub struct Config {
     pub port: u16,
     /// The number of workers to run concurrently.
     pub workers: u16,
+    /// Maximum number of connections waiting for a worker or None if unbounded.
+    pub max_pending: Option<u32>,
     /// Keep-alive timeout in seconds or None if disabled.
     pub keep_alive: Option<u32>,
+    /// Timeout in seconds for reading the request line and headers or None if
+    /// disabled.
+    pub header_read_timeout: Option<u32>,
+    /// Timeout in seconds for each read from the network or None if disabled.
+    pub read_timeout: Option<u32>,
+    /// Timeout in seconds for each write to the network or None if disabled.
+    pub write_timeout: Option<u32>,
+    /// Maximum time in seconds to wait for in-flight requests on shutdown.
+    pub shutdown_grace: u32,
+    /// Whether to serve on a socket inherited via `LISTEN_FDS`, if any.
+    pub listen_fds: bool,
+    /// Whether to offer HTTP/2 via ALPN on TLS connections.
+    pub http2: bool,
+    /// Whether to accept HTTP/2 with prior knowledge on plaintext sockets.
+    pub h2c: bool,
+    /// Whether to respond with `405 Method Not Allowed` when only the method
+    /// of a request doesn't match.
+    pub auto_405: bool,
     /// How much information to log.
     pub log_level: LoggingLevel,
     /// The secret key.
     crate secret_key: SecretKey,
     /// TLS configuration.
     crate tls: Option<TlsConfig>,
+    /// Additional endpoints to serve on.
+    crate endpoints: Vec<Endpoint>,
     /// Streaming read size limits.
     pub limits: Limits,
     /// Extra parameters that aren't part of Rocket's core config.
@@ -228,10 +250,20 @@ impl Config {
                     address: "localhost".to_string(),
                     port: 8000,
                     workers: default_workers,
+                    max_pending: None,
                     keep_alive: Some(5),
+                    header_read_timeout: Some(10),
+                    read_timeout: Some(30),
+                    write_timeout: Some(30),
+                    shutdown_grace: 5,
+                    listen_fds: false,
+                    http2: false,
+                    h2c: false,
+                    auto_405: true,
                     log_level: LoggingLevel::Normal,
                     secret_key: key,
                     tls: None,
+                    endpoints: vec![],
                     limits: Limits::default(),
                     extras: HashMap::new(),
                     config_file_path: None,
@@ -244,10 +276,20 @@ impl Config {
                     address: "0.0.0.0".to_string(),
                     port: 8000,
                     workers: default_workers,
+                    max_pending: None,
                     keep_alive: Some(5),
+                    header_read_timeout: Some(10),
+                    read_timeout: Some(30),
+                    write_timeout: Some(30),
+                    shutdown_grace: 5,
+                    listen_fds: false,
+                    http2: false,
+                    h2c: false,
+                    auto_405: true,
                     log_level: LoggingLevel::Normal,
                     secret_key: key,
                     tls: None,
+                    endpoints: vec![],
                     limits: Limits::default(),
                     extras: HashMap::new(),
                     config_file_path: None,
@@ -260,10 +302,20 @@ impl Config {
                     address: "0.0.0.0".to_string(),
                     port: 8000,
                     workers: default_workers,
+                    max_pending: None,
                     keep_alive: Some(5),
+                    header_read_timeout: Some(10),
+                    read_timeout: Some(30),
+                    write_timeout: Some(30),
+                    shutdown_grace: 5,
+                    listen_fds: false,
+                    http2: false,
+                    h2c: false,
+                    auto_405: true,
                     log_level: LoggingLevel::Critical,
                     secret_key: key,
                     tls: None,
+                    endpoints: vec![],
                     limits: Limits::default(),
                     extras: HashMap::new(),
                     config_file_path: None,
@@ -296,20 +348,41 @@ impl Config {
     ///   * **address**: String
     ///   * **port**: Integer (16-bit unsigned)
     ///   * **workers**: Integer (16-bit unsigned)
+    ///   * **max_pending**: Integer
     ///   * **keep_alive**: Integer
+    ///   * **header_read_timeout**: Integer
+    ///   * **read_timeout**: Integer
+    ///   * **write_timeout**: Integer
+    ///   * **shutdown_grace**: Integer
+    ///   * **listen_fds**: Boolean
+    ///   * **http2**: Boolean
+    ///   * **h2c**: Boolean
+    ///   * **auto_405**: Boolean
     ///   * **log**: String
     ///   * **secret_key**: String (256-bit base64)
     ///   * **tls**: Table (`certs` (path as String), `key` (path as String))
+    ///   * **endpoints**: Array of Tables (`address` (String), `port`
+    ///     (Integer), `tls` (Table, optional))
     crate fn set_raw(&mut self, name: &str, val: &Value) -> Result<()> {
         let (id, ok) = (|val| val, |_| Ok(()));
         config_from_raw!(self, name, val,
             address => (str, set_address, id),
             port => (u16, set_port, ok),
             workers => (u16, set_workers, ok),
+            max_pending => (u32, set_max_pending, ok),
             keep_alive => (u32, set_keep_alive, ok),
+            header_read_timeout => (u32, set_header_read_timeout, ok),
+            read_timeout => (u32, set_read_timeout, ok),
+            write_timeout => (u32, set_write_timeout, ok),
+            shutdown_grace => (u32, set_shutdown_grace, ok),
+            listen_fds => (bool, set_listen_fds, ok),
+            http2 => (bool, set_http2, ok),
+            h2c => (bool, set_h2c, ok),
+            auto_405 => (bool, set_auto_405, ok),
             log => (log_level, set_log_level, ok),
             secret_key => (str, set_secret_key, id),
             tls => (tls_config, set_raw_tls, id),
+            endpoints => (endpoints, set_raw_endpoints, id),
             limits => (limits, set_limits, ok),
             | _ => {
                 self.extras.insert(name.into(), val.clone());
@@ -340,10 +413,14 @@ impl Config {
 
     /// Sets the address of `self` to `address`.
     ///
+    /// On Unix platforms, `address` may also be of the form
+    /// `unix:/path/to.sock`, in which case Rocket serves on a Unix domain socket
+    /// at that path and the configured port is ignored.
+    ///
     /// # Errors
     ///
-    /// If `address` is not a valid IP address or hostname, returns a `BadType`
-    /// error.
+    /// If `address` is not a valid IP address, hostname, or Unix socket
+    /// address, returns a `BadType` error.
     ///
     /// # Example
     ///
@@ -354,14 +431,29 @@ impl Config {
     /// assert!(config.set_address("localhost").is_ok());
     /// assert!(config.set_address("::").is_ok());
     /// assert!(config.set_address("?").is_err());
+    ///
+    /// # #[cfg(unix)]
+    /// assert!(config.set_address("unix:/tmp/rocket.sock").is_ok());
+    /// assert!(config.set_address("unix:").is_err());
     /// ```
     pub fn set_address<A: Into<String>>(&mut self, address: A) -> Result<()> {
         let address = address.into();
-        if (&*address, 0u16).to_socket_addrs().is_err() {
-            return Err(self.bad_type("address", "string", "a valid hostname or IP"));
+        self.check_address("address", &address)?;
+        self.address = address;
+        Ok(())
+    }
+
+    /// Returns an error unless `address` is a valid hostname, IP address, or,
+    /// on Unix platforms, Unix socket address.
+    fn check_address(&self, name: &str, address: &str) -> Result<()> {
+        if address.starts_with("unix:") {
+            if !cfg!(unix) || address.len() == "unix:".len() {
+                return Err(self.bad_type(name, "string", "a valid Unix socket path"));
+            }
+        } else if (address, 0u16).to_socket_addrs().is_err() {
+            return Err(self.bad_type(name, "string", "a valid hostname or IP"));
         }
 
-        self.address = address;
         Ok(())
     }
 
@@ -383,6 +475,14 @@ impl Config {
 
     /// Sets the number of `workers` in `self` to `workers`.
     ///
+    /// A worker serves a connection from the moment a request starts arriving
+    /// until its response has been written. On Unix platforms, connections
+    /// waiting for their first request or idling in keep-alive don't occupy a
+    /// worker, and connections that are upgraded, such as to a WebSocket, are
+    /// handed off to a thread of their own. Connections that speak HTTP/2 or
+    /// that stream a never-ending response, such as an `EventStream`, occupy
+    /// their worker for as long as they stay open.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -397,6 +497,29 @@ impl Config {
         self.workers = workers;
     }
 
+    /// Sets the maximum number of connections waiting for a worker to
+    /// `max_pending`. If `max_pending` is `0`, the number is unbounded.
+    ///
+    /// Once the limit is reached, Rocket responds to new requests immediately
+    /// with a `503 Service Unavailable` error and closes the connection.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// let mut config = Config::development();
+    /// config.set_max_pending(128);
+    /// assert_eq!(config.max_pending, Some(128));
+    ///
+    /// config.set_max_pending(0);
+    /// assert_eq!(config.max_pending, None);
+    /// ```
+    #[inline]
+    pub fn set_max_pending(&mut self, max_pending: u32) {
+        self.max_pending = if max_pending == 0 { None } else { Some(max_pending) };
+    }
+
     /// Sets the keep-alive timeout to `timeout` seconds. If `timeout` is `0`,
     /// keep-alive is disabled.
     ///
@@ -424,6 +547,192 @@ impl Config {
         }
     }
 
+    /// Sets the header read timeout to `timeout` seconds. If `timeout` is `0`,
+    /// the timeout is disabled.
+    ///
+    /// The timeout bounds the total time a client may take to send the request
+    /// line and headers of a request, starting once the first byte of the
+    /// request is received. Clients that exceed it are responded to by the
+    /// `408` catcher if their request line arrived and by the bad request
+    /// handler otherwise. A new connection on which no request arrives within
+    /// the timeout is closed. Between requests on a kept-alive connection, the
+    /// keep-alive timeout applies instead.
+    ///
+    /// On platforms other than Unix, the timeout for the first request on a
+    /// connection starts as soon as a worker begins serving the connection.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// let mut config = Config::development();
+    /// config.set_header_read_timeout(20);
+    /// assert_eq!(config.header_read_timeout, Some(20));
+    ///
+    /// config.set_header_read_timeout(0);
+    /// assert_eq!(config.header_read_timeout, None);
+    /// ```
+    #[inline]
+    pub fn set_header_read_timeout(&mut self, timeout: u32) {
+        self.header_read_timeout = if timeout == 0 { None } else { Some(timeout) };
+    }
+
+    /// Sets the read timeout to `timeout` seconds. If `timeout` is `0`, the
+    /// timeout is disabled.
+    ///
+    /// The timeout bounds the total time a client may take to send the body of
+    /// a request, starting once the head of the request has been read or, if
+    /// the client asked for a `100 Continue`, once it has been sent. A client
+    /// that steadily trickles in the body still times out. If reading the body
+    /// of a request times out, the `408` catcher is invoked for the request.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// let mut config = Config::development();
+    /// config.set_read_timeout(30);
+    /// assert_eq!(config.read_timeout, Some(30));
+    ///
+    /// config.set_read_timeout(0);
+    /// assert_eq!(config.read_timeout, None);
+    /// ```
+    #[inline]
+    pub fn set_read_timeout(&mut self, timeout: u32) {
+        self.read_timeout = if timeout == 0 { None } else { Some(timeout) };
+    }
+
+    /// Sets the write timeout to `timeout` seconds. If `timeout` is `0`, the
+    /// timeout is disabled.
+    ///
+    /// The timeout bounds the time any single write of response data to the
+    /// network may block. If a write times out, the connection is closed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// let mut config = Config::development();
+    /// config.set_write_timeout(60);
+    /// assert_eq!(config.write_timeout, Some(60));
+    ///
+    /// config.set_write_timeout(0);
+    /// assert_eq!(config.write_timeout, None);
+    /// ```
+    #[inline]
+    pub fn set_write_timeout(&mut self, timeout: u32) {
+        self.write_timeout = if timeout == 0 { None } else { Some(timeout) };
+    }
+
+    /// Sets the shutdown grace period to `grace` seconds. On shutdown, Rocket
+    /// waits at most this long for in-flight requests to complete. If `grace`
+    /// is `0`, Rocket does not wait.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// let mut config = Config::development();
+    /// config.set_shutdown_grace(30);
+    /// assert_eq!(config.shutdown_grace, 30);
+    /// ```
+    #[inline]
+    pub fn set_shutdown_grace(&mut self, grace: u32) {
+        self.shutdown_grace = grace;
+    }
+
+    /// Sets whether Rocket serves on a listening socket passed to it by the
+    /// process that started it, as done by systemd's socket activation. When
+    /// `enabled` and the `LISTEN_FDS` and `LISTEN_PID` environment variables
+    /// name a socket for this process, Rocket serves on that socket instead of
+    /// binding to the configured address and port. Otherwise, Rocket binds as
+    /// usual.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// let mut config = Config::development();
+    /// config.set_listen_fds(true);
+    /// assert!(config.listen_fds);
+    /// ```
+    #[inline]
+    pub fn set_listen_fds(&mut self, enabled: bool) {
+        self.listen_fds = enabled;
+    }
+
+    /// Sets whether Rocket offers HTTP/2 to clients via ALPN on TLS
+    /// connections. When `enabled`, clients that accept the offer are served
+    /// over HTTP/2; all other clients are served over HTTP/1.x as usual.
+    ///
+    /// HTTP/2 support is experimental and is only compiled in if the `http2`
+    /// feature is enabled; without it, this setting is ignored. Rocket serves
+    /// the streams of an HTTP/2 connection one at a time, and the connection
+    /// occupies a worker for as long as it stays open. As such, HTTP/2 is
+    /// disabled by default.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// let mut config = Config::development();
+    /// config.set_http2(true);
+    /// assert!(config.http2);
+    /// ```
+    #[inline]
+    pub fn set_http2(&mut self, enabled: bool) {
+        self.http2 = enabled;
+    }
+
+    /// Sets whether Rocket accepts HTTP/2 connections with prior knowledge,
+    /// known as "h2c", on plaintext sockets. When `enabled`, a connection
+    /// whose first bytes are the HTTP/2 connection preface is served over
+    /// HTTP/2; all other connections are served over HTTP/1.x as usual.
+    ///
+    /// HTTP/2 over TLS is enabled separately via
+    /// [`set_http2()`](Config::set_http2()). The caveats listed there apply
+    /// equally to `h2c`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// let mut config = Config::development();
+    /// config.set_h2c(true);
+    /// assert!(config.h2c);
+    /// ```
+    #[inline]
+    pub fn set_h2c(&mut self, enabled: bool) {
+        self.h2c = enabled;
+    }
+
+    /// Sets whether Rocket responds with `405 Method Not Allowed` to requests
+    /// that no route matches when routes for the request's path and host
+    /// exist under other methods. When `enabled`, the `405` catcher is used
+    /// and the response's `Allow` header lists those methods. Otherwise, such
+    /// requests are handled by the `404` catcher.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// let mut config = Config::development();
+    /// config.set_auto_405(false);
+    /// assert!(!config.auto_405);
+    /// ```
+    #[inline]
+    pub fn set_auto_405(&mut self, enabled: bool) {
+        self.auto_405 = enabled;
+    }
+
     /// Sets the `secret_key` in `self` to `key` which must be a 256-bit base64
     /// encoded string.
     ///
@@ -478,6 +787,10 @@ impl Config {
 
     /// Sets the receive limits in `self` to `limits`.
     ///
+    /// Unlike the configuration file and [`ConfigBuilder`], this method doesn't
+    /// reject a "headers" limit greater than 100. Requests with more than 100
+    /// headers are rejected regardless.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -518,6 +831,21 @@ impl Config {
     /// ```
     #[cfg(feature = "tls")]
     pub fn set_tls(&mut self, certs_path: &str, key_path: &str) -> Result<()> {
+        self.tls = Some(self.load_tls(certs_path, key_path)?);
+        Ok(())
+    }
+
+    #[doc(hidden)]
+    #[cfg(not(feature = "tls"))]
+    pub fn set_tls(&mut self, _: &str, _: &str) -> Result<()> {
+        self.tls = Some(TlsConfig);
+        Ok(())
+    }
+
+    /// Reads and parses the TLS certificates and private key at the given
+    /// paths, relative to the configuration's root.
+    #[cfg(feature = "tls")]
+    fn load_tls(&self, certs_path: &str, key_path: &str) -> Result<TlsConfig> {
         use http::tls::util::{self, Error};
 
         let pem_err = "malformed PEM file";
@@ -536,14 +864,99 @@ impl Config {
                 _ => self.bad_type("tls", pem_err, "a valid private key file")
             })?;
 
-        self.tls = Some(TlsConfig { certs, key });
+        Ok(TlsConfig { certs, key })
+    }
+
+    /// Adds an additional endpoint at `address` and `port` for Rocket to serve
+    /// on. The address may be of any form accepted by
+    /// [`set_address()`](Config::set_address()).
+    ///
+    /// # Errors
+    ///
+    /// If `address` is not a valid IP address, hostname, or Unix socket
+    /// address, returns a `BadType` error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// let mut config = Config::development();
+    /// assert!(config.add_endpoint("::1", 8000).is_ok());
+    /// assert!(config.add_endpoint("?", 8000).is_err());
+    /// assert_eq!(config.endpoints().len(), 1);
+    /// ```
+    pub fn add_endpoint<A: Into<String>>(&mut self, address: A, port: u16) -> Result<()> {
+        let address = address.into();
+        self.check_address("endpoints.address", &address)?;
+        self.endpoints.push(Endpoint { address, port, tls: None });
+        Ok(())
+    }
+
+    /// Adds an additional endpoint at `address` and `port` for Rocket to serve
+    /// on using TLS. Certificates and the private key are read from
+    /// `certs_path` and `key_path` as in [`set_tls()`](Config::set_tls()).
+    ///
+    /// # Errors
+    ///
+    /// If `address` is invalid, returns a `BadType` error. If reading either
+    /// the certificates or private key fails, an error of variant `Io` is
+    /// returned. If either file is malformed, an error of `BadType` is
+    /// returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = Config::development();
+    /// config.add_tls_endpoint("0.0.0.0", 8443, "/etc/ssl/certs.pem", "/etc/ssl/key.pem")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "tls")]
+    pub fn add_tls_endpoint<A: Into<String>>(
+        &mut self,
+        address: A,
+        port: u16,
+        certs_path: &str,
+        key_path: &str
+    ) -> Result<()> {
+        let address = address.into();
+        self.check_address("endpoints.address", &address)?;
+        let tls = self.load_tls(certs_path, key_path)?;
+        self.endpoints.push(Endpoint { address, port, tls: Some(tls) });
         Ok(())
     }
 
     #[doc(hidden)]
     #[cfg(not(feature = "tls"))]
-    pub fn set_tls(&mut self, _: &str, _: &str) -> Result<()> {
-        self.tls = Some(TlsConfig);
+    pub fn add_tls_endpoint<A: Into<String>>(
+        &mut self,
+        address: A,
+        port: u16,
+        _: &str,
+        _: &str
+    ) -> Result<()> {
+        let address = address.into();
+        self.check_address("endpoints.address", &address)?;
+        self.endpoints.push(Endpoint { address, port, tls: Some(TlsConfig) });
+        Ok(())
+    }
+
+    fn set_raw_endpoints(&mut self, endpoints: Vec<RawEndpoint>) -> Result<()> {
+        self.endpoints.clear();
+        for (address, port, _tls) in endpoints {
+            match _tls {
+                // During unit testing, we don't want to actually read certs/keys.
+                #[cfg(not(test))]
+                Some((certs, key)) => self.add_tls_endpoint(address, port, certs, key)?,
+                _ => self.add_endpoint(address, port)?,
+            }
+        }
+
         Ok(())
     }
 
@@ -605,6 +1018,24 @@ impl Config {
         self.extras.iter().map(|(k, v)| (k.as_str(), v))
     }
 
+    /// Returns the additional endpoints Rocket serves on.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Config;
+    ///
+    /// let mut config = Config::development();
+    /// assert!(config.endpoints().is_empty());
+    ///
+    /// config.add_endpoint("::1", 8000).unwrap();
+    /// assert_eq!(config.endpoints()[0].address, "::1");
+    /// ```
+    #[inline]
+    pub fn endpoints(&self) -> &[Endpoint] {
+        &self.endpoints
+    }
+
     /// Returns `true` if TLS is enabled.
     ///
     /// Always returns `false` if the `tls` compilation feature is not enabled.
@@ -923,7 +1354,17 @@ impl fmt::Debug for Config {
         s.field("address", &self.address);
         s.field("port", &self.port);
         s.field("workers", &self.workers);
+        s.field("max_pending", &self.max_pending);
         s.field("keep_alive", &self.keep_alive);
+        s.field("header_read_timeout", &self.header_read_timeout);
+        s.field("read_timeout", &self.read_timeout);
+        s.field("write_timeout", &self.write_timeout);
+        s.field("shutdown_grace", &self.shutdown_grace);
+        s.field("listen_fds", &self.listen_fds);
+        s.field("http2", &self.http2);
+        s.field("h2c", &self.h2c);
+        s.field("auto_405", &self.auto_405);
+        s.field("endpoints", &self.endpoints);
         s.field("log_level", &self.log_level);
 
         for (key, value) in self.extras() {
@@ -940,8 +1381,18 @@ impl PartialEq for Config {
         self.address == other.address
             && self.port == other.port
             && self.workers == other.workers
+            && self.max_pending == other.max_pending
             && self.log_level == other.log_level
             && self.keep_alive == other.keep_alive
+            && self.header_read_timeout == other.header_read_timeout
+            && self.read_timeout == other.read_timeout
+            && self.write_timeout == other.write_timeout
+            && self.shutdown_grace == other.shutdown_grace
+            && self.listen_fds == other.listen_fds
+            && self.http2 == other.http2
+            && self.h2c == other.h2c
+            && self.auto_405 == other.auto_405
+            && self.endpoints == other.endpoints
             && self.environment == other.environment
             && self.extras == other.extras
     }
@@ -6,6 +6,9 @@ use http::private::Key;
 
 use config::{Result, Config, Value, ConfigError, LoggingLevel};
 
+/// The most headers Hyper parses in a request.
+const MAX_HEADERS: u64 = 100;
+
 #[derive(Clone)]
 pub enum SecretKey {
     Generated(Key),
@@ -54,6 +57,72 @@ pub struct TlsConfig {
 #[derive(Clone)]
 pub struct TlsConfig;
 
+/// An additional address and port for Rocket to serve on.
+///
+/// Endpoints are added to a configuration via the `endpoints` configuration
+/// parameter, [`Config::add_endpoint()`], or [`Config::add_tls_endpoint()`].
+/// Rocket serves on every endpoint in addition to the configured `address` and
+/// `port`. All endpoints share the same routes, managed state, and fairings.
+/// Each endpoint may have its own TLS configuration; the top-level `tls`
+/// configuration applies only to the primary `address` and `port`.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::config::{Config, Environment};
+///
+/// let config = Config::build(Environment::Staging)
+///     .address("0.0.0.0")
+///     .port(8000)
+///     .endpoint("::", 8000)
+///     .unwrap();
+///
+/// let endpoint = &config.endpoints()[0];
+/// assert_eq!(endpoint.address, "::");
+/// assert_eq!(endpoint.port, 8000);
+/// assert!(!endpoint.tls_enabled());
+/// ```
+#[derive(Clone)]
+pub struct Endpoint {
+    /// The address to serve on.
+    pub address: String,
+    /// The port to serve on.
+    pub port: u16,
+    /// TLS configuration for this endpoint, if any.
+    crate tls: Option<TlsConfig>,
+}
+
+impl Endpoint {
+    /// Returns `true` if TLS is enabled for this endpoint.
+    ///
+    /// Always returns `false` if the `tls` compilation feature is not enabled.
+    pub fn tls_enabled(&self) -> bool {
+        if cfg!(feature = "tls") {
+            self.tls.is_some()
+        } else {
+            false
+        }
+    }
+}
+
+impl PartialEq for Endpoint {
+    fn eq(&self, other: &Endpoint) -> bool {
+        self.address == other.address
+            && self.port == other.port
+            && self.tls.is_some() == other.tls.is_some()
+    }
+}
+
+impl fmt::Debug for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Endpoint")
+            .field("address", &self.address)
+            .field("port", &self.port)
+            .field("tls", &self.tls.is_some())
+            .finish()
+    }
+}
+
 /// Mapping from data type to size limits.
 ///
 /// A `Limits` structure contains a mapping from a given data type ("forms",
@@ -62,11 +131,22 @@ pub struct TlsConfig;
 /// "forms" is set to `256`, only 256 bytes from an incoming form request will
 /// be read.
 ///
+/// Two limits apply to request headers instead of data: "headers" caps the
+/// number of headers in a request, and "header_bytes" caps the total size of
+/// their names and values in bytes. Requests exceeding either limit are
+/// rejected with a `431 Request Header Fields Too Large` error before any
+/// fairings see them. The "headers" limit may be at most 100, the most headers
+/// that Rocket's HTTP parser accepts; a configuration that sets a higher limit
+/// fails to load. Requests with more headers than the parser accepts are
+/// rejected with a `431` error all the same.
+///
 /// # Defaults
 ///
 /// As documented in [`config`](::config), the default limits are as follows:
 ///
 ///   * **forms**: 32KiB
+///   * **headers**: 100
+///   * **header_bytes**: 32KiB
 ///
 /// # Usage
 ///
@@ -84,13 +164,21 @@ pub struct TlsConfig;
 pub struct Limits {
     // We cache this internally but don't share that fact in the API.
     crate forms: u64,
+    crate headers: u64,
+    crate header_bytes: u64,
     extra: Vec<(String, u64)>
 }
 
 impl Default for Limits {
     fn default() -> Limits {
-        // Default limit for forms is 32KiB.
-        Limits { forms: 32 * 1024, extra: Vec::new() }
+        // Default limit for forms and header bytes is 32KiB. Hyper won't parse
+        // more than `MAX_HEADERS` headers, so that's the default limit as well.
+        Limits {
+            forms: 32 * 1024,
+            headers: MAX_HEADERS,
+            header_bytes: 32 * 1024,
+            extra: Vec::new()
+        }
     }
 }
 
@@ -104,6 +192,8 @@ impl Limits {
     ///
     /// let limits = Limits::new();
     /// assert_eq!(limits.get("forms"), Some(32 * 1024));
+    /// assert_eq!(limits.get("headers"), Some(100));
+    /// assert_eq!(limits.get("header_bytes"), Some(32 * 1024));
     /// ```
     #[inline]
     pub fn new() -> Self {
@@ -131,6 +221,8 @@ impl Limits {
         let name = name.into();
         match name.as_str() {
             "forms" => self.forms = limit,
+            "headers" => self.headers = limit,
+            "header_bytes" => self.header_bytes = limit,
             _ => {
                 let mut found = false;
                 for tuple in &mut self.extra {
@@ -165,8 +257,11 @@ impl Limits {
     /// assert!(limits.get("msgpack").is_none());
     /// ```
     pub fn get(&self, name: &str) -> Option<u64> {
-        if name == "forms" {
-            return Some(self.forms);
+        match name {
+            "forms" => return Some(self.forms),
+            "headers" => return Some(self.headers),
+            "header_bytes" => return Some(self.header_bytes),
+            _ => {}
         }
 
         for &(ref key, val) in &self.extra {
@@ -193,6 +288,8 @@ impl fmt::Display for Limits {
 
         write!(f, "forms = ")?;
         fmt_size(self.forms, f)?;
+        write!(f, ", headers = {}, header_bytes = ", self.headers)?;
+        fmt_size(self.header_bytes, f)?;
         for &(ref key, val) in &self.extra {
             write!(f, ", {}* = ", key)?;
             fmt_size(val, f)?;
@@ -206,6 +303,10 @@ pub fn str<'a>(conf: &Config, name: &str, v: &'a Value) -> Result<&'a str> {
     v.as_str().ok_or_else(|| conf.bad_type(name, v.type_str(), "a string"))
 }
 
+pub fn bool(conf: &Config, name: &str, value: &Value) -> Result<bool> {
+    value.as_bool().ok_or_else(|| conf.bad_type(name, value.type_str(), "a boolean"))
+}
+
 pub fn u64(conf: &Config, name: &str, value: &Value) -> Result<u64> {
     match value.as_integer() {
         Some(x) if x >= 0 => Ok(x as u64),
@@ -260,6 +361,50 @@ pub fn tls_config<'v>(conf: &Config,
     }
 }
 
+/// An endpoint as read from a configuration file: the address, the port, and
+/// the paths to the TLS certificates and private key, if any.
+crate type RawEndpoint<'v> = (&'v str, u16, Option<(&'v str, &'v str)>);
+
+pub fn endpoints<'v>(conf: &Config,
+                              name: &str,
+                              value: &'v Value,
+                              ) -> Result<Vec<RawEndpoint<'v>>> {
+    let expected = "an array of tables";
+    let array = value.as_array()
+        .ok_or_else(|| conf.bad_type(name, value.type_str(), expected))?;
+
+    let mut endpoints = vec![];
+    for value in array {
+        let table = value.as_table()
+            .ok_or_else(|| conf.bad_type(name, value.type_str(), expected))?;
+
+        let (mut address, mut port, mut tls) = (None, None, None);
+        for (key, value) in table {
+            match key.as_str() {
+                "address" => address = Some(str(conf, "endpoints.address", value)?),
+                "port" => port = Some(u16(conf, "endpoints.port", value)?),
+                "tls" => tls = Some(tls_config(conf, "endpoints.tls", value)?),
+                _ => {
+                    let key = format!("{}.endpoints.{}", conf.environment, key);
+                    return Err(ConfigError::UnknownKey(key));
+                }
+            }
+        }
+
+        // Unix domain sockets don't have a port.
+        match (address, port) {
+            (Some(address), Some(port)) => endpoints.push((address, port, tls)),
+            (Some(address), None) if address.starts_with("unix:") => {
+                endpoints.push((address, 0, tls))
+            }
+            _ => return Err(conf.bad_type(name, "a table with missing entries",
+                                "a table with `address` and `port` entries"))
+        }
+    }
+
+    Ok(endpoints)
+}
+
 pub fn limits(conf: &Config, name: &str, value: &Value) -> Result<Limits> {
     let table = value.as_table()
         .ok_or_else(|| conf.bad_type(name, value.type_str(), "a table"))?;
@@ -270,5 +415,18 @@ pub fn limits(conf: &Config, name: &str, value: &Value) -> Result<Limits> {
         limits = limits.limit(key.as_str(), val);
     }
 
+    check_limits(conf, &limits)?;
     Ok(limits)
 }
+
+/// Fails if `limits` can't be honored: Hyper rejects requests with more than
+/// `MAX_HEADERS` headers before Rocket sees them, so the "headers" limit can't
+/// be any higher.
+crate fn check_limits(conf: &Config, limits: &Limits) -> Result<()> {
+    if limits.headers > MAX_HEADERS {
+        let expected = "an integer no greater than 100";
+        return Err(conf.bad_type("limits.headers", "an integer greater than 100", expected));
+    }
+
+    Ok(())
+}
@@ -31,18 +31,28 @@
 //! not used by Rocket itself but can be used by external libraries. The
 //! standard configuration parameters are:
 //!
-//! | name       | type           | description                                                 | examples                   |
-//! |------------|----------------|-------------------------------------------------------------|----------------------------|
-//! | address    | string         | ip address or host to listen on                             | `"localhost"`, `"1.2.3.4"` |
-//! | port       | integer        | port number to listen on                                    | `8000`, `80`               |
-//! | keep_alive | integer        | keep-alive timeout in seconds                               | `0` (disable), `10`        |
-//! | workers    | integer        | number of concurrent thread workers                         | `36`, `512`                |
-//! | log        | string         | max log level: `"off"`, `"normal"`, `"debug"`, `"critical"` | `"off"`, `"normal"`        |
-//! | secret_key | 256-bit base64 | secret key for private cookies                              | `"8Xui8SI..."` (44 chars)  |
-//! | tls        | table          | tls config table with two keys (`certs`, `key`)             | _see below_                |
-//! | tls.certs  | string         | path to certificate chain in PEM format                     | `"private/cert.pem"`       |
-//! | tls.key    | string         | path to private key for `tls.certs` in PEM format           | `"private/key.pem"`        |
-//! | limits     | table          | map from data type (string) to data limit (integer: bytes)  | `{ forms = 65536 }`        |
+//! | name                | type           | description                                                 | examples                   |
+//! |---------------------|----------------|-------------------------------------------------------------|----------------------------|
+//! | address             | string         | ip address, host, or `unix:` socket path to listen on       | `"localhost"`, `"1.2.3.4"` |
+//! | port                | integer        | port number to listen on                                    | `8000`, `80`               |
+//! | keep_alive          | integer        | keep-alive timeout in seconds                               | `0` (disable), `10`        |
+//! | header_read_timeout | integer        | seconds to wait for a request's line and headers            | `0` (disable), `10`        |
+//! | read_timeout        | integer        | seconds to wait for a request's entire body                 | `0` (disable), `30`        |
+//! | write_timeout       | integer        | seconds to wait for each write to the network               | `0` (disable), `30`        |
+//! | shutdown_grace      | integer        | seconds to wait for in-flight requests on shutdown          | `0` (don't wait), `30`     |
+//! | listen_fds          | boolean        | serve on a socket passed via `LISTEN_FDS` (systemd)         | `false`, `true`            |
+//! | http2               | boolean        | offer HTTP/2 via ALPN on TLS connections (experimental)     | `false`, `true`            |
+//! | h2c                 | boolean        | accept prior-knowledge HTTP/2 without TLS (experimental)    | `false`, `true`            |
+//! | auto_405            | boolean        | respond with `405` when only the request method mismatches  | `true`, `false`            |
+//! | workers             | integer        | number of concurrent thread workers                         | `36`, `512`                |
+//! | max_pending         | integer        | connections to queue while all workers are busy             | `0` (unbounded), `128`     |
+//! | log                 | string         | max log level: `"off"`, `"normal"`, `"debug"`, `"critical"` | `"off"`, `"normal"`        |
+//! | secret_key          | 256-bit base64 | secret key for private cookies                              | `"8Xui8SI..."` (44 chars)  |
+//! | tls                 | table          | tls config table with two keys (`certs`, `key`)             | _see below_                |
+//! | tls.certs           | string         | path to certificate chain in PEM format                     | `"private/cert.pem"`       |
+//! | tls.key             | string         | path to private key for `tls.certs` in PEM format           | `"private/key.pem"`        |
+//! | limits              | table          | map from data type (string) to data limit (integer: bytes)  | `{ forms = 65536 }`        |
+//! | endpoints           | array          | additional endpoints to listen on                           | _see below_                |
 //!
 //! ### Rocket.toml
 //!
@@ -63,7 +73,16 @@
 //! address = "localhost"
 //! port = 8000
 //! workers = [number_of_cpus * 2]
+//! max_pending = 0
 //! keep_alive = 5
+//! header_read_timeout = 10
+//! read_timeout = 30
+//! write_timeout = 30
+//! shutdown_grace = 5
+//! listen_fds = false
+//! http2 = false
+//! h2c = false
+//! auto_405 = true
 //! log = "normal"
 //! secret_key = [randomly generated at launch]
 //! limits = { forms = 32768 }
@@ -72,7 +91,16 @@
 //! address = "0.0.0.0"
 //! port = 8000
 //! workers = [number_of_cpus * 2]
+//! max_pending = 0
 //! keep_alive = 5
+//! header_read_timeout = 10
+//! read_timeout = 30
+//! write_timeout = 30
+//! shutdown_grace = 5
+//! listen_fds = false
+//! http2 = false
+//! h2c = false
+//! auto_405 = true
 //! log = "normal"
 //! secret_key = [randomly generated at launch]
 //! limits = { forms = 32768 }
@@ -81,7 +109,16 @@
 //! address = "0.0.0.0"
 //! port = 8000
 //! workers = [number_of_cpus * 2]
+//! max_pending = 0
 //! keep_alive = 5
+//! header_read_timeout = 10
+//! read_timeout = 30
+//! write_timeout = 30
+//! shutdown_grace = 5
+//! listen_fds = false
+//! http2 = false
+//! h2c = false
+//! auto_405 = true
 //! log = "critical"
 //! secret_key = [randomly generated at launch]
 //! limits = { forms = 32768 }
@@ -124,6 +161,26 @@
 //! key = "/path/to/key.pem"
 //! ```
 //!
+//! ### Multiple Endpoints
+//!
+//! Rocket can serve on several endpoints at once in addition to the configured
+//! `address` and `port`. Each entry in the `endpoints` array is a table with
+//! an `address`, a `port`, and, optionally, a `tls` table with its own `certs`
+//! and `key`. The top-level `tls` parameters apply only to the primary
+//! `address` and `port`. All endpoints share the same routes, managed state,
+//! and fairings. For example, to serve HTTP on IPv4 and IPv6 and HTTPS on
+//! port `8443`:
+//!
+//! ```toml
+//! [global]
+//! address = "0.0.0.0"
+//! port = 8000
+//! endpoints = [
+//!     { address = "::", port = 8000 },
+//!     { address = "0.0.0.0", port = 8443, tls = { certs = "certs.pem", key = "key.pem" } },
+//! ]
+//! ```
+//!
 //! ### Environment Variables
 //!
 //! All configuration parameters, including extras, can be overridden through
@@ -197,7 +254,7 @@ use std::env;
 
 use toml;
 
-pub use self::custom_values::Limits;
+pub use self::custom_values::{Limits, Endpoint};
 pub use toml::value::{Array, Table, Value, Datetime};
 pub use self::error::ConfigError;
 pub use self::environment::Environment;
@@ -205,6 +262,7 @@ pub use self::config::Config;
 pub use self::builder::ConfigBuilder;
 pub use logger::LoggingLevel;
 crate use self::toml_ext::LoggedValue;
+crate use self::custom_values::TlsConfig;
 
 use logger;
 use self::Environment::*;
@@ -656,6 +714,14 @@ mod test {
                       "#.to_string(), TEST_CONFIG_FILENAME), {
                           default_config(Development).address("0.0.0.0")
                       });
+
+        #[cfg(unix)]
+        check_config!(RocketConfig::parse(r#"
+                          [dev]
+                          address = "unix:/tmp/rocket.sock"
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Development).address("unix:/tmp/rocket.sock")
+                      });
     }
 
     #[test]
@@ -683,6 +749,11 @@ mod test {
             [staging]
             address = "1.2.3.4:100"
         "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [staging]
+            address = "unix:"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
     }
 
     // Only do this test when the tls feature is disabled since the file paths
@@ -863,6 +934,44 @@ mod test {
         "#.to_string(), TEST_CONFIG_FILENAME).is_err());
     }
 
+    #[test]
+    fn test_good_max_pendings() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          max_pending = 128
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).max_pending(128)
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          max_pending = 0
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).max_pending(0)
+                      });
+    }
+
+    #[test]
+    fn test_bad_max_pendings() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::remove_var(CONFIG_ENV);
+
+        assert!(RocketConfig::parse(r#"
+            [dev]
+            max_pending = true
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [dev]
+            max_pending = -1
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
     #[test]
     fn test_good_keep_alives() {
         // Take the lock so changing the environment doesn't cause races.
@@ -925,6 +1034,317 @@ mod test {
         "#.to_string(), TEST_CONFIG_FILENAME).is_err());
     }
 
+    #[test]
+    fn test_good_timeouts() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          header_read_timeout = 20
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).header_read_timeout(20)
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          read_timeout = 0
+                          write_timeout = 60
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).read_timeout(0).write_timeout(60)
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          header_read_timeout = 0
+                          read_timeout = 1
+                          write_timeout = 0
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging)
+                              .header_read_timeout(0)
+                              .read_timeout(1)
+                              .write_timeout(0)
+                      });
+    }
+
+    #[test]
+    fn test_bad_timeouts() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::remove_var(CONFIG_ENV);
+
+        assert!(RocketConfig::parse(r#"
+            [dev]
+            header_read_timeout = true
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [dev]
+            read_timeout = -1
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [dev]
+            write_timeout = "30s"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_good_shutdown_graces() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          shutdown_grace = 30
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).shutdown_grace(30)
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          shutdown_grace = 0
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).shutdown_grace(0)
+                      });
+    }
+
+    #[test]
+    fn test_bad_shutdown_graces() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::remove_var(CONFIG_ENV);
+
+        assert!(RocketConfig::parse(r#"
+            [dev]
+            shutdown_grace = true
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [dev]
+            shutdown_grace = -1
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_good_listen_fds() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          listen_fds = true
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).listen_fds(true)
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          listen_fds = false
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).listen_fds(false)
+                      });
+    }
+
+    #[test]
+    fn test_bad_listen_fds() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::remove_var(CONFIG_ENV);
+
+        assert!(RocketConfig::parse(r#"
+            [development]
+            listen_fds = 1
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [development]
+            listen_fds = "true"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_good_http2() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          http2 = true
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).http2(true)
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          http2 = false
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).http2(false)
+                      });
+    }
+
+    #[test]
+    fn test_bad_http2() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::remove_var(CONFIG_ENV);
+
+        assert!(RocketConfig::parse(r#"
+            [development]
+            http2 = 1
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [development]
+            http2 = "true"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_good_h2c() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          h2c = true
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).h2c(true)
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          h2c = false
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).h2c(false)
+                      });
+    }
+
+    #[test]
+    fn test_bad_h2c() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::remove_var(CONFIG_ENV);
+
+        assert!(RocketConfig::parse(r#"
+            [development]
+            h2c = 1
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [development]
+            h2c = "true"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_good_auto_405() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          auto_405 = false
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).auto_405(false)
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          auto_405 = true
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).auto_405(true)
+                      });
+    }
+
+    #[test]
+    fn test_bad_auto_405() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::remove_var(CONFIG_ENV);
+
+        assert!(RocketConfig::parse(r#"
+            [development]
+            auto_405 = 0
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [development]
+            auto_405 = "false"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_good_endpoints() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          endpoints = [{ address = "::", port = 8000 }]
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).endpoint("::", 8000)
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          endpoints = [
+                              { address = "127.0.0.1", port = 80 },
+                              { address = "localhost", port = 8001 },
+                          ]
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging)
+                              .endpoint("127.0.0.1", 80)
+                              .endpoint("localhost", 8001)
+                      });
+
+        check_config!(RocketConfig::parse(r#"
+                          [stage]
+                          endpoints = []
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging)
+                      });
+    }
+
+    #[test]
+    fn test_bad_endpoints() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::remove_var(CONFIG_ENV);
+
+        assert!(RocketConfig::parse(r#"
+            [development]
+            endpoints = { address = "::", port = 8000 }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [development]
+            endpoints = [{ address = "::" }]
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [development]
+            endpoints = [{ address = "........", port = 8000 }]
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [development]
+            endpoints = [{ address = "::", port = 8000, other = 1 }]
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(RocketConfig::parse(r#"
+            [development]
+            endpoints = [{ address = "::", port = 8000, tls = { certs = "x" } }]
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
     #[test]
     fn test_good_log_levels() {
         // Take the lock so changing the environment doesn't cause races.
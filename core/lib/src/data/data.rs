@@ -1,13 +1,17 @@
 use std::io::{self, Read, Write, Cursor, Chain};
 use std::path::Path;
 use std::fs::File;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use std::net::Shutdown;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 #[cfg(feature = "tls")] use super::net_stream::HttpsStream;
 
-use super::data_stream::{DataStream, kill_stream};
+use super::data_stream::{DataStream, Deadline, kill_stream, is_timeout};
 use super::net_stream::NetStream;
 use ext::ReadExt;
+#[cfg(feature = "http2")] use http2;
 
 use http::hyper;
 use http::hyper::h1::HttpReader;
@@ -55,10 +59,31 @@ const PEEK_BYTES: usize = 512;
 /// The `peek` method returns a slice containing at most 512 bytes of buffered
 /// body data. This enables partially or fully reading from a `Data` object
 /// without consuming the `Data` object.
+///
+/// # Continuation
+///
+/// A client may send an `Expect: 100-continue` header to ask for permission
+/// before sending the body of the request. Rocket grants it by sending an
+/// interim `100 Continue` response when the `Data` is first opened, that is,
+/// only once the request has been routed and its request guards have
+/// succeeded. If the request is rejected before then, the client receives the
+/// final response without ever transferring the body, and the connection is
+/// closed. Until the `Data` is opened, the `peek` buffer of such a request is
+/// empty. Whether the client asked for continuation can be checked via
+/// [`expects_continue()`](Data::expects_continue()).
 pub struct Data {
     buffer: Vec<u8>,
     is_complete: bool,
     stream: BodyReader,
+    timed_out: Arc<AtomicBool>,
+    // Set once the body has been abandoned and the connection can't be reused.
+    closed: Arc<AtomicBool>,
+    // When reading the body from the network times out, if ever.
+    deadline: Option<Instant>,
+    expects_continue: bool,
+    // The stream to write a `100 Continue` to, if one is yet to be sent, and
+    // the read timeout to start once it has been.
+    continuation: Option<(NetStream, Option<Duration>)>,
 }
 
 impl Data {
@@ -79,6 +104,19 @@ impl Data {
     /// }
     /// ```
     pub fn open(mut self) -> DataStream {
+        // The client is waiting for permission to send the body. Grant it.
+        if let Some((mut stream, read_timeout)) = self.continuation.take() {
+            let result = stream.write_all(b"HTTP/1.1 100 Continue\r\n\r\n")
+                .and_then(|_| stream.flush());
+
+            if let Err(e) = result {
+                debug!("Failed to write 100-continue: {:?}", e);
+            }
+
+            // The client only starts sending the body now.
+            self.deadline = read_timeout.map(|timeout| Instant::now() + timeout);
+        }
+
         let buffer = ::std::mem::replace(&mut self.buffer, vec![]);
         let empty_stream = Cursor::new(vec![]).chain(NetStream::Empty);
 
@@ -87,16 +125,25 @@ impl Data {
         // actually do this, however.
         let empty_http_stream = HttpReader::SizedReader(empty_stream, 0);
         let stream = ::std::mem::replace(&mut self.stream, empty_http_stream);
-        DataStream(Cursor::new(buffer).chain(stream))
+        let inner = Cursor::new(buffer).chain(stream);
+        DataStream(inner, self.timed_out.clone(), self.deadline, self.closed.clone())
     }
 
     // FIXME: This is absolutely terrible (downcasting!), thanks to Hyper.
-    crate fn from_hyp(mut body: HyperBodyReader) -> Result<Data, &'static str> {
+    crate fn from_hyp(
+        mut body: HyperBodyReader,
+        read_timeout: Option<Duration>,
+        expects_continue: bool
+    ) -> Result<Data, &'static str> {
         #[inline(always)]
         #[cfg(feature = "tls")]
         fn concrete_stream(stream: &mut NetworkStream) -> Option<NetStream> {
-            stream.downcast_ref::<HttpsStream>()
-                .map(|s| NetStream::Https(s.clone()))
+            stream.downcast_ref::<NetStream>()
+                .cloned()
+                .or_else(|| {
+                    stream.downcast_ref::<HttpsStream>()
+                        .map(|s| NetStream::Https(s.clone()))
+                })
                 .or_else(|| {
                     stream.downcast_ref::<HttpStream>()
                         .map(|s| NetStream::Http(s.clone()))
@@ -106,8 +153,12 @@ impl Data {
         #[inline(always)]
         #[cfg(not(feature = "tls"))]
         fn concrete_stream(stream: &mut NetworkStream) -> Option<NetStream> {
-            stream.downcast_ref::<HttpStream>()
-                .map(|s| NetStream::Http(s.clone()))
+            stream.downcast_ref::<NetStream>()
+                .cloned()
+                .or_else(|| {
+                    stream.downcast_ref::<HttpStream>()
+                        .map(|s| NetStream::Http(s.clone()))
+                })
         }
 
         // Retrieve the underlying Http(s)Stream from Hyper.
@@ -116,15 +167,19 @@ impl Data {
             None => return Err("Stream is not an HTTP(s) stream!")
         };
 
-        // Set the read timeout to 5 seconds.
-        let _ = net_stream.set_read_timeout(Some(Duration::from_secs(5)));
-
         // Steal the internal, undecoded data buffer from Hyper.
         let (mut hyper_buf, pos, cap) = body.get_mut().take_buf();
         hyper_buf.truncate(cap); // slow, but safe
         let mut cursor = Cursor::new(hyper_buf);
         cursor.set_position(pos as u64);
 
+        // Only defer reading if there's a body the client is holding back.
+        let continuation = match body {
+            EmptyReader(_) | SizedReader(_, 0) => None,
+            _ if expects_continue => Some(net_stream.clone()),
+            _ => None
+        };
+
         // Create an HTTP reader from the buffer + stream.
         let inner_data = cursor.chain(net_stream);
         let http_stream = match body {
@@ -134,7 +189,22 @@ impl Data {
             ChunkedReader(_, n) => ChunkedReader(inner_data, n)
         };
 
-        Ok(Data::new(http_stream))
+        match continuation {
+            Some(stream) => Ok(Data::deferred(http_stream, stream, read_timeout)),
+            None => {
+                let deadline = read_timeout.map(|timeout| Instant::now() + timeout);
+                let mut data = Data::new(http_stream, deadline);
+                data.expects_continue = expects_continue;
+                Ok(data)
+            }
+        }
+    }
+
+    /// Creates a data object from the body of a request received over HTTP/2.
+    #[cfg(feature = "http2")]
+    crate fn from_h2(body: http2::Body) -> Data {
+        let stream = Cursor::new(vec![]).chain(NetStream::Http2(body));
+        Data::new(EofReader(stream), None)
     }
 
     /// Retrieve the `peek` buffer.
@@ -182,6 +252,25 @@ impl Data {
         self.is_complete
     }
 
+    /// Returns `true` if the client sent an `Expect: 100-continue` header,
+    /// asking for permission before sending the body of the request.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::Data;
+    ///
+    /// fn handler(data: Data) {
+    ///     if data.expects_continue() {
+    ///         println!("The body will be requested when the data is opened.");
+    ///     }
+    /// }
+    /// ```
+    #[inline(always)]
+    pub fn expects_continue(&self) -> bool {
+        self.expects_continue
+    }
+
     /// A helper method to write the body of the request to any `Write` type.
     ///
     /// This method is identical to `io::copy(&mut data.open(), writer)`.
@@ -228,16 +317,18 @@ impl Data {
     // Creates a new data object with an internal buffer `buf`, where the cursor
     // in the buffer is at `pos` and the buffer has `cap` valid bytes. Thus, the
     // bytes `vec[pos..cap]` are buffered and unread. The remainder of the data
-    // bytes can be read from `stream`.
+    // bytes can be read from `stream`. Reading from the network times out at
+    // `deadline`.
     #[inline(always)]
-    crate fn new(mut stream: BodyReader) -> Data {
+    crate fn new(mut stream: BodyReader, deadline: Option<Instant>) -> Data {
         trace_!("Data::new({:?})", stream);
         let mut peek_buf: Vec<u8> = vec![0; PEEK_BYTES];
 
         // Fill the buffer with as many bytes as possible. If we read less than
         // that buffer's length, we know we reached the EOF. Otherwise, it's
         // unclear, so we just say we didn't reach EOF.
-        let eof = match stream.read_max(&mut peek_buf[..]) {
+        let timed_out = Arc::new(AtomicBool::new(false));
+        let eof = match Deadline(&mut stream, deadline).read_max(&mut peek_buf[..]) {
             Ok(n) => {
                 trace_!("Filled peek buf with {} bytes.", n);
                 // We can use `set_len` here instead of `truncate`, but we'll
@@ -248,6 +339,7 @@ impl Data {
             }
             Err(e) => {
                 error_!("Failed to read into peek buffer: {:?}.", e);
+                timed_out.store(is_timeout(&e), Ordering::Relaxed);
                 // Likewise here as above.
                 peek_buf.truncate(0);
                 false
@@ -255,7 +347,39 @@ impl Data {
         };
 
         trace_!("Peek bytes: {}/{} bytes.", peek_buf.len(), PEEK_BYTES);
-        Data { buffer: peek_buf, stream, is_complete: eof }
+        Data {
+            buffer: peek_buf,
+            stream,
+            is_complete: eof,
+            timed_out,
+            closed: Arc::new(AtomicBool::new(false)),
+            deadline,
+            expects_continue: false,
+            continuation: None,
+        }
+    }
+
+    // Creates a new data object for a request whose client is waiting for a
+    // `100 Continue` on `continuation` before sending the body. Nothing is
+    // read into the peek buffer since nothing can be read yet. The body must
+    // arrive within `read_timeout` of the `100 Continue` being sent.
+    #[inline]
+    crate fn deferred(
+        stream: BodyReader,
+        continuation: NetStream,
+        read_timeout: Option<Duration>
+    ) -> Data {
+        trace_!("Data::deferred({:?})", stream);
+        Data {
+            buffer: vec![],
+            stream,
+            is_complete: false,
+            timed_out: Arc::new(AtomicBool::new(false)),
+            closed: Arc::new(AtomicBool::new(false)),
+            deadline: None,
+            expects_continue: true,
+            continuation: Some((continuation, read_timeout)),
+        }
     }
 
     /// This creates a `data` object from a local data source `data`.
@@ -267,12 +391,52 @@ impl Data {
             buffer: data,
             stream: HttpReader::SizedReader(empty_stream, 0),
             is_complete: true,
+            timed_out: Arc::new(AtomicBool::new(false)),
+            closed: Arc::new(AtomicBool::new(false)),
+            deadline: None,
+            expects_continue: false,
+            continuation: None,
         }
     }
+
+    /// Returns `true` if reading the body from the network has timed out.
+    #[inline]
+    crate fn timed_out(&self) -> bool {
+        self.timed_out.load(Ordering::Relaxed)
+    }
+
+    /// Returns a flag that is set once reading the body from the network times
+    /// out, even after `self` has been opened and consumed.
+    #[inline]
+    crate fn timeout_flag(&self) -> Arc<AtomicBool> {
+        self.timed_out.clone()
+    }
+
+    /// Returns a flag that is set once the body is left unread and the read
+    /// half of the connection shut down as a result, even after `self` has
+    /// been opened and consumed. The connection mustn't be reused thereafter.
+    #[inline]
+    crate fn close_flag(&self) -> Arc<AtomicBool> {
+        self.closed.clone()
+    }
 }
 
 impl Drop for Data {
     fn drop(&mut self) {
-        kill_stream(&mut self.stream);
+        // The client never sent the body, so there's nothing to drain. The
+        // connection can't be reused, however: the client may yet send it.
+        if let Some((ref mut stream, _)) = self.continuation {
+            debug!("Request rejected before 100-continue. Closing network stream.");
+            if let Err(e) = stream.close(Shutdown::Read) {
+                error_!("Failed to close network stream: {:?}", e);
+            }
+
+            self.closed.store(true, Ordering::Relaxed);
+            return;
+        }
+
+        if kill_stream(&mut self.stream) {
+            self.closed.store(true, Ordering::Relaxed);
+        }
     }
 }
@@ -1,5 +1,8 @@
 use std::io::{self, Read, Cursor, Chain};
 use std::net::Shutdown;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
 
 use super::data::BodyReader;
 use http::hyper::net::NetworkStream;
@@ -14,7 +17,50 @@ pub type InnerStream = Chain<Cursor<Vec<u8>>, BodyReader>;
 /// [`Data::open()`](::data::Data::open()). The stream contains all of the data
 /// in the body of the request. It exposes no methods directly. Instead, it must
 /// be used as an opaque [`Read`] structure.
-pub struct DataStream(crate InnerStream);
+pub struct DataStream(
+    crate InnerStream,
+    crate Arc<AtomicBool>,
+    crate Option<Instant>,
+    crate Arc<AtomicBool>,
+);
+
+/// Reads from a request body, failing with `TimedOut` once the deadline, if
+/// any, has passed.
+crate struct Deadline<'a>(crate &'a mut BodyReader, crate Option<Instant>);
+
+/// Returns `true` if `error` is the result of a read or write timing out.
+#[inline]
+crate fn is_timeout(error: &io::Error) -> bool {
+    match error.kind() {
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => true,
+        _ => false
+    }
+}
+
+// Bounds the next read from the network in `stream` by `deadline`. Like the
+// head of a request, the body must arrive in its entirety before the deadline,
+// no matter how steadily it trickles in.
+fn set_deadline(stream: &BodyReader, deadline: Option<Instant>) -> io::Result<()> {
+    let deadline = match deadline {
+        Some(deadline) => deadline,
+        None => return Ok(())
+    };
+
+    let now = Instant::now();
+    if now >= deadline {
+        return Err(io::ErrorKind::TimedOut.into());
+    }
+
+    stream.get_ref().get_ref().1.set_read_timeout(Some(deadline - now))
+}
+
+impl<'a> Read for Deadline<'a> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        set_deadline(self.0, self.1)?;
+        self.0.read(buf)
+    }
+}
 
 // TODO: Have a `BufRead` impl for `DataStream`. At the moment, this isn't
 // possible since Hyper's `HttpReader` doesn't implement `BufRead`.
@@ -22,16 +68,36 @@ impl Read for DataStream {
     #[inline(always)]
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         trace_!("DataStream::read()");
-        self.0.read(buf)
+        // Only reads from the network are subject to the deadline: the peek
+        // buffer can be read from no matter how late.
+        let result = {
+            let (buffer, body) = self.0.get_mut();
+            match buffer.read(buf) {
+                Ok(0) if !buf.is_empty() => Deadline(body, self.2).read(buf),
+                result => result
+            }
+        };
+
+        if let Err(ref e) = result {
+            // Remember the timeout so Rocket can respond with a 408.
+            if is_timeout(e) {
+                self.1.store(true, Ordering::Relaxed);
+            }
+        }
+
+        result
     }
 }
 
-pub fn kill_stream(stream: &mut BodyReader) {
+// Drains what's left of the body in `stream`, if anything. If there is too much
+// left to drain, shuts down the read half of the connection instead. Returns
+// `true` if it did so, in which case the connection can't be reused.
+pub fn kill_stream(stream: &mut BodyReader) -> bool {
     // Only do the expensive reading if we're not sure we're done.
     use self::HttpReader::*;
     match *stream {
         SizedReader(_, n) | ChunkedReader(_, Some(n)) if n > 0 => { /* continue */ },
-        _ => return
+        _ => return false
     };
 
     // Take <= 1k from the stream. If there might be more data, force close.
@@ -43,13 +109,20 @@ pub fn kill_stream(stream: &mut BodyReader) {
             if let Err(e) = network.close(Shutdown::Read) {
                 error_!("Failed to close network stream: {:?}", e);
             }
+
+            true
+        }
+        Ok(n) => {
+            debug!("flushed {} unread bytes", n);
+            false
         }
-        Ok(n) => debug!("flushed {} unread bytes", n)
     }
 }
 
 impl Drop for DataStream {
     fn drop(&mut self) {
-        kill_stream(&mut self.0.get_mut().1);
+        if kill_stream(&mut self.0.get_mut().1) {
+            self.3.store(true, Ordering::Relaxed);
+        }
     }
 }
@@ -6,5 +6,7 @@ mod net_stream;
 mod from_data;
 
 pub use self::data::Data;
+crate use self::net_stream::NetStream;
 pub use self::data_stream::DataStream;
+crate use self::data_stream::is_timeout;
 pub use self::from_data::{FromData, FromDataSimple, Outcome, Transform, Transformed};
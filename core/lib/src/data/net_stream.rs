@@ -1,13 +1,17 @@
 use std::io;
 use std::net::{SocketAddr, Shutdown};
 use std::time::Duration;
+#[cfg(unix)] use std::sync::Arc;
+#[cfg(unix)] use std::os::unix::net::UnixStream;
+#[cfg(unix)] use std::os::unix::io::{AsRawFd, RawFd};
 
-#[cfg(feature = "tls")] use http::tls::{WrappedStream, ServerSession};
+#[cfg(feature = "tls")] use http::tls::TlsStream;
 use http::hyper::net::{HttpStream, NetworkStream};
+#[cfg(feature = "http2")] use http2;
 
 use self::NetStream::*;
 
-#[cfg(feature = "tls")] pub type HttpsStream = WrappedStream<ServerSession>;
+#[cfg(feature = "tls")] pub type HttpsStream = TlsStream;
 
 // This is a representation of all of the possible network streams we might get.
 // This really shouldn't be necessary, but, you know, Hyper.
@@ -16,9 +20,48 @@ pub enum NetStream {
     Http(HttpStream),
     #[cfg(feature = "tls")]
     Https(HttpsStream),
+    #[cfg(unix)]
+    Unix(Arc<UnixStream>),
+    /// The body of a request received over HTTP/2. It can only be read.
+    #[cfg(feature = "http2")]
+    Http2(http2::Body),
     Empty,
 }
 
+impl From<HttpStream> for NetStream {
+    #[inline(always)]
+    fn from(stream: HttpStream) -> NetStream {
+        Http(stream)
+    }
+}
+
+#[cfg(feature = "tls")]
+impl From<HttpsStream> for NetStream {
+    #[inline(always)]
+    fn from(stream: HttpsStream) -> NetStream {
+        Https(stream)
+    }
+}
+
+impl NetStream {
+    /// Returns the raw file descriptor of the underlying socket if readiness
+    /// of the socket reflects readiness of the stream. TLS streams buffer data
+    /// internally, so their sockets are only pollable while nothing is.
+    #[cfg(unix)]
+    crate fn pollable_fd(&self) -> Option<RawFd> {
+        match *self {
+            Http(ref stream) => Some(stream.0.as_raw_fd()),
+            #[cfg(feature = "tls")]
+            Https(ref stream) if stream.is_drained() => Some(stream.as_raw_fd()),
+            #[cfg(feature = "tls")]
+            Https(_) => None,
+            Unix(ref stream) => Some(stream.as_raw_fd()),
+            #[cfg(feature = "http2")] Http2(_) => None,
+            Empty => None,
+        }
+    }
+}
+
 impl io::Read for NetStream {
     #[inline(always)]
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
@@ -26,6 +69,8 @@ impl io::Read for NetStream {
         let res = match *self {
             Http(ref mut stream) => stream.read(buf),
             #[cfg(feature = "tls")] Https(ref mut stream) => stream.read(buf),
+            #[cfg(unix)] Unix(ref stream) => (&**stream).read(buf),
+            #[cfg(feature = "http2")] Http2(ref mut body) => body.read(buf),
             Empty => Ok(0),
         };
 
@@ -41,6 +86,8 @@ impl io::Write for NetStream {
         match *self {
             Http(ref mut stream) => stream.write(buf),
             #[cfg(feature = "tls")] Https(ref mut stream) => stream.write(buf),
+            #[cfg(unix)] Unix(ref stream) => (&**stream).write(buf),
+            #[cfg(feature = "http2")] Http2(_) => Ok(0),
             Empty => Ok(0),
         }
     }
@@ -50,6 +97,8 @@ impl io::Write for NetStream {
         match *self {
             Http(ref mut stream) => stream.flush(),
             #[cfg(feature = "tls")] Https(ref mut stream) => stream.flush(),
+            #[cfg(unix)] Unix(ref stream) => (&**stream).flush(),
+            #[cfg(feature = "http2")] Http2(_) => Ok(()),
             Empty => Ok(()),
         }
     }
@@ -61,6 +110,9 @@ impl NetworkStream for NetStream {
         match *self {
             Http(ref mut stream) => stream.peer_addr(),
             #[cfg(feature = "tls")] Https(ref mut stream) => stream.peer_addr(),
+            #[cfg(unix)] Unix(_) => Err(io::Error::from(io::ErrorKind::AddrNotAvailable)),
+            #[cfg(feature = "http2")]
+            Http2(_) => Err(io::Error::from(io::ErrorKind::AddrNotAvailable)),
             Empty => Err(io::Error::from(io::ErrorKind::AddrNotAvailable)),
         }
     }
@@ -70,6 +122,9 @@ impl NetworkStream for NetStream {
         match *self {
             Http(ref stream) => stream.set_read_timeout(dur),
             #[cfg(feature = "tls")] Https(ref stream) => stream.set_read_timeout(dur),
+            #[cfg(unix)] Unix(ref stream) => stream.set_read_timeout(dur),
+            // The timeouts of an HTTP/2 connection are managed by its server.
+            #[cfg(feature = "http2")] Http2(_) => Ok(()),
             Empty => Ok(()),
         }
     }
@@ -79,6 +134,8 @@ impl NetworkStream for NetStream {
         match *self {
             Http(ref stream) => stream.set_write_timeout(dur),
             #[cfg(feature = "tls")] Https(ref stream) => stream.set_write_timeout(dur),
+            #[cfg(unix)] Unix(ref stream) => stream.set_write_timeout(dur),
+            #[cfg(feature = "http2")] Http2(_) => Ok(()),
             Empty => Ok(()),
         }
     }
@@ -88,6 +145,9 @@ impl NetworkStream for NetStream {
         match *self {
             Http(ref mut stream) => stream.close(how),
             #[cfg(feature = "tls")] Https(ref mut stream) => stream.close(how),
+            #[cfg(unix)] Unix(ref stream) => stream.shutdown(how),
+            // Unread data is discarded once the stream completes.
+            #[cfg(feature = "http2")] Http2(_) => Ok(()),
             Empty => Ok(()),
         }
     }
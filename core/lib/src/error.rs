@@ -46,13 +46,14 @@ pub enum LaunchErrorKind {
 ///
 /// ```rust
 /// # if false {
-/// let error = rocket::ignite().launch();
+/// if let Err(error) = rocket::ignite().launch() {
+///     // This line is only reached if launching failed. This "inspects" the
+///     // error.
+///     println!("Launch failed! Error: {}", error);
 ///
-/// // This line is only reached if launching failed. This "inspects" the error.
-/// println!("Launch failed! Error: {}", error);
-///
-/// // This call to drop (explicit here for demonstration) will do nothing.
-/// drop(error);
+///     // This call to drop (explicit here for demonstration) will do nothing.
+///     drop(error);
+/// }
 /// # }
 /// ```
 ///
@@ -61,11 +62,11 @@ pub enum LaunchErrorKind {
 ///
 /// ```rust
 /// # if false {
-/// let error = rocket::ignite().launch();
-///
-/// // This call to drop (explicit here for demonstration) will result in
-/// // `error` being pretty-printed to the console along with a `panic!`.
-/// drop(error);
+/// if let Err(error) = rocket::ignite().launch() {
+///     // This call to drop (explicit here for demonstration) will result in
+///     // `error` being pretty-printed to the console along with a `panic!`.
+///     drop(error);
+/// }
 /// # }
 /// ```
 ///
@@ -107,10 +108,10 @@ impl LaunchError {
     ///
     /// ```rust
     /// # if false {
-    /// let error = rocket::ignite().launch();
-    ///
-    /// // This line is only reached if launch failed.
-    /// let error_kind = error.kind();
+    /// if let Err(error) = rocket::ignite().launch() {
+    ///     // This line is only reached if launch failed.
+    ///     let error_kind = error.kind();
+    /// }
     /// # }
     /// ```
     #[inline]
@@ -233,6 +234,8 @@ pub enum RouteUriError {
     Uri(uri::Error<'static>),
     /// The base (mount point) contains dynamic segments.
     DynamicBase,
+    /// The host pattern is empty or contains invalid labels.
+    Host,
 }
 
 impl<'a> From<(&'a str, SegmentError<'a>)> for RouteUriError {
@@ -256,9 +259,72 @@ impl fmt::Display for RouteUriError {
             RouteUriError::DynamicBase => {
                 write!(f, "The mount point contains dynamic parameters.")
             }
+            RouteUriError::Host => {
+                write!(f, "The host pattern contains malformed labels.")
+            }
             RouteUriError::Uri(error) => {
                 write!(f, "Malformed URI: {}", error)
             }
         }
     }
 }
+
+/// Error returned by [`Rocket::url_for()`](::Rocket::url_for()) and
+/// [`Request::url_for()`](::Request::url_for()) when a URI can't be generated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UrlForError {
+    /// No mounted route has the given name.
+    UnknownRoute(String),
+    /// More than one mounted route has the given name, for instance because
+    /// it was mounted at several mount points. Use
+    /// [`Rocket::mounted_url_for()`](::Rocket::mounted_url_for()) to pick one.
+    AmbiguousRoute(String),
+    /// The route has the given host pattern. An origin URI can't name a host,
+    /// so it can't express that the route only matches requests for that host.
+    HostRoute(String),
+    /// No value was given for the named dynamic path parameter.
+    MissingParameter(String),
+    /// The value given for the named dynamic path parameter is empty or
+    /// doesn't satisfy the parameter's constraint, so the route would never
+    /// match the URI.
+    InvalidParameter(String),
+    /// A value was given for the named parameter, which is not a parameter of
+    /// the route, and the route has no trailing query parameter to take it.
+    UnknownParameter(String),
+}
+
+impl fmt::Display for UrlForError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UrlForError::UnknownRoute(name) => write!(f, "No route is named '{}'.", name),
+            UrlForError::AmbiguousRoute(name) => {
+                write!(f, "Several routes are named '{}'.", name)
+            }
+            UrlForError::HostRoute(host) => {
+                write!(f, "The route only matches requests for host '{}'.", host)
+            }
+            UrlForError::MissingParameter(name) => {
+                write!(f, "No value was given for parameter '{}'.", name)
+            }
+            UrlForError::InvalidParameter(name) => {
+                write!(f, "The value for parameter '{}' is empty or violates its constraint.", name)
+            }
+            UrlForError::UnknownParameter(name) => {
+                write!(f, "The route has no parameter '{}'.", name)
+            }
+        }
+    }
+}
+
+impl ::std::error::Error for UrlForError {
+    fn description(&self) -> &str {
+        match self {
+            UrlForError::UnknownRoute(_) => "unknown route",
+            UrlForError::AmbiguousRoute(_) => "ambiguous route",
+            UrlForError::HostRoute(_) => "route has a host pattern",
+            UrlForError::MissingParameter(_) => "missing parameter",
+            UrlForError::InvalidParameter(_) => "invalid parameter",
+            UrlForError::UnknownParameter(_) => "unknown parameter",
+        }
+    }
+}
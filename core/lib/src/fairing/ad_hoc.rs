@@ -1,3 +1,4 @@
+use std::any::Any;
 use std::sync::Mutex;
 
 use {Rocket, Request, Response, Data};
@@ -11,7 +12,8 @@ use fairing::{Fairing, Kind, Info};
 /// # Usage
 ///
 /// Use the [`on_attach`](#method.on_attach), [`on_launch`](#method.on_launch),
-/// [`on_request`](#method.on_request), or [`on_response`](#method.on_response)
+/// [`on_request`](#method.on_request), [`on_response`](#method.on_response),
+/// [`on_panic`](#method.on_panic), or [`on_shutdown`](#method.on_shutdown)
 /// constructors to create an `AdHoc` structure from a function or closure.
 /// Then, simply attach the structure to the `Rocket` instance.
 ///
@@ -50,6 +52,10 @@ enum AdHocKind {
     /// An ad-hoc **response** fairing. Called when a response is ready to be
     /// sent to a client.
     Response(Box<dyn Fn(&Request, &mut Response) + Send + Sync + 'static>),
+    /// An ad-hoc **panic** fairing. Called when processing a request panics.
+    Panic(Box<dyn Fn(&Request, &(dyn Any + Send)) + Send + Sync + 'static>),
+    /// An ad-hoc **shutdown** fairing. Called when Rocket shuts down.
+    Shutdown(Mutex<Option<Box<dyn FnOnce(&Rocket) + Send + 'static>>>),
 }
 
 impl AdHoc {
@@ -128,6 +134,48 @@ impl AdHoc {
     {
         AdHoc { name, kind: AdHocKind::Response(Box::new(f)) }
     }
+
+    /// Constructs an `AdHoc` panic fairing named `name`. The function `f` will
+    /// be called by Rocket when processing a request panics with the request
+    /// and the panic's payload.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::fairing::AdHoc;
+    ///
+    /// // A fairing that reports the message of panics to standard error.
+    /// let fairing = AdHoc::on_panic("Reporter", |req, payload| {
+    ///     let message = payload.downcast_ref::<&str>().map(|s| *s)
+    ///         .or_else(|| payload.downcast_ref::<String>().map(|s| s.as_str()));
+    ///
+    ///     eprintln!("{} panicked: {:?}", req, message);
+    /// });
+    /// ```
+    pub fn on_panic<F>(name: &'static str, f: F) -> AdHoc
+        where F: Fn(&Request, &(dyn Any + Send)) + Send + Sync + 'static
+    {
+        AdHoc { name, kind: AdHocKind::Panic(Box::new(f)) }
+    }
+
+    /// Constructs an `AdHoc` shutdown fairing named `name`. The function `f`
+    /// will be called by Rocket when the application shuts down.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::fairing::AdHoc;
+    ///
+    /// // A fairing that prints a message when the application shuts down.
+    /// let fairing = AdHoc::on_shutdown("Goodbye", |rocket| {
+    ///     println!("Shutting down. Goodbye!");
+    /// });
+    /// ```
+    pub fn on_shutdown<F>(name: &'static str, f: F) -> AdHoc
+        where F: FnOnce(&Rocket) + Send + 'static
+    {
+        AdHoc { name, kind: AdHocKind::Shutdown(Mutex::new(Some(Box::new(f)))) }
+    }
 }
 
 impl Fairing for AdHoc {
@@ -137,6 +185,8 @@ impl Fairing for AdHoc {
             AdHocKind::Launch(_) => Kind::Launch,
             AdHocKind::Request(_) => Kind::Request,
             AdHocKind::Response(_) => Kind::Response,
+            AdHocKind::Panic(_) => Kind::Panic,
+            AdHocKind::Shutdown(_) => Kind::Shutdown,
         };
 
         Info { name: self.name, kind }
@@ -171,4 +221,18 @@ impl Fairing for AdHoc {
             callback(request, response)
         }
     }
+
+    fn on_panic(&self, request: &Request, payload: &(dyn Any + Send)) {
+        if let AdHocKind::Panic(ref callback) = self.kind {
+            callback(request, payload)
+        }
+    }
+
+    fn on_shutdown(&self, rocket: &Rocket) {
+        if let AdHocKind::Shutdown(ref mutex) = self.kind {
+            let mut opt = mutex.lock().expect("AdHoc::Shutdown lock");
+            let f = opt.take().expect("internal error: `on_shutdown` one-call invariant broken");
+            f(rocket)
+        }
+    }
 }
@@ -1,3 +1,5 @@
+use std::any::Any;
+
 use {Rocket, Request, Response, Data};
 use fairing::{Fairing, Kind};
 
@@ -11,6 +13,8 @@ pub struct Fairings {
     launch: Vec<usize>,
     request: Vec<usize>,
     response: Vec<usize>,
+    panic: Vec<usize>,
+    shutdown: Vec<usize>,
 }
 
 impl Fairings {
@@ -41,6 +45,8 @@ impl Fairings {
             if kind.is(Kind::Launch) { self.launch.push(index); }
             if kind.is(Kind::Request) { self.request.push(index); }
             if kind.is(Kind::Response) { self.response.push(index); }
+            if kind.is(Kind::Panic) { self.panic.push(index); }
+            if kind.is(Kind::Shutdown) { self.shutdown.push(index); }
         }
     }
 
@@ -71,6 +77,20 @@ impl Fairings {
         }
     }
 
+    #[inline(always)]
+    pub fn handle_panic(&self, request: &Request, payload: &(dyn Any + Send)) {
+        for &i in &self.panic {
+            self.all_fairings[i].on_panic(request, payload);
+        }
+    }
+
+    #[inline(always)]
+    pub fn handle_shutdown(&self, rocket: &Rocket) {
+        for &i in &self.shutdown {
+            self.all_fairings[i].on_shutdown(rocket);
+        }
+    }
+
     pub fn failures(&self) -> Option<&[&'static str]> {
         if self.attach_failures.is_empty() {
             None
@@ -97,6 +117,8 @@ impl Fairings {
             self.info_for("launch", &self.launch);
             self.info_for("request", &self.request);
             self.info_for("response", &self.response);
+            self.info_for("panic", &self.panic);
+            self.info_for("shutdown", &self.shutdown);
         }
     }
 }
@@ -1,4 +1,5 @@
-//! Fairings: callbacks at attach, launch, request, and response time.
+//! Fairings: callbacks at attach, launch, request, response, panic, and
+//! shutdown time.
 //!
 //! Fairings allow for structured interposition at various points in the
 //! application lifetime. Fairings can be seen as a restricted form of
@@ -47,6 +48,8 @@
 //! of other `Fairings` are not jeopardized. For instance, unless it is made
 //! abundantly clear, a fairing should not rewrite every request.
 
+use std::any::Any;
+
 use {Rocket, Request, Response, Data};
 
 mod fairings;
@@ -65,9 +68,10 @@ pub use self::info_kind::{Info, Kind};
 // encourages implicit handling, a bad practice. Fairings can still, however,
 // return a default `Response` if routing fails via a response fairing. For
 // instance, to automatically handle preflight in CORS, a response fairing can
-// check that the user didn't handle the `OPTIONS` request (404) and return an
-// appropriate response. This allows the users to handle `OPTIONS` requests
-// when they'd like but default to the fairing when they don't want to.
+// add the appropriate headers to the response Rocket autogenerates for
+// `OPTIONS` requests the user didn't handle. This allows the users to handle
+// `OPTIONS` requests when they'd like but default to the fairing when they
+// don't want to.
 
 /// Trait implemented by fairings: Rocket's structured middleware.
 ///
@@ -94,12 +98,13 @@ pub use self::info_kind::{Info, Kind};
 ///
 /// ## Fairing Callbacks
 ///
-/// There are four kinds of fairing callbacks: attach, launch, request, and
-/// response. A fairing can request any combination of these callbacks through
-/// the `kind` field of the `Info` structure returned from the `info` method.
-/// Rocket will only invoke the callbacks set in the `kind` field.
+/// There are six kinds of fairing callbacks: attach, launch, request,
+/// response, panic, and shutdown. A fairing can request any combination of
+/// these callbacks through the `kind` field of the `Info` structure returned
+/// from the `info` method. Rocket will only invoke the callbacks set in the
+/// `kind` field.
 ///
-/// The four callback kinds are as follows:
+/// The six callback kinds are as follows:
 ///
 ///   * **Attach (`on_attach`)**
 ///
@@ -136,7 +141,7 @@ pub use self::info_kind::{Info, Kind};
 ///     to the request; these issues are better handled via [request guards] or
 ///     via response callbacks. Any modifications to a request are persisted and
 ///     can potentially alter how a request is routed.
-///=
+///
 ///   * **Response (`on_response`)**
 ///
 ///     A response callback, represented by the [`Fairing::on_response()`]
@@ -152,13 +157,35 @@ pub use self::info_kind::{Info, Kind};
 ///     request. Additionally, Rocket will automatically strip the body for
 ///     `HEAD` requests _after_ response fairings have run.
 ///
+///   * **Panic (`on_panic`)**
+///
+///     A panic callback, represented by the [`Fairing::on_panic()`] method, is
+///     called when a request guard, data guard, or handler panics while
+///     processing a request. At this point, Rocket has caught the panic and
+///     logged it but has not yet generated a response. A panic callback
+///     receives the request being processed and the panic's payload, making
+///     it suitable for error reporting. Once all panic callbacks have run,
+///     Rocket responds with the `500` error catcher. Response callbacks are
+///     then run as usual.
+///
+///   * **Shutdown (`on_shutdown`)**
+///
+///     A shutdown callback, represented by the [`Fairing::on_shutdown()`]
+///     method, is called when a launched Rocket application shuts down via
+///     [`Shutdown::notify()`](::Shutdown::notify()). At this point, Rocket has
+///     stopped accepting connections and in-flight requests have either
+///     completed or exceeded the shutdown grace period. A shutdown callback can
+///     inspect the `Rocket` instance and release any resources it holds.
+///     [`Rocket::launch()`] returns once all shutdown callbacks have completed.
+///
 /// # Implementing
 ///
 /// A `Fairing` implementation has one required method: [`info`]. A `Fairing`
 /// can also implement any of the available callbacks: `on_attach`, `on_launch`,
-/// `on_request`, and `on_response`. A `Fairing` _must_ set the appropriate
-/// callback kind in the `kind` field of the returned `Info` structure from
-/// [`info`] for a callback to actually be called by Rocket.
+/// `on_request`, `on_response`, `on_panic`, and `on_shutdown`. A `Fairing`
+/// _must_ set the appropriate callback kind in the `kind` field of the returned
+/// `Info` structure from [`info`] for a callback to actually be called by
+/// Rocket.
 ///
 /// ## Fairing `Info`
 ///
@@ -409,6 +436,33 @@ pub trait Fairing: Send + Sync + 'static {
     /// The default implementation of this method does nothing.
     #[allow(unused_variables)]
     fn on_response(&self, request: &Request, response: &mut Response) {}
+
+    /// The panic callback.
+    ///
+    /// This method is called when processing a request panics if `Kind::Panic`
+    /// is in the `kind` field of the `Info` structure for this fairing. The
+    /// `&Request` parameter is the request being processed, and `payload` is
+    /// the payload of the panic. The payload is typically a `&'static str` or
+    /// a `String` containing the panic message.
+    ///
+    /// ## Default Implementation
+    ///
+    /// The default implementation of this method does nothing.
+    #[allow(unused_variables)]
+    fn on_panic(&self, request: &Request, payload: &(dyn Any + Send)) {}
+
+    /// The shutdown callback.
+    ///
+    /// This method is called when the application shuts down, after it has
+    /// stopped accepting connections, if `Kind::Shutdown` is in the `kind`
+    /// field of the `Info` structure for this fairing. The `&Rocket` parameter
+    /// corresponds to the application that is shutting down.
+    ///
+    /// ## Default Implementation
+    ///
+    /// The default implementation of this method does nothing.
+    #[allow(unused_variables)]
+    fn on_shutdown(&self, rocket: &Rocket) {}
 }
 
 impl<T: Fairing> Fairing for ::std::sync::Arc<T> {
@@ -436,4 +490,14 @@ impl<T: Fairing> Fairing for ::std::sync::Arc<T> {
     fn on_response(&self, request: &Request, response: &mut Response) {
         (self as &T).on_response(request, response)
     }
+
+    #[inline]
+    fn on_panic(&self, request: &Request, payload: &(dyn Any + Send)) {
+        (self as &T).on_panic(request, payload)
+    }
+
+    #[inline]
+    fn on_shutdown(&self, rocket: &Rocket) {
+        (self as &T).on_shutdown(rocket)
+    }
 }
@@ -1,7 +1,7 @@
 //! Types and traits for request and error handlers and their return values.
 
 use data::Data;
-use request::Request;
+use request::{Request, BadRequestInfo};
 use response::{self, Response, Responder};
 use http::Status;
 use outcome;
@@ -181,6 +181,15 @@ impl<F: Clone + Sync + Send + 'static> Handler for F
 /// The type of an error handler.
 pub type ErrorHandler = for<'r> fn(&'r Request) -> response::Result<'r>;
 
+/// The type of a bad request handler.
+///
+/// A bad request handler is called with a [`BadRequestInfo`] when an incoming
+/// request cannot be parsed. Its response is sent to the client, after which
+/// the connection is closed. See [`Rocket::on_bad_request()`] for details.
+///
+/// [`Rocket::on_bad_request()`]: ::Rocket::on_bad_request()
+pub type BadRequestHandler = fn(&BadRequestInfo) -> Response<'static>;
+
 impl<'r> Outcome<'r> {
     /// Return the `Outcome` of response to `req` from `responder`.
     ///
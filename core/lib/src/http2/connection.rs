@@ -0,0 +1,552 @@
+use std::cmp::min;
+use std::collections::VecDeque;
+use std::io::{self, BufReader, Chain, Cursor, Read, Write};
+use std::net::Shutdown;
+
+use hpack;
+
+use data::NetStream;
+use super::frame::{self, *};
+
+use http::hyper::net::NetworkStream;
+
+/// The client connection preface. (RFC 7540, Section 3.5)
+crate const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+/// A header block as decoded by HPACK: a list of name-value pairs.
+crate type Fields = Vec<(Vec<u8>, Vec<u8>)>;
+
+/// The state of the one stream that is being served at any given time.
+struct Stream {
+    id: u32,
+    send_window: i64,
+    recv_window: i64,
+    // Bytes consumed from the stream but not yet credited back to the client.
+    unacked: u32,
+    data: VecDeque<u8>,
+    // The client has sent all of its data, i.e, `END_STREAM`.
+    remote_closed: bool,
+    // The client reset the stream with `RST_STREAM`.
+    reset: bool,
+}
+
+/// A header block that is being received across `CONTINUATION` frames.
+struct PartialHeaders {
+    stream: u32,
+    end_stream: bool,
+    block: Vec<u8>,
+}
+
+/// The server side of an HTTP/2 connection. Streams are served one at a time:
+/// clients are told so via `SETTINGS_MAX_CONCURRENT_STREAMS`, and streams that
+/// are opened regardless are refused.
+crate struct Connection {
+    reader: BufReader<Chain<Cursor<Vec<u8>>, NetStream>>,
+    writer: NetStream,
+    decoder: hpack::Decoder<'static>,
+    max_header_block: usize,
+    // The client's settings.
+    max_frame: u32,
+    initial_window: u32,
+    // Connection-level flow control.
+    send_window: i64,
+    recv_window: i64,
+    unacked: u32,
+    stream: Option<Stream>,
+    partial: Option<PartialHeaders>,
+    pending: Option<(u32, Fields)>,
+    last_stream: u32,
+    goaway: bool,
+    failed: Option<u32>,
+}
+
+impl Connection {
+    /// Creates a connection over `stream`. `buffered` contains the bytes that
+    /// were already read from `stream`, if any. Header blocks larger than
+    /// `max_header_block` are refused.
+    crate fn new(stream: NetStream, buffered: Vec<u8>, max_header_block: usize) -> Connection {
+        Connection {
+            reader: BufReader::new(Cursor::new(buffered).chain(stream.clone())),
+            writer: stream,
+            decoder: hpack::Decoder::new(),
+            max_header_block,
+            max_frame: DEFAULT_MAX_FRAME,
+            initial_window: DEFAULT_WINDOW,
+            send_window: DEFAULT_WINDOW as i64,
+            recv_window: DEFAULT_WINDOW as i64,
+            unacked: 0,
+            stream: None,
+            partial: None,
+            pending: None,
+            last_stream: 0,
+            goaway: false,
+            failed: None,
+        }
+    }
+
+    /// Reads the client's connection preface and exchanges settings.
+    crate fn handshake(&mut self) -> Result<(), Error> {
+        let mut preface = [0u8; 24];
+        self.reader.read_exact(&mut preface)?;
+        if preface != PREFACE {
+            return Err(Error::Protocol(PROTOCOL_ERROR, "invalid connection preface"));
+        }
+
+        let max_header_list = min(self.max_header_block, MAX_WINDOW as usize) as u32;
+        let ours = [
+            (SETTINGS_MAX_CONCURRENT_STREAMS, 1),
+            (SETTINGS_MAX_HEADER_LIST_SIZE, max_header_list),
+        ];
+
+        let mut settings = vec![];
+        for &(id, value) in &ours {
+            settings.extend_from_slice(&[(id >> 8) as u8, id as u8]);
+            settings.extend_from_slice(&u32_bytes(value));
+        }
+
+        self.write(SETTINGS, 0, 0, &settings)?;
+        let frame = Frame::read(&mut self.reader, DEFAULT_MAX_FRAME)?;
+        if frame.kind != SETTINGS || frame.has(ACK) {
+            return Err(Error::Protocol(PROTOCOL_ERROR, "expected SETTINGS frame"));
+        }
+
+        self.handle(frame)
+    }
+
+    /// Returns `true` if the client has announced that it's going away.
+    #[inline]
+    crate fn is_going_away(&self) -> bool {
+        self.goaway
+    }
+
+    /// Returns the error code of the protocol violation that was encountered
+    /// while serving a stream, if any.
+    #[inline]
+    crate fn failure(&self) -> Option<u32> {
+        self.failed
+    }
+
+    /// Waits for the client to open the next stream, returning its id and
+    /// request headers. Returns `None` if the client closed the connection or
+    /// announced that it's going away.
+    crate fn next_request(&mut self) -> Result<Option<(u32, Fields)>, Error> {
+        loop {
+            if let Some(request) = self.pending.take() {
+                return Ok(Some(request));
+            }
+
+            if self.goaway {
+                return Ok(None);
+            }
+
+            match self.poll() {
+                Ok(()) => continue,
+                Err(Error::Io(ref e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    return Ok(None);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Reads the request body of stream `id` into `buf`.
+    crate fn read_body(&mut self, id: u32, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let n = match self.stream {
+                Some(ref mut stream) if stream.id == id => {
+                    if stream.reset {
+                        return Err(reset_error());
+                    }
+
+                    if stream.data.is_empty() && !stream.remote_closed {
+                        None
+                    } else {
+                        let n = min(buf.len(), stream.data.len());
+                        for (byte, value) in buf.iter_mut().zip(stream.data.drain(..n)) {
+                            *byte = value;
+                        }
+
+                        Some(n)
+                    }
+                }
+                _ => return Ok(0)
+            };
+
+            match n {
+                Some(n) => {
+                    self.consumed(n as u32)?;
+                    return Ok(n);
+                }
+                None => self.poll().map_err(|e| self.fail(e))?,
+            }
+        }
+    }
+
+    /// Sends `block` as the response headers of stream `id`.
+    crate fn send_headers(&mut self, id: u32, block: &[u8], end_stream: bool) -> io::Result<()> {
+        let mut chunks = block.chunks(self.max_frame as usize).peekable();
+        let mut kind = HEADERS;
+        let mut flags = if end_stream { END_STREAM } else { 0 };
+        loop {
+            let chunk = chunks.next().unwrap_or(&[]);
+            if chunks.peek().is_none() {
+                flags |= END_HEADERS;
+            }
+
+            self.write(kind, flags, id, chunk)?;
+            if flags & END_HEADERS != 0 {
+                return self.writer.flush();
+            }
+
+            kind = CONTINUATION;
+            flags = 0;
+        }
+    }
+
+    /// Sends `data` on stream `id`, waiting for the client to open its flow
+    /// control windows as needed.
+    crate fn send_data(&mut self, id: u32, mut data: &[u8], end_stream: bool) -> io::Result<()> {
+        loop {
+            let available = match self.stream {
+                Some(ref stream) if stream.id == id && !stream.reset => {
+                    min(self.send_window, stream.send_window)
+                }
+                _ => return Err(reset_error()),
+            };
+
+            let len = min(min(available.max(0) as usize, self.max_frame as usize), data.len());
+            if len == 0 && !data.is_empty() {
+                self.poll().map_err(|e| self.fail(e))?;
+                continue;
+            }
+
+            let end = if end_stream && len == data.len() { END_STREAM } else { 0 };
+            self.write(DATA, end, id, &data[..len])?;
+            self.send_window -= len as i64;
+            if let Some(ref mut stream) = self.stream {
+                stream.send_window -= len as i64;
+            }
+
+            data = &data[len..];
+            if data.is_empty() {
+                return self.writer.flush();
+            }
+        }
+    }
+
+    /// Completes stream `id`. The stream is reset with `code` unless `code` is
+    /// `NO_ERROR` and the client is done sending data.
+    crate fn finish(&mut self, id: u32, code: u32) -> io::Result<()> {
+        let stream = match self.stream.take() {
+            Some(stream) => stream,
+            None => return Ok(()),
+        };
+
+        if stream.id != id {
+            self.stream = Some(stream);
+            return Ok(());
+        }
+
+        // Credit the connection with the data that will never be read.
+        self.credit(stream.data.len() as u32)?;
+        if !stream.reset && (code != NO_ERROR || !stream.remote_closed) {
+            self.write(RST_STREAM, 0, id, &u32_bytes(code))?;
+        }
+
+        self.writer.flush()
+    }
+
+    /// Closes the connection with a `GOAWAY` carrying `code`.
+    crate fn close(&mut self, code: u32) {
+        let last_stream = self.last_stream;
+        let mut payload = u32_bytes(last_stream).to_vec();
+        payload.extend_from_slice(&u32_bytes(code));
+        let result = self.write(GOAWAY, 0, 0, &payload)
+            .and_then(|_| self.writer.flush())
+            .and_then(|_| self.writer.close(Shutdown::Both));
+
+        if let Err(e) = result {
+            debug!("Failed to close HTTP/2 connection: {:?}", e);
+        }
+    }
+
+    // Records a protocol violation so that the connection is closed with the
+    // appropriate error code once the current stream completes.
+    fn fail(&mut self, error: Error) -> io::Error {
+        match error {
+            Error::Io(e) => e,
+            Error::Protocol(code, reason) => {
+                self.failed = Some(code);
+                io::Error::new(io::ErrorKind::InvalidData, reason)
+            }
+        }
+    }
+
+    #[inline]
+    fn write(&mut self, kind: u8, flags: u8, stream: u32, payload: &[u8]) -> io::Result<()> {
+        frame::write(&mut self.writer, kind, flags, stream, payload)
+    }
+
+    // Credits the connection with `n` bytes of received data, sending a
+    // `WINDOW_UPDATE` once enough credit has accumulated.
+    fn credit(&mut self, n: u32) -> io::Result<()> {
+        self.unacked += n;
+        if self.unacked >= DEFAULT_WINDOW / 2 {
+            let increment = ::std::mem::replace(&mut self.unacked, 0);
+            self.recv_window += increment as i64;
+            self.write(WINDOW_UPDATE, 0, 0, &u32_bytes(increment))?;
+        }
+
+        Ok(())
+    }
+
+    // Credits the connection and the current stream with `n` consumed bytes.
+    fn consumed(&mut self, n: u32) -> io::Result<()> {
+        self.credit(n)?;
+        let update = match self.stream {
+            Some(ref mut stream) if !stream.remote_closed => {
+                stream.unacked += n;
+                if stream.unacked >= DEFAULT_WINDOW / 2 {
+                    let increment = ::std::mem::replace(&mut stream.unacked, 0);
+                    stream.recv_window += increment as i64;
+                    Some((stream.id, increment))
+                } else {
+                    None
+                }
+            }
+            _ => None
+        };
+
+        match update {
+            Some((id, increment)) => self.write(WINDOW_UPDATE, 0, id, &u32_bytes(increment)),
+            None => Ok(())
+        }
+    }
+
+    /// Reads and handles a single frame.
+    fn poll(&mut self) -> Result<(), Error> {
+        let frame = Frame::read(&mut self.reader, DEFAULT_MAX_FRAME)?;
+        self.handle(frame)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    fn handle(&mut self, frame: Frame) -> Result<(), Error> {
+        // A header block must be received contiguously.
+        if let Some(ref partial) = self.partial {
+            if frame.kind != CONTINUATION || frame.stream != partial.stream {
+                return Err(Error::Protocol(PROTOCOL_ERROR, "expected CONTINUATION frame"));
+            }
+        }
+
+        match frame.kind {
+            DATA => self.handle_data(frame),
+            HEADERS => self.handle_headers(frame),
+            CONTINUATION => self.handle_continuation(frame),
+            PRIORITY => {
+                expect(frame.stream != 0, PROTOCOL_ERROR, "PRIORITY on stream 0")?;
+                expect(frame.payload.len() == 5, FRAME_SIZE_ERROR, "invalid PRIORITY frame")
+            }
+            RST_STREAM => {
+                expect(frame.stream != 0, PROTOCOL_ERROR, "RST_STREAM on stream 0")?;
+                expect(frame.stream <= self.last_stream, PROTOCOL_ERROR, "RST_STREAM on idle stream")?;
+                expect(frame.payload.len() == 4, FRAME_SIZE_ERROR, "invalid RST_STREAM frame")?;
+                if let Some(ref mut stream) = self.stream {
+                    stream.reset |= stream.id == frame.stream;
+                }
+
+                Ok(())
+            }
+            SETTINGS => self.handle_settings(frame),
+            PUSH_PROMISE => Err(Error::Protocol(PROTOCOL_ERROR, "client sent PUSH_PROMISE")),
+            PING => {
+                expect(frame.stream == 0, PROTOCOL_ERROR, "PING on a stream")?;
+                expect(frame.payload.len() == 8, FRAME_SIZE_ERROR, "invalid PING frame")?;
+                if !frame.has(ACK) {
+                    self.write(PING, ACK, 0, &frame.payload)?;
+                }
+
+                Ok(())
+            }
+            GOAWAY => {
+                expect(frame.stream == 0, PROTOCOL_ERROR, "GOAWAY on a stream")?;
+                self.goaway = true;
+                Ok(())
+            }
+            WINDOW_UPDATE => self.handle_window_update(frame),
+            // Unknown frame types must be ignored.
+            _ => Ok(())
+        }
+    }
+
+    fn handle_data(&mut self, frame: Frame) -> Result<(), Error> {
+        expect(frame.stream != 0, PROTOCOL_ERROR, "DATA on stream 0")?;
+        expect(frame.stream <= self.last_stream, PROTOCOL_ERROR, "DATA on idle stream")?;
+
+        // The entire frame, padding included, counts against the windows.
+        let len = frame.payload.len() as u32;
+        self.recv_window -= len as i64;
+        expect(self.recv_window >= 0, FLOW_CONTROL_ERROR, "connection window exceeded")?;
+
+        let data = frame.unpadded(0)
+            .ok_or(Error::Protocol(PROTOCOL_ERROR, "invalid DATA padding"))?;
+
+        let accepted = match self.stream {
+            Some(ref mut stream) if stream.id == frame.stream && !stream.reset => {
+                expect(!stream.remote_closed, STREAM_CLOSED, "DATA after END_STREAM")?;
+                stream.recv_window -= len as i64;
+                expect(stream.recv_window >= 0, FLOW_CONTROL_ERROR, "stream window exceeded")?;
+                stream.data.extend(data);
+                stream.remote_closed = frame.has(END_STREAM);
+                data.len() as u32
+            }
+            // Data for a stream that's already done is discarded.
+            _ => 0
+        };
+
+        self.credit(len - accepted)?;
+        Ok(())
+    }
+
+    fn handle_headers(&mut self, frame: Frame) -> Result<(), Error> {
+        expect(frame.stream != 0, PROTOCOL_ERROR, "HEADERS on stream 0")?;
+        let extra = if frame.has(PRIORITY_FLAG) { 5 } else { 0 };
+        let block = frame.unpadded(extra)
+            .ok_or(Error::Protocol(PROTOCOL_ERROR, "invalid HEADERS padding"))?
+            .to_vec();
+
+        let end_stream = frame.has(END_STREAM);
+        self.partial = Some(PartialHeaders { stream: frame.stream, end_stream, block: vec![] });
+        self.append_headers(&block, frame.has(END_HEADERS))
+    }
+
+    fn handle_continuation(&mut self, frame: Frame) -> Result<(), Error> {
+        expect(self.partial.is_some(), PROTOCOL_ERROR, "unexpected CONTINUATION frame")?;
+        self.append_headers(&frame.payload, frame.has(END_HEADERS))
+    }
+
+    fn append_headers(&mut self, fragment: &[u8], end_headers: bool) -> Result<(), Error> {
+        let size = {
+            let partial = self.partial.as_mut().expect("partial headers");
+            partial.block.extend_from_slice(fragment);
+            partial.block.len()
+        };
+
+        expect(size <= self.max_header_block, ENHANCE_YOUR_CALM, "header block too large")?;
+        if !end_headers {
+            return Ok(());
+        }
+
+        let partial = self.partial.take().expect("partial headers");
+        self.headers_complete(partial)
+    }
+
+    fn headers_complete(&mut self, partial: PartialHeaders) -> Result<(), Error> {
+        // The block must be decoded even if it's refused to keep the state of
+        // the decoder in sync with the client's encoder.
+        let fields = self.decoder.decode(&partial.block)
+            .map_err(|_| Error::Protocol(COMPRESSION_ERROR, "invalid header block"))?;
+
+        let id = partial.stream;
+        if let Some(ref mut stream) = self.stream {
+            if stream.id == id {
+                // These are trailers. They're not exposed to applications.
+                expect(!stream.remote_closed, STREAM_CLOSED, "HEADERS after END_STREAM")?;
+                expect(partial.end_stream, PROTOCOL_ERROR, "trailers without END_STREAM")?;
+                stream.remote_closed = true;
+                return Ok(());
+            }
+        }
+
+        expect(id % 2 == 1, PROTOCOL_ERROR, "client opened an even stream")?;
+        expect(id > self.last_stream, STREAM_CLOSED, "HEADERS on closed stream")?;
+        self.last_stream = id;
+        if self.stream.is_some() || self.goaway {
+            self.write(RST_STREAM, 0, id, &u32_bytes(REFUSED_STREAM))?;
+            return Ok(());
+        }
+
+        self.stream = Some(Stream {
+            id,
+            send_window: self.initial_window as i64,
+            recv_window: DEFAULT_WINDOW as i64,
+            unacked: 0,
+            data: VecDeque::new(),
+            remote_closed: partial.end_stream,
+            reset: false,
+        });
+
+        self.pending = Some((id, fields));
+        Ok(())
+    }
+
+    fn handle_settings(&mut self, frame: Frame) -> Result<(), Error> {
+        expect(frame.stream == 0, PROTOCOL_ERROR, "SETTINGS on a stream")?;
+        if frame.has(ACK) {
+            return expect(frame.payload.is_empty(), FRAME_SIZE_ERROR, "invalid SETTINGS ACK");
+        }
+
+        expect(frame.payload.len() % 6 == 0, FRAME_SIZE_ERROR, "invalid SETTINGS frame")?;
+        for setting in frame.payload.chunks(6) {
+            let id = (setting[0] as u16) << 8 | setting[1] as u16;
+            let value = (setting[2] as u32) << 24 | (setting[3] as u32) << 16
+                | (setting[4] as u32) << 8 | setting[5] as u32;
+
+            match id {
+                SETTINGS_ENABLE_PUSH => {
+                    expect(value <= 1, PROTOCOL_ERROR, "invalid SETTINGS_ENABLE_PUSH")?;
+                }
+                SETTINGS_INITIAL_WINDOW_SIZE => {
+                    expect(value <= MAX_WINDOW, FLOW_CONTROL_ERROR, "window too large")?;
+                    let delta = value as i64 - self.initial_window as i64;
+                    self.initial_window = value;
+                    if let Some(ref mut stream) = self.stream {
+                        stream.send_window += delta;
+                        let max = MAX_WINDOW as i64;
+                        expect(stream.send_window <= max, FLOW_CONTROL_ERROR, "window too large")?;
+                    }
+                }
+                SETTINGS_MAX_FRAME_SIZE => {
+                    let valid = (DEFAULT_MAX_FRAME..=MAX_FRAME).contains(&value);
+                    expect(valid, PROTOCOL_ERROR, "invalid SETTINGS_MAX_FRAME_SIZE")?;
+                    self.max_frame = value;
+                }
+                // We serve one stream at a time and never compress with the
+                // dynamic table, so the remaining settings don't matter.
+                _ => {}
+            }
+        }
+
+        self.write(SETTINGS, ACK, 0, &[])?;
+        Ok(())
+    }
+
+    fn handle_window_update(&mut self, frame: Frame) -> Result<(), Error> {
+        expect(frame.payload.len() == 4, FRAME_SIZE_ERROR, "invalid WINDOW_UPDATE frame")?;
+        let increment = read_u32(&frame.payload) as i64;
+        expect(increment != 0, PROTOCOL_ERROR, "zero WINDOW_UPDATE increment")?;
+
+        let window = match self.stream {
+            _ if frame.stream == 0 => &mut self.send_window,
+            Some(ref mut stream) if stream.id == frame.stream => &mut stream.send_window,
+            _ => return Ok(())
+        };
+
+        *window += increment;
+        expect(*window <= MAX_WINDOW as i64, FLOW_CONTROL_ERROR, "window too large")
+    }
+}
+
+#[inline]
+fn expect(condition: bool, code: u32, reason: &'static str) -> Result<(), Error> {
+    if condition {
+        Ok(())
+    } else {
+        Err(Error::Protocol(code, reason))
+    }
+}
+
+#[inline]
+fn reset_error() -> io::Error {
+    io::Error::new(io::ErrorKind::ConnectionReset, "stream was reset by the client")
+}
@@ -0,0 +1,218 @@
+use std::io::{self, Read, Write};
+
+// Frame types. (RFC 7540, Section 6)
+crate const DATA: u8 = 0x0;
+crate const HEADERS: u8 = 0x1;
+crate const PRIORITY: u8 = 0x2;
+crate const RST_STREAM: u8 = 0x3;
+crate const SETTINGS: u8 = 0x4;
+crate const PUSH_PROMISE: u8 = 0x5;
+crate const PING: u8 = 0x6;
+crate const GOAWAY: u8 = 0x7;
+crate const WINDOW_UPDATE: u8 = 0x8;
+crate const CONTINUATION: u8 = 0x9;
+
+// Frame flags. `ACK` shares its value with `END_STREAM`.
+crate const END_STREAM: u8 = 0x1;
+crate const ACK: u8 = 0x1;
+crate const END_HEADERS: u8 = 0x4;
+crate const PADDED: u8 = 0x8;
+crate const PRIORITY_FLAG: u8 = 0x20;
+
+// Error codes. (RFC 7540, Section 7)
+crate const NO_ERROR: u32 = 0x0;
+crate const PROTOCOL_ERROR: u32 = 0x1;
+crate const INTERNAL_ERROR: u32 = 0x2;
+crate const FLOW_CONTROL_ERROR: u32 = 0x3;
+crate const STREAM_CLOSED: u32 = 0x5;
+crate const FRAME_SIZE_ERROR: u32 = 0x6;
+crate const REFUSED_STREAM: u32 = 0x7;
+crate const COMPRESSION_ERROR: u32 = 0x9;
+crate const ENHANCE_YOUR_CALM: u32 = 0xb;
+
+// Settings. (RFC 7540, Section 6.5.2)
+crate const SETTINGS_ENABLE_PUSH: u16 = 0x2;
+crate const SETTINGS_MAX_CONCURRENT_STREAMS: u16 = 0x3;
+crate const SETTINGS_INITIAL_WINDOW_SIZE: u16 = 0x4;
+crate const SETTINGS_MAX_FRAME_SIZE: u16 = 0x5;
+crate const SETTINGS_MAX_HEADER_LIST_SIZE: u16 = 0x6;
+
+/// The initial size of flow-control windows and the initial and minimum
+/// maximum frame size.
+crate const DEFAULT_WINDOW: u32 = 65_535;
+crate const DEFAULT_MAX_FRAME: u32 = 16_384;
+
+/// The largest allowed flow-control window and maximum frame size.
+crate const MAX_WINDOW: u32 = (1 << 31) - 1;
+crate const MAX_FRAME: u32 = (1 << 24) - 1;
+
+/// An error that ends an HTTP/2 connection.
+#[derive(Debug)]
+crate enum Error {
+    /// Reading from or writing to the network failed.
+    Io(io::Error),
+    /// The client violated the protocol. Carries the error code to send to
+    /// the client and a description of the violation.
+    Protocol(u32, &'static str),
+}
+
+impl From<io::Error> for Error {
+    #[inline(always)]
+    fn from(error: io::Error) -> Error {
+        Error::Io(error)
+    }
+}
+
+/// A single frame, as read from the network.
+crate struct Frame {
+    crate kind: u8,
+    crate flags: u8,
+    crate stream: u32,
+    crate payload: Vec<u8>,
+}
+
+impl Frame {
+    #[inline(always)]
+    crate fn has(&self, flag: u8) -> bool {
+        self.flags & flag == flag
+    }
+
+    /// Reads a frame from `reader`. Fails with a `FRAME_SIZE_ERROR` if the
+    /// frame's payload is larger than `max_size`.
+    crate fn read<R: Read>(reader: &mut R, max_size: u32) -> Result<Frame, Error> {
+        let mut head = [0u8; 9];
+        reader.read_exact(&mut head)?;
+
+        let len = (head[0] as u32) << 16 | (head[1] as u32) << 8 | head[2] as u32;
+        if len > max_size {
+            return Err(Error::Protocol(FRAME_SIZE_ERROR, "frame exceeds maximum size"));
+        }
+
+        let mut payload = vec![0; len as usize];
+        reader.read_exact(&mut payload)?;
+        Ok(Frame { kind: head[3], flags: head[4], stream: read_u32(&head[5..]), payload })
+    }
+
+    /// Returns the payload without padding, or `None` if the padding is
+    /// malformed. `extra` is the length of any fields preceding the data.
+    crate fn unpadded(&self, extra: usize) -> Option<&[u8]> {
+        let (start, padding) = if self.has(PADDED) {
+            (1, *self.payload.first()? as usize)
+        } else {
+            (0, 0)
+        };
+
+        let end = self.payload.len().checked_sub(padding)?;
+        if start + extra > end {
+            return None;
+        }
+
+        Some(&self.payload[(start + extra)..end])
+    }
+}
+
+/// Reads a big-endian `u32` from the first four bytes of `bytes`, ignoring
+/// the reserved high bit.
+#[inline]
+crate fn read_u32(bytes: &[u8]) -> u32 {
+    let value = (bytes[0] as u32) << 24 | (bytes[1] as u32) << 16
+        | (bytes[2] as u32) << 8 | bytes[3] as u32;
+
+    value & MAX_WINDOW
+}
+
+/// Writes a frame with the given header and payload to `writer`.
+crate fn write<W: Write>(
+    writer: &mut W,
+    kind: u8,
+    flags: u8,
+    stream: u32,
+    payload: &[u8]
+) -> io::Result<()> {
+    let len = payload.len();
+    let mut frame = Vec::with_capacity(9 + len);
+    frame.extend_from_slice(&[(len >> 16) as u8, (len >> 8) as u8, len as u8, kind, flags]);
+    frame.extend_from_slice(&u32_bytes(stream));
+    frame.extend_from_slice(payload);
+    writer.write_all(&frame)
+}
+
+#[inline]
+crate fn u32_bytes(value: u32) -> [u8; 4] {
+    [(value >> 24) as u8, (value >> 16) as u8, (value >> 8) as u8, value as u8]
+}
+
+/// Encodes `headers` into a header block. Fields are encoded as literals that
+/// are never added to the dynamic table, so the peer's table size is moot.
+crate fn encode_headers<'a, I>(headers: I) -> Vec<u8>
+    where I: IntoIterator<Item = (&'a str, &'a [u8])>
+{
+    // Appends an integer with an `n`-bit prefix. (RFC 7541, Section 5.1)
+    fn integer(buf: &mut Vec<u8>, mask: u8, n: u8, mut value: usize) {
+        let max = (1usize << n) - 1;
+        if value < max {
+            buf.push(mask | value as u8);
+            return;
+        }
+
+        buf.push(mask | max as u8);
+        value -= max;
+        while value >= 128 {
+            buf.push((value % 128) as u8 | 0x80);
+            value /= 128;
+        }
+
+        buf.push(value as u8);
+    }
+
+    let mut block = vec![];
+    for (name, value) in headers {
+        // A literal header field without indexing with a new name.
+        block.push(0);
+        integer(&mut block, 0, 7, name.len());
+        block.extend(name.bytes().map(|b| b.to_ascii_lowercase()));
+        integer(&mut block, 0, 7, value.len());
+        block.extend_from_slice(value);
+    }
+
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_round_trip() {
+        let mut bytes = vec![];
+        write(&mut bytes, HEADERS, END_HEADERS | PADDED, 3, &[2, 7, 7, 0, 0]).unwrap();
+        assert_eq!(&bytes[..9], &[0, 0, 5, HEADERS, END_HEADERS | PADDED, 0, 0, 0, 3]);
+
+        let frame = Frame::read(&mut &bytes[..], DEFAULT_MAX_FRAME).unwrap();
+        assert_eq!((frame.kind, frame.stream), (HEADERS, 3));
+        assert!(frame.has(PADDED) && !frame.has(END_STREAM));
+        assert_eq!(frame.unpadded(0), Some(&[7, 7][..]));
+        assert_eq!(frame.unpadded(2), Some(&[][..]));
+        assert_eq!(frame.unpadded(3), None);
+
+        assert!(Frame::read(&mut &bytes[..], 4).is_err());
+    }
+
+    #[test]
+    fn encodes_literal_headers() {
+        let block = encode_headers(vec![(":status", &b"200"[..]), ("X-Id", &b"a"[..])]);
+        let mut expected = vec![0, 7];
+        expected.extend_from_slice(b":status");
+        expected.push(3);
+        expected.extend_from_slice(b"200");
+        expected.extend_from_slice(&[0, 4]);
+        expected.extend_from_slice(b"x-id");
+        expected.extend_from_slice(&[1, b'a']);
+        assert_eq!(block, expected);
+
+        // Lengths of 127 and more spill into continuation bytes.
+        let value = vec![b'v'; 300];
+        let block = encode_headers(vec![("a", &value[..])]);
+        assert_eq!(&block[..6], &[0, 1, b'a', 127, 173, 1]);
+    }
+}
@@ -0,0 +1,326 @@
+//! HTTP/2 support: framing, the connection state machine, and the glue that
+//! serves a connection's streams through `Rocket`.
+//!
+//! Hyper 0.10 only speaks HTTP/1, so HTTP/2 connections are handled here in
+//! their entirety. Each stream is converted into the same Hyper request types
+//! that HTTP/1 requests are parsed into, dispatched as usual, and responded to
+//! via a `Responder`, which writes the response out as HTTP/2 frames.
+//!
+//! Streams are served one at a time, on the connection's worker thread. This
+//! support is experimental and is only compiled in with the `http2` feature.
+
+mod frame;
+mod connection;
+
+use std::io::{self, Read};
+use std::iter;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use {Rocket, Shutdown};
+use data::NetStream;
+use ext::ReadExt;
+use response::{self, Response, Trailers};
+
+use http::hyper::{self, header::Headers};
+use http::hyper::net::NetworkStream;
+
+use self::connection::{Connection, Fields};
+use self::frame::{Error, NO_ERROR, PROTOCOL_ERROR, INTERNAL_ERROR, DEFAULT_MAX_FRAME};
+
+crate use self::connection::PREFACE;
+
+/// Headers that are specific to an HTTP/1 connection and are forbidden in
+/// HTTP/2 messages. (RFC 7540, Section 8.1.2.2)
+crate const CONNECTION_HEADERS: &[&str] = &[
+    "Connection", "Keep-Alive", "Proxy-Connection", "Transfer-Encoding", "Upgrade"
+];
+
+/// The parts of an HTTP/2 request head, as Hyper would have parsed them.
+crate struct RequestHead {
+    crate method: hyper::Method,
+    crate uri: hyper::RequestUri,
+    crate headers: Headers,
+}
+
+impl RequestHead {
+    /// Validates and converts the decoded header `fields` of a request.
+    /// (RFC 7540, Section 8.1.2)
+    fn parse(fields: Fields) -> Result<RequestHead, &'static str> {
+        let (mut method, mut scheme, mut path, mut authority) = (None, None, None, None);
+        let mut headers = Headers::new();
+        let mut seen_regular = false;
+        for (name, value) in fields {
+            if name.starts_with(b":") {
+                if seen_regular {
+                    return Err("pseudo-header field after regular field");
+                }
+
+                let field = match &name[..] {
+                    b":method" => &mut method,
+                    b":scheme" => &mut scheme,
+                    b":path" => &mut path,
+                    b":authority" => &mut authority,
+                    _ => return Err("unknown pseudo-header field"),
+                };
+
+                if field.is_some() {
+                    return Err("duplicate pseudo-header field");
+                }
+
+                *field = Some(String::from_utf8(value).map_err(|_| "non UTF-8 pseudo-header")?);
+                continue;
+            }
+
+            seen_regular = true;
+            let name = String::from_utf8(name).map_err(|_| "non UTF-8 header name")?;
+            if name.is_empty() || name.bytes().any(|b| b.is_ascii_uppercase()) {
+                return Err("header name is not lowercase");
+            }
+
+            if CONNECTION_HEADERS.iter().any(|h| h.eq_ignore_ascii_case(&name)) {
+                return Err("connection-specific header field");
+            }
+
+            if name == "te" && value != b"trailers" {
+                return Err("TE header field other than `trailers`");
+            }
+
+            headers.append_raw(name, value);
+        }
+
+        let method = method.ok_or("missing :method")?;
+        let uri = if method == "CONNECT" {
+            if scheme.is_some() || path.is_some() {
+                return Err("CONNECT with :scheme or :path");
+            }
+
+            hyper::RequestUri::Authority(authority.clone().ok_or("missing :authority")?)
+        } else {
+            scheme.ok_or("missing :scheme")?;
+            let path = path.ok_or("missing :path")?;
+            path.parse().map_err(|_| "invalid :path")?
+        };
+
+        // `:authority` takes the place of the `Host` header.
+        if let Some(authority) = authority {
+            if headers.get_raw("Host").is_none() {
+                headers.set_raw("Host", vec![authority.into_bytes()]);
+            }
+        }
+
+        let method = method.parse().map_err(|_| "invalid :method")?;
+        Ok(RequestHead { method, uri, headers })
+    }
+}
+
+#[inline]
+fn lock(connection: &Mutex<Connection>) -> MutexGuard<Connection> {
+    connection.lock().expect("http2 connection lock")
+}
+
+/// The body of an HTTP/2 request.
+#[derive(Clone)]
+pub struct Body {
+    connection: Arc<Mutex<Connection>>,
+    stream: u32,
+}
+
+impl Read for Body {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        lock(&self.connection).read_body(self.stream, buf)
+    }
+}
+
+/// Writes the response to an HTTP/2 request.
+crate struct Responder {
+    connection: Arc<Mutex<Connection>>,
+    stream: u32,
+}
+
+impl Responder {
+    /// Writes `response` out. If that fails midway, the stream is reset.
+    crate fn respond(self, response: Response) -> io::Result<()> {
+        let result = self.write_response(response);
+        if result.is_err() {
+            let mut connection = lock(&self.connection);
+            if let Err(e) = connection.finish(self.stream, INTERNAL_ERROR) {
+                debug!("Failed to reset HTTP/2 stream: {:?}", e);
+            }
+        }
+
+        result
+    }
+
+    fn write_response(&self, mut response: Response) -> io::Result<()> {
+        let length = match response.body() {
+            None => Some(0),
+            Some(response::Body::Sized(_, size)) => Some(size),
+            Some(response::Body::Chunked(..)) => None,
+        };
+
+        let block = {
+            let status = response.status().code.to_string();
+            let length = length.map(|n| n.to_string());
+            let headers: Vec<_> = response.headers().iter()
+                .filter(|h| !h.name().eq_ignore_ascii_case("Content-Length"))
+                .collect();
+
+            let fields = iter::once((":status", status.as_bytes()))
+                .chain(headers.iter().map(|h| (h.name(), h.value().as_bytes())))
+                .chain(length.as_ref().map(|n| ("content-length", n.as_bytes())));
+
+            frame::encode_headers(fields)
+        };
+
+        let flush_reads = response.flushes_reads();
+        let trailers = response.take_trailers();
+        match response.body() {
+            None => lock(&self.connection).send_headers(self.stream, &block, true),
+            Some(response::Body::Sized(body, size)) => {
+                let chunk_size = DEFAULT_MAX_FRAME as u64;
+                self.write_body(&block, body.take(size), chunk_size, false, None)
+            }
+            Some(response::Body::Chunked(body, chunk_size)) => {
+                self.write_body(&block, body, chunk_size, flush_reads, trailers)
+            }
+        }
+    }
+
+    // Writes the body out in DATA frames. Data is sent as soon as the body
+    // has `chunk_size` bytes of it or, if `flush_reads`, after every read.
+    // The stream ends with a HEADERS frame carrying `trailers`, if any.
+    fn write_body<R: Read>(
+        &self,
+        block: &[u8],
+        mut body: R,
+        chunk_size: u64,
+        flush_reads: bool,
+        trailers: Option<Trailers>
+    ) -> io::Result<()> {
+        // This _might_ happen on a 32-bit machine!
+        if chunk_size > (usize::max_value() as u64) {
+            let msg = "chunk size exceeds limits of usize type";
+            return Err(io::Error::new(io::ErrorKind::Other, msg));
+        }
+
+        lock(&self.connection).send_headers(self.stream, block, false)?;
+
+        // The body may well be reading the request body, which requires the
+        // connection. Only lock it to write each chunk out.
+        let mut buffer = vec![0; chunk_size as usize];
+        loop {
+            let read = if flush_reads {
+                body.read(&mut buffer)?
+            } else {
+                body.read_max(&mut buffer)?
+            };
+
+            match read {
+                0 => break,
+                n => lock(&self.connection).send_data(self.stream, &buffer[..n], false)?,
+            }
+        }
+
+        match trailers {
+            Some(trailers) => {
+                let trailers = trailers.into_headers();
+                let fields = trailers.iter().collect::<Vec<_>>();
+                let block = frame::encode_headers(fields.iter()
+                    .map(|h| (h.name(), h.value().as_bytes())));
+
+                lock(&self.connection).send_headers(self.stream, &block, true)
+            }
+            None => lock(&self.connection).send_data(self.stream, &[], true)
+        }
+    }
+}
+
+/// Serves the HTTP/2 connection on `stream` until the client goes away, the
+/// connection times out, or a shutdown is requested. `buffered` contains any
+/// bytes that were already read from `stream`. `busy` is called with `false`
+/// while the connection is idle and with `true` while a stream is served.
+crate fn serve(
+    rocket: &Rocket,
+    shutdown: &Shutdown,
+    stream: NetStream,
+    buffered: Vec<u8>,
+    remote: Option<SocketAddr>,
+    busy: &mut FnMut(bool)
+) {
+    let keep_alive = rocket.config.keep_alive.map(|s| Duration::from_secs(s as u64));
+    let read_timeout = rocket.config.read_timeout.map(|s| Duration::from_secs(s as u64));
+    let max_header_block = rocket.config.limits.header_bytes as usize;
+
+    let connection = Connection::new(stream.clone(), buffered, max_header_block);
+    let connection = Arc::new(Mutex::new(connection));
+    let result = lock(&connection).handshake();
+    let code = match result {
+        Ok(()) => loop {
+            busy(false);
+            if stream.set_read_timeout(keep_alive).is_err() {
+                break NO_ERROR;
+            }
+
+            let next = lock(&connection).next_request();
+            let (id, fields) = match next {
+                Ok(Some(request)) => request,
+                Ok(None) => break NO_ERROR,
+                Err(e) => break error_code(e),
+            };
+
+            busy(true);
+            if stream.set_read_timeout(read_timeout).is_err() {
+                break NO_ERROR;
+            }
+
+            let code = match RequestHead::parse(fields) {
+                Ok(head) => {
+                    let body = Body { connection: connection.clone(), stream: id };
+                    let responder = Responder { connection: connection.clone(), stream: id };
+                    rocket.handle_h2(head, remote, body, responder);
+                    NO_ERROR
+                }
+                Err(reason) => {
+                    debug!("Malformed HTTP/2 request: {}", reason);
+                    PROTOCOL_ERROR
+                }
+            };
+
+            let mut connection = lock(&connection);
+            if let Some(code) = connection.failure() {
+                break code;
+            }
+
+            if connection.finish(id, code).is_err() {
+                break NO_ERROR;
+            }
+
+            if rocket.config.keep_alive.is_none()
+                || shutdown.is_requested()
+                || connection.is_going_away()
+            {
+                break NO_ERROR;
+            }
+        },
+        Err(e) => error_code(e),
+    };
+
+    lock(&connection).close(code);
+}
+
+// Returns the error code to close the connection with after `error`.
+fn error_code(error: Error) -> u32 {
+    match error {
+        Error::Protocol(code, reason) => {
+            debug!("HTTP/2 protocol error: {}", reason);
+            code
+        }
+        Error::Io(e) => {
+            debug!("HTTP/2 connection failed: {:?}", e);
+            NO_ERROR
+        }
+    }
+}
@@ -0,0 +1,414 @@
+use std::io;
+use std::time::Duration;
+
+use data::NetStream;
+
+#[cfg(unix)] use std::net::Shutdown;
+#[cfg(unix)] use std::sync::{Arc, mpsc};
+#[cfg(unix)] use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(unix)] use std::thread;
+#[cfg(unix)] use std::time::Instant;
+#[cfg(unix)] use std::os::unix::io::RawFd;
+
+#[cfg(unix)] use mio::{Poll, Events, Token, Ready, PollOpt, Registration, SetReadiness};
+#[cfg(unix)] use mio::unix::EventedFd;
+#[cfg(unix)] use http::hyper::net::NetworkStream;
+
+// A connection idling in keep-alive would otherwise block a worker thread in a
+// read until the client sends its next request. Instead, workers "park" such
+// connections with a single thread that waits for activity on all of them at
+// once via epoll or kqueue. Once a parked connection becomes readable, it's
+// handed back to the workers. Idle connections thus cost a file descriptor, not
+// a thread.
+//
+// New connections are parked the same way until their first request arrives.
+//
+// Parking and resuming a connection costs the same however many connections
+// are parked: each is registered with the poller once, when it's parked, and
+// its deadline goes into a timing wheel that is advanced tick by tick.
+//
+// A TLS stream is only parked while its session has no buffered data, which
+// polling the underlying socket wouldn't reveal. Parking is only supported on
+// Unix platforms; elsewhere, connections idle on their worker as before.
+
+/// The token of the registration used to wake the idle thread.
+#[cfg(unix)]
+const WAKER: Token = Token(usize::max_value() - 1);
+
+/// The granularity, in milliseconds, of parked connections' deadlines.
+#[cfg(unix)]
+const TICK_MILLIS: u64 = 100;
+
+/// The number of ticks in the timing wheel. Deadlines further out than one
+/// revolution wait in their slot for as many revolutions as needed.
+#[cfg(unix)]
+const WHEEL_SLOTS: usize = 512;
+
+/// A connection idling in keep-alive until `deadline`.
+#[cfg(unix)]
+struct Parked {
+    stream: NetStream,
+    fd: RawFd,
+    deadline: Option<Instant>,
+}
+
+/// A handle to the thread watching parked connections.
+#[cfg(unix)]
+#[derive(Clone)]
+crate struct Idle {
+    sender: mpsc::Sender<Parked>,
+    waker: SetReadiness,
+    stopped: Arc<AtomicBool>,
+}
+
+#[cfg(unix)]
+impl Idle {
+    /// Spawns the thread watching parked connections. Connections that become
+    /// readable are passed to `resume`.
+    crate fn spawn<F>(resume: F) -> io::Result<Idle>
+        where F: Fn(NetStream) + Send + 'static
+    {
+        let poll = Poll::new()?;
+        let (registration, waker) = Registration::new2();
+        poll.register(&registration, WAKER, Ready::readable(), PollOpt::edge())?;
+
+        let (sender, receiver) = mpsc::channel();
+        let stopped = Arc::new(AtomicBool::new(false));
+        let (thread_stopped, thread_waker) = (stopped.clone(), waker.clone());
+        thread::Builder::new().name("rocket-idle".into()).spawn(move || {
+            let _registration = registration;
+            Watcher::new(poll, thread_waker).watch(&receiver, &thread_stopped, resume)
+        })?;
+
+        Ok(Idle { sender, waker, stopped })
+    }
+
+    /// Returns `true` if `stream` can be parked.
+    #[inline]
+    crate fn can_park(&self, stream: &NetStream) -> bool {
+        stream.pollable_fd().is_some()
+    }
+
+    /// Parks `stream` for at most `timeout`, after which it is closed. Returns
+    /// the stream back if it can't be parked.
+    crate fn park(&self, stream: NetStream, timeout: Option<Duration>) -> Result<(), NetStream> {
+        let fd = match stream.pollable_fd() {
+            Some(fd) => fd,
+            None => return Err(stream)
+        };
+
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        self.sender.send(Parked { stream, fd, deadline }).map_err(|e| e.0.stream)?;
+        self.wake();
+        Ok(())
+    }
+
+    /// Closes all parked connections and stops the thread watching them.
+    crate fn stop(&self) {
+        self.stopped.store(true, Ordering::Release);
+        self.wake();
+    }
+
+    fn wake(&self) {
+        if let Err(e) = self.waker.set_readiness(Ready::readable()) {
+            error_!("Failed to wake the idle connection thread: {}", e);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn close(mut parked: Parked) {
+    if let Err(e) = parked.stream.close(Shutdown::Both) {
+        debug!("Failed to close network stream: {:?}", e);
+    }
+}
+
+/// A slot holding a parked connection. The slot's index is the connection's
+/// token. `generation` tells the connections that used the slot apart.
+#[cfg(unix)]
+#[derive(Default)]
+struct Slot {
+    parked: Option<Parked>,
+    generation: u64,
+}
+
+/// A hashed timing wheel of the deadlines of parked connections. A deadline
+/// is filed under the first tick at or after it; advancing the wheel visits
+/// only the slots of the ticks that have passed.
+#[cfg(unix)]
+struct Wheel {
+    slots: Vec<Vec<(usize, u64, Instant)>>,
+    start: Instant,
+    /// The next tick to expire.
+    tick: u64,
+    /// The number of deadlines in the wheel, including those of connections
+    /// that were resumed since.
+    len: usize,
+}
+
+#[cfg(unix)]
+impl Wheel {
+    fn new(start: Instant) -> Wheel {
+        Wheel { slots: vec![vec![]; WHEEL_SLOTS], start, tick: 0, len: 0 }
+    }
+
+    // Returns the time since the start of the wheel and `time` in ticks and
+    // the nanoseconds left over.
+    fn ticks(&self, time: Instant) -> (u64, u64) {
+        let elapsed = if time > self.start { time - self.start } else { Duration::from_secs(0) };
+        let nanos = elapsed.as_secs() * 1_000_000_000 + elapsed.subsec_nanos() as u64;
+        (nanos / (TICK_MILLIS * 1_000_000), nanos % (TICK_MILLIS * 1_000_000))
+    }
+
+    fn slot(tick: u64) -> usize {
+        (tick % WHEEL_SLOTS as u64) as usize
+    }
+
+    fn insert(&mut self, token: usize, generation: u64, deadline: Instant) {
+        let tick = match self.ticks(deadline) {
+            (ticks, 0) => ticks,
+            (ticks, _) => ticks + 1
+        };
+
+        let tick = ::std::cmp::max(tick, self.tick);
+        self.slots[Wheel::slot(tick)].push((token, generation, deadline));
+        self.len += 1;
+    }
+
+    // Removes every deadline at or before `now`, passing each to `expire`.
+    // Only ticks that have fully passed are visited, at most one revolution's
+    // worth, so every deadline left in a visited slot is in a later revolution.
+    fn advance<F: FnMut(usize, u64)>(&mut self, now: Instant, mut expire: F) {
+        let current = self.ticks(now).0;
+        let mut visited = 0;
+        while self.tick <= current && visited < WHEEL_SLOTS && self.len > 0 {
+            let slot = &mut self.slots[Wheel::slot(self.tick)];
+            let mut i = 0;
+            while i < slot.len() {
+                if slot[i].2 <= now {
+                    let (token, generation, _) = slot.swap_remove(i);
+                    expire(token, generation);
+                    self.len -= 1;
+                } else {
+                    i += 1;
+                }
+            }
+
+            self.tick += 1;
+            visited += 1;
+        }
+
+        // Every slot was visited, or there is nothing left to expire.
+        if self.tick <= current {
+            self.tick = current + 1;
+        }
+    }
+
+    // Returns how long to wait until the next tick has passed, or `None` if
+    // no deadlines are pending.
+    fn timeout(&self, now: Instant) -> Option<Duration> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let next = self.start + Duration::from_millis(self.tick * TICK_MILLIS);
+        Some(if next > now { next - now } else { Duration::from_secs(0) })
+    }
+}
+
+/// The state of the thread watching parked connections.
+#[cfg(unix)]
+struct Watcher {
+    poll: Poll,
+    waker: SetReadiness,
+    slots: Vec<Slot>,
+    free: Vec<usize>,
+    wheel: Wheel,
+}
+
+#[cfg(unix)]
+impl Watcher {
+    fn new(poll: Poll, waker: SetReadiness) -> Watcher {
+        let wheel = Wheel::new(Instant::now());
+        Watcher { poll, waker, slots: vec![], free: vec![], wheel }
+    }
+
+    // Registers `parked` with the poller, closing it if that fails.
+    fn add(&mut self, parked: Parked) {
+        let token = self.free.pop().unwrap_or_else(|| {
+            self.slots.push(Slot::default());
+            self.slots.len() - 1
+        });
+
+        let registered = {
+            let opts = PollOpt::level() | PollOpt::oneshot();
+            self.poll.register(&EventedFd(&parked.fd), Token(token), Ready::readable(), opts)
+        };
+        if let Err(e) = registered {
+            error_!("Failed to watch idle connection: {}", e);
+            self.free.push(token);
+            return close(parked);
+        }
+
+        let slot = &mut self.slots[token];
+        slot.generation += 1;
+        if let Some(deadline) = parked.deadline {
+            self.wheel.insert(token, slot.generation, deadline);
+        }
+
+        slot.parked = Some(parked);
+    }
+
+    // Stops watching the connection in slot `token` and returns it.
+    fn remove(&mut self, token: usize) -> Option<Parked> {
+        let parked = self.slots.get_mut(token).and_then(|slot| slot.parked.take())?;
+        let deregistered = self.poll.deregister(&EventedFd(&parked.fd));
+        if let Err(e) = deregistered {
+            debug!("Failed to stop watching idle connection: {:?}", e);
+        }
+
+        self.free.push(token);
+        Some(parked)
+    }
+
+    fn watch<F: Fn(NetStream)>(
+        mut self,
+        receiver: &mpsc::Receiver<Parked>,
+        stopped: &AtomicBool,
+        resume: F
+    ) {
+        let mut events = Events::with_capacity(1024);
+        loop {
+            // Reset the waker before picking up newly parked connections so
+            // that a connection parked from now on wakes the next poll.
+            let _ = self.waker.set_readiness(Ready::empty());
+            if stopped.load(Ordering::Acquire) {
+                break;
+            }
+
+            for parked in receiver.try_iter() {
+                self.add(parked);
+            }
+
+            // Close connections that have idled for too long.
+            let mut expired = vec![];
+            {
+                let slots = &self.slots;
+                self.wheel.advance(Instant::now(), |token, generation| {
+                    if slots[token].generation == generation && slots[token].parked.is_some() {
+                        expired.push(token);
+                    }
+                });
+            }
+
+            for token in expired {
+                if let Some(parked) = self.remove(token) {
+                    close(parked);
+                }
+            }
+
+            let timeout = self.wheel.timeout(Instant::now());
+            if let Err(e) = self.poll.poll(&mut events, timeout) {
+                if e.kind() != io::ErrorKind::Interrupted {
+                    error_!("Failed to poll idle connections: {}", e);
+                    break;
+                }
+
+                continue;
+            }
+
+            // Hand connections with activity, including hang-ups, back to the
+            // workers.
+            for event in events.iter() {
+                if event.token() == WAKER {
+                    continue;
+                }
+
+                if let Some(parked) = self.remove(event.token().0) {
+                    resume(parked.stream);
+                }
+            }
+        }
+
+        for parked in receiver.try_iter() {
+            close(parked);
+        }
+
+        for slot in &mut self.slots {
+            if let Some(parked) = slot.parked.take() {
+                close(parked);
+            }
+        }
+    }
+}
+
+/// Parking connections is only supported on Unix platforms.
+#[cfg(not(unix))]
+#[derive(Clone)]
+crate struct Idle;
+
+#[cfg(not(unix))]
+impl Idle {
+    crate fn spawn<F>(_: F) -> io::Result<Idle>
+        where F: Fn(NetStream) + Send + 'static
+    {
+        Ok(Idle)
+    }
+
+    #[inline]
+    crate fn can_park(&self, _: &NetStream) -> bool {
+        false
+    }
+
+    crate fn park(&self, stream: NetStream, _: Option<Duration>) -> Result<(), NetStream> {
+        Err(stream)
+    }
+
+    crate fn stop(&self) {  }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{Wheel, WHEEL_SLOTS, TICK_MILLIS};
+
+    fn expired(wheel: &mut Wheel, now: Instant) -> Vec<usize> {
+        let mut tokens = vec![];
+        wheel.advance(now, |token, _| tokens.push(token));
+        tokens.sort();
+        tokens
+    }
+
+    #[test]
+    fn wheel_expires_deadlines_once_passed() {
+        let start = Instant::now();
+        let at = |millis: u64| start + Duration::from_millis(millis);
+        let revolution = WHEEL_SLOTS as u64 * TICK_MILLIS;
+
+        let mut wheel = Wheel::new(start);
+        wheel.insert(0, 1, at(150));
+        wheel.insert(1, 1, at(200));
+        wheel.insert(2, 1, at(250 + revolution));
+        wheel.insert(3, 1, at(5));
+        assert_eq!(wheel.timeout(start), Some(Duration::from_millis(0)));
+
+        // Deadlines expire once the tick they're rounded up to has passed.
+        assert_eq!(expired(&mut wheel, at(4)), vec![]);
+        assert_eq!(expired(&mut wheel, at(150)), vec![3]);
+        assert_eq!(wheel.timeout(at(150)), Some(Duration::from_millis(50)));
+        assert_eq!(expired(&mut wheel, at(199)), vec![]);
+        assert_eq!(expired(&mut wheel, at(200)), vec![0, 1]);
+
+        // A deadline a revolution out stays put when its slot comes around.
+        assert_eq!(expired(&mut wheel, at(300)), vec![]);
+        assert_eq!(expired(&mut wheel, at(revolution + 249)), vec![]);
+        assert_eq!(expired(&mut wheel, at(revolution + 300)), vec![2]);
+        assert_eq!(wheel.timeout(at(revolution + 300)), None);
+
+        // Deadlines that have already passed expire once the next tick has.
+        wheel.insert(4, 1, at(10));
+        assert_eq!(expired(&mut wheel, at(revolution + 300)), vec![]);
+        assert_eq!(expired(&mut wheel, at(revolution + 400)), vec![4]);
+    }
+}
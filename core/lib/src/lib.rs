@@ -110,6 +110,10 @@ extern crate time;
 extern crate memchr;
 extern crate base64;
 extern crate atty;
+#[cfg(feature = "http2")] extern crate hpack;
+extern crate sha1;
+#[cfg(unix)] extern crate libc;
+#[cfg(unix)] extern crate mio;
 
 #[cfg(test)] #[macro_use] extern crate lazy_static;
 
@@ -123,6 +127,7 @@ pub mod data;
 pub mod handler;
 pub mod fairing;
 pub mod error;
+pub mod websocket;
 
 // Reexport of HTTP everything.
 pub mod http {
@@ -140,6 +145,14 @@ mod rocket;
 mod codegen;
 mod catcher;
 mod ext;
+mod server;
+mod shutdown;
+mod saturation;
+mod idle;
+#[cfg(feature = "http2")] mod http2;
+mod listener;
+mod upgrade;
+mod sendfile;
 
 #[doc(inline)] pub use response::Response;
 #[doc(inline)] pub use handler::{Handler, ErrorHandler};
@@ -151,6 +164,9 @@ pub use router::Route;
 pub use request::{Request, State};
 pub use catcher::Catcher;
 pub use rocket::Rocket;
+pub use shutdown::Shutdown;
+pub use saturation::Saturation;
+pub use listener::Listener;
 
 /// Alias to [`Rocket::ignite()`] Creates a new instance of `Rocket`.
 pub fn ignite() -> Rocket {
@@ -0,0 +1,318 @@
+use std::{io, fmt};
+use std::net::{TcpListener, TcpStream, SocketAddr, IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::Duration;
+
+#[cfg(unix)] use std::{env, fs, process};
+#[cfg(unix)] use std::path::{Path, PathBuf};
+#[cfg(unix)] use std::os::unix::fs::FileTypeExt;
+#[cfg(unix)] use std::os::unix::io::{FromRawFd, IntoRawFd, RawFd};
+#[cfg(unix)] use std::os::unix::net::{UnixListener, UnixStream};
+
+#[cfg(feature = "tls")] use http::tls::TlsServer;
+
+use config::TlsConfig;
+use data::NetStream;
+use http::hyper::net::HttpStream;
+
+/// A bound socket that a Rocket application can be launched on.
+///
+/// A `Listener` is typically created implicitly by passing an already-bound
+/// [`TcpListener`] or, on Unix platforms, [`UnixListener`] to
+/// [`Rocket::launch_on()`](::Rocket::launch_on()). This allows applications to
+/// bind sockets themselves, for instance, to bind to port `0` and learn the
+/// port chosen by the operating system before launching.
+///
+/// # Example
+///
+/// ```rust
+/// # if false {
+/// use std::net::TcpListener;
+///
+/// let listener = TcpListener::bind("127.0.0.1:0").expect("bound");
+/// println!("Serving on port {}.", listener.local_addr().unwrap().port());
+///
+/// let _ = rocket::ignite().launch_on(listener);
+/// # }
+/// ```
+pub enum Listener {
+    /// A TCP socket.
+    Tcp(TcpListener),
+    /// A Unix domain socket.
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+impl From<TcpListener> for Listener {
+    #[inline(always)]
+    fn from(listener: TcpListener) -> Listener {
+        Listener::Tcp(listener)
+    }
+}
+
+#[cfg(unix)]
+impl From<UnixListener> for Listener {
+    #[inline(always)]
+    fn from(listener: UnixListener) -> Listener {
+        Listener::Unix(listener)
+    }
+}
+
+impl fmt::Debug for Listener {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Listener::Tcp(ref listener) => listener.fmt(f),
+            #[cfg(unix)] Listener::Unix(ref listener) => listener.fmt(f),
+        }
+    }
+}
+
+/// Where the `Listener` being served on came from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+crate enum Source {
+    /// Bound by Rocket to the configured address.
+    Bound,
+    /// Supplied by the caller via `Rocket::launch_on()`.
+    Supplied,
+    /// Inherited from the parent process via `LISTEN_FDS`.
+    Inherited,
+}
+
+/// The first file descriptor passed via systemd's socket activation protocol.
+#[cfg(unix)]
+const LISTEN_FDS_START: RawFd = 3;
+
+impl Listener {
+    /// Returns the listening socket passed to this process by its parent, as
+    /// done by systemd's socket activation, if there is one. The `LISTEN_PID`
+    /// environment variable must name this process, and `LISTEN_FDS` must
+    /// indicate that at least one socket was passed.
+    ///
+    /// The environment variables are unset so that they aren't inherited by
+    /// child processes. Only one socket is used; others are ignored.
+    #[cfg(unix)]
+    crate fn inherited() -> io::Result<Option<Listener>> {
+        let pid = env::var("LISTEN_PID").ok().and_then(|pid| pid.parse::<u32>().ok());
+        if pid != Some(process::id()) {
+            return Ok(None);
+        }
+
+        let count = env::var("LISTEN_FDS").ok()
+            .and_then(|n| n.parse::<RawFd>().ok())
+            .unwrap_or(0);
+
+        env::remove_var("LISTEN_PID");
+        env::remove_var("LISTEN_FDS");
+        env::remove_var("LISTEN_FDNAMES");
+
+        match count {
+            n if n < 1 => return Ok(None),
+            1 => {},
+            n => warn_!("{} sockets were passed via LISTEN_FDS. Using the first.", n),
+        }
+
+        // The protocol transfers ownership of the descriptors to this process.
+        // The socket is either a TCP or a Unix domain socket: retrieving the
+        // local address as a TCP address fails for the latter.
+        let tcp = unsafe { TcpListener::from_raw_fd(LISTEN_FDS_START) };
+        if tcp.local_addr().is_ok() {
+            return Ok(Some(Listener::Tcp(tcp)));
+        }
+
+        let unix = unsafe { UnixListener::from_raw_fd(tcp.into_raw_fd()) };
+        unix.local_addr()?;
+        Ok(Some(Listener::Unix(unix)))
+    }
+
+    /// Inheriting sockets is only supported on Unix platforms.
+    #[cfg(not(unix))]
+    crate fn inherited() -> io::Result<Option<Listener>> {
+        Ok(None)
+    }
+
+    /// Binds to `address` and `port`. If the address is of the form
+    /// `unix:/path/to.sock`, binds a Unix domain socket at that path, removing
+    /// a stale socket file first. Files at the path that aren't sockets are
+    /// never removed; binding fails instead.
+    crate fn bind(address: &str, port: u16) -> io::Result<Listener> {
+        #[cfg(unix)]
+        {
+            if address.starts_with("unix:") {
+                let path = Path::new(&address["unix:".len()..]);
+                // Only remove the file if it's a socket nobody is listening on.
+                let is_socket = match fs::symlink_metadata(path) {
+                    Ok(metadata) => metadata.file_type().is_socket(),
+                    Err(ref e) if e.kind() == io::ErrorKind::NotFound => false,
+                    Err(e) => return Err(e),
+                };
+
+                if is_socket && UnixStream::connect(path).is_err() {
+                    fs::remove_file(path)?;
+                }
+
+                return UnixListener::bind(path).map(Listener::Unix);
+            }
+        }
+
+        TcpListener::bind((address, port)).map(Listener::Tcp)
+    }
+
+    /// Returns the port this listener is bound to, if it's a TCP listener.
+    crate fn port(&self) -> io::Result<Option<u16>> {
+        match *self {
+            Listener::Tcp(ref listener) => Ok(Some(listener.local_addr()?.port())),
+            #[cfg(unix)] Listener::Unix(_) => Ok(None),
+        }
+    }
+
+    /// Returns a human readable address for the listener, sans protocol.
+    crate fn address(&self) -> io::Result<String> {
+        match *self {
+            Listener::Tcp(ref listener) => Ok(listener.local_addr()?.to_string()),
+            #[cfg(unix)]
+            Listener::Unix(ref listener) => {
+                let addr = listener.local_addr()?;
+                let path = addr.as_pathname().map(|p| p.display().to_string());
+                Ok(format!("unix:{}", path.unwrap_or_else(|| "(unnamed)".into())))
+            }
+        }
+    }
+}
+
+/// The application protocols offered via ALPN on TLS connections, in order of
+/// preference, when HTTP/2 is enabled and when it isn't.
+#[cfg(feature = "tls")]
+const ALPN_HTTP2: &[&str] = &["h2", "http/1.1"];
+#[cfg(feature = "tls")]
+const ALPN_HTTP1: &[&str] = &["http/1.1"];
+
+/// Accepts connections from a `Listener`, wrapping them in TLS if configured.
+crate struct Acceptor {
+    listener: Listener,
+    source: Source,
+    #[cfg(feature = "tls")]
+    tls: Option<TlsServer>,
+    // The path to a Unix socket file to remove once the acceptor is dropped.
+    #[cfg(unix)]
+    socket_path: Option<PathBuf>,
+}
+
+impl Acceptor {
+    #[cfg_attr(not(feature = "tls"), allow(unused_variables))]
+    crate fn new(
+        listener: Listener,
+        source: Source,
+        tls: Option<&TlsConfig>,
+        http2: bool
+    ) -> io::Result<Acceptor> {
+        // Only remove socket files that Rocket itself created.
+        #[cfg(unix)]
+        let socket_path = match listener {
+            Listener::Unix(ref listener) if source == Source::Bound => {
+                listener.local_addr().ok()
+                    .and_then(|addr| addr.as_pathname().map(|p| p.to_path_buf()))
+            }
+            _ => None
+        };
+
+        #[cfg(feature = "tls")]
+        let tls = match (&listener, tls.cloned()) {
+            (&Listener::Tcp(_), Some(tls)) => {
+                let offer_http2 = http2 && cfg!(feature = "http2");
+                let protocols = if offer_http2 { ALPN_HTTP2 } else { ALPN_HTTP1 };
+                let server = TlsServer::new(tls.certs, tls.key, protocols)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+                Some(server)
+            }
+            #[cfg(unix)]
+            (&Listener::Unix(_), Some(_)) => {
+                warn_!("TLS is not supported on Unix domain sockets. Ignoring.");
+                None
+            }
+            _ => None
+        };
+
+        Ok(Acceptor {
+            listener,
+            source,
+            #[cfg(feature = "tls")] tls,
+            #[cfg(unix)] socket_path,
+        })
+    }
+
+    #[inline]
+    crate fn listener(&self) -> &Listener {
+        &self.listener
+    }
+
+    #[inline]
+    crate fn source(&self) -> Source {
+        self.source
+    }
+
+    /// Returns the protocol prefix for URLs served by this acceptor.
+    crate fn protocol(&self) -> &'static str {
+        match self.listener {
+            #[cfg(feature = "tls")]
+            Listener::Tcp(_) if self.tls.is_some() => "https://",
+            Listener::Tcp(_) => "http://",
+            #[cfg(unix)] Listener::Unix(_) => "",
+        }
+    }
+
+    /// Blocks until a new connection is accepted.
+    crate fn accept(&self) -> io::Result<NetStream> {
+        match self.listener {
+            Listener::Tcp(ref listener) => {
+                let stream = HttpStream(listener.accept()?.0);
+
+                #[cfg(feature = "tls")]
+                {
+                    if let Some(ref tls) = self.tls {
+                        return Ok(NetStream::Https(tls.accept(stream)));
+                    }
+                }
+
+                Ok(NetStream::Http(stream))
+            }
+            #[cfg(unix)]
+            Listener::Unix(ref listener) => {
+                Ok(NetStream::Unix(listener.accept()?.0.into()))
+            }
+        }
+    }
+
+    /// Connects to the listener so that a thread blocked in `accept()` wakes.
+    crate fn wake(&self) -> io::Result<()> {
+        match self.listener {
+            Listener::Tcp(ref listener) => {
+                let addr = listener.local_addr()?;
+                let ip = match addr.ip() {
+                    IpAddr::V4(ip) if ip.is_unspecified() => Ipv4Addr::LOCALHOST.into(),
+                    IpAddr::V6(ip) if ip.is_unspecified() => Ipv6Addr::LOCALHOST.into(),
+                    ip => ip
+                };
+
+                let wake_addr = SocketAddr::new(ip, addr.port());
+                TcpStream::connect_timeout(&wake_addr, Duration::from_secs(1)).map(|_| ())
+            }
+            #[cfg(unix)]
+            Listener::Unix(ref listener) => {
+                let addr = listener.local_addr()?;
+                let path = addr.as_pathname()
+                    .ok_or_else(|| io::Error::from(io::ErrorKind::AddrNotAvailable))?;
+
+                UnixStream::connect(path).map(|_| ())
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Acceptor {
+    fn drop(&mut self) {
+        if let Some(ref path) = self.socket_path {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
@@ -5,7 +5,7 @@ use std::ops::{Deref, DerefMut};
 use std::borrow::Cow;
 
 use {Request, Response, Data};
-use http::{Status, Method, Header, Cookie, uri::Origin, ext::IntoOwned};
+use http::{Status, Method, Protocol, Header, Cookie, uri::Origin, ext::IntoOwned};
 use local::Client;
 
 /// A structure representing a local request as created by [`Client`].
@@ -15,9 +15,10 @@ use local::Client;
 /// A `LocalRequest` value is constructed via method constructors on [`Client`].
 /// Headers can be added via the [`header`] builder method and the
 /// [`add_header`] method. Cookies can be added via the [`cookie`] builder
-/// method. The remote IP address can be set via the [`remote`] builder method.
-/// The body of the request can be set via the [`body`] builder method or
-/// [`set_body`] method.
+/// method. The remote IP address can be set via the [`remote`] builder method,
+/// and the version of the HTTP protocol being simulated via the [`protocol`]
+/// builder method. The body of the request can be set via the [`body`] builder
+/// method or [`set_body`] method.
 ///
 /// ## Example
 ///
@@ -61,6 +62,7 @@ use local::Client;
 /// [`add_header`]: #method.add_header
 /// [`cookie`]: #method.cookie
 /// [`remote`]: #method.remote
+/// [`protocol`]: #method.protocol
 /// [`body`]: #method.body
 /// [`set_body`]: #method.set_body
 /// [`dispatch`]: #method.dispatch
@@ -219,6 +221,27 @@ impl<'c> LocalRequest<'c> {
         self
     }
 
+    /// Set the version of the HTTP protocol this request simulates. Requests
+    /// simulate HTTP/1.1 by default.
+    ///
+    /// # Examples
+    ///
+    /// Simulate a request received over HTTP/2:
+    ///
+    /// ```rust
+    /// use rocket::local::Client;
+    /// use rocket::http::Protocol;
+    ///
+    /// let client = Client::new(rocket::ignite()).unwrap();
+    /// let req = client.get("/").protocol(Protocol::Http2);
+    /// assert_eq!(req.inner().protocol(), Protocol::Http2);
+    /// ```
+    #[inline]
+    pub fn protocol(mut self, protocol: Protocol) -> Self {
+        self.request_mut().set_protocol(protocol);
+        self
+    }
+
     /// Add a cookie to this request.
     ///
     /// # Examples
@@ -0,0 +1,149 @@
+use std::fmt;
+use std::net::SocketAddr;
+
+use http::Status;
+
+/// The reason an incoming request could not be turned into a [`Request`].
+///
+/// [`Request`]: ::Request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BadRequestError {
+    /// The request is not a syntactically valid HTTP request. The string
+    /// describes the parse failure.
+    Malformed(String),
+    /// The request method is not one that Rocket can represent: it is an
+    /// extension method whose name is longer than 31 bytes, the most an
+    /// [`ExtensionMethod`](::http::ExtensionMethod) can hold.
+    UnknownMethod,
+    /// The request URI is not in origin form, that is, it is not of the form
+    /// `/path?query`. This is the case, for instance, for `*` and for absolute
+    /// URIs such as `http://rocket.rs/`. Only `CONNECT` requests may use an
+    /// authority, such as `rocket.rs:443`, instead.
+    UnsupportedUri,
+    /// The request URI is in origin form, or in authority form for a
+    /// `CONNECT` request, but failed to parse. The string describes the parse
+    /// failure.
+    InvalidUri(String),
+    /// The client failed to send the complete request head within the
+    /// configured `header_read_timeout`.
+    Timeout,
+    /// The request head is too large to be parsed: it contains more than 100
+    /// headers or is larger than the server's read buffer.
+    HeadersTooLarge,
+}
+
+impl BadRequestError {
+    /// The status a response to a request failing with `self` should have.
+    crate fn status(&self) -> Status {
+        match *self {
+            BadRequestError::Timeout => Status::RequestTimeout,
+            BadRequestError::HeadersTooLarge => Status::RequestHeaderFieldsTooLarge,
+            _ => Status::BadRequest,
+        }
+    }
+}
+
+impl fmt::Display for BadRequestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BadRequestError::Malformed(ref e) => write!(f, "malformed request: {}", e),
+            BadRequestError::UnknownMethod => write!(f, "unknown request method"),
+            BadRequestError::UnsupportedUri => write!(f, "URI is not in origin form"),
+            BadRequestError::InvalidUri(ref e) => write!(f, "invalid URI: {}", e),
+            BadRequestError::Timeout => write!(f, "timed out reading request head"),
+            BadRequestError::HeadersTooLarge => write!(f, "request head is too large"),
+        }
+    }
+}
+
+/// Information about an incoming request that could not be parsed.
+///
+/// When an incoming request cannot be turned into a [`Request`], Rocket can't
+/// route it, and it can't invoke a catcher, since catchers operate on a
+/// `Request`. Instead, Rocket logs the failure and calls the application's
+/// [bad request handler](::handler::BadRequestHandler) with a `BadRequestInfo`
+/// describing the failure. The handler's response is sent to the client, after
+/// which the connection is closed. A handler is registered via
+/// [`Rocket::on_bad_request()`](::Rocket::on_bad_request()).
+///
+/// A request whose head is sent too slowly or is too large to parse fails with
+/// [`BadRequestError::Timeout`] or [`BadRequestError::HeadersTooLarge`]. If
+/// its request line was read, however, there is a `Request` after all: it is
+/// responded to by the `408` or `431` catcher, respectively, like any other
+/// request, and the bad request handler isn't called. The default
+/// handler responds with a generic `400 Bad Request` HTML page or, for
+/// [`BadRequestError::Timeout`] and [`BadRequestError::HeadersTooLarge`], a
+/// `408 Request Timeout` or `431 Request Header Fields Too Large` page.
+///
+/// Which of the raw method and URI are available depends on how far parsing
+/// progressed: if the request line itself is malformed, neither is.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io::Cursor;
+///
+/// use rocket::Response;
+/// use rocket::http::Status;
+/// use rocket::request::BadRequestInfo;
+///
+/// fn bad_request(info: &BadRequestInfo) -> Response<'static> {
+///     let uri = info.uri().map(String::from_utf8_lossy);
+///     eprintln!("bad request from {:?} for {:?}: {}", info.remote(), uri, info.error());
+///
+///     Response::build()
+///         .status(Status::BadRequest)
+///         .sized_body(Cursor::new(info.error().to_string()))
+///         .finalize()
+/// }
+///
+/// # if false {
+/// let _ = rocket::ignite().on_bad_request(bad_request).launch();
+/// # }
+/// ```
+///
+/// [`Request`]: ::Request
+#[derive(Debug, Clone)]
+pub struct BadRequestInfo {
+    method: Option<String>,
+    uri: Option<Vec<u8>>,
+    error: BadRequestError,
+    remote: Option<SocketAddr>,
+}
+
+impl BadRequestInfo {
+    crate fn new(
+        method: Option<String>,
+        uri: Option<Vec<u8>>,
+        error: BadRequestError,
+        remote: Option<SocketAddr>
+    ) -> BadRequestInfo {
+        BadRequestInfo { method, uri, error, remote }
+    }
+
+    /// Returns the request method as sent by the client, if it was parsed.
+    #[inline(always)]
+    pub fn method(&self) -> Option<&str> {
+        self.method.as_ref().map(|s| s.as_str())
+    }
+
+    /// Returns the raw bytes of the request URI as sent by the client, if it
+    /// was parsed.
+    #[inline(always)]
+    pub fn uri(&self) -> Option<&[u8]> {
+        self.uri.as_ref().map(|v| v.as_slice())
+    }
+
+    /// Returns the reason the request could not be parsed.
+    #[inline(always)]
+    pub fn error(&self) -> &BadRequestError {
+        &self.error
+    }
+
+    /// Returns the address of the client that sent the request, if known. The
+    /// address is unknown for connections over Unix domain sockets.
+    #[inline(always)]
+    pub fn remote(&self) -> Option<SocketAddr> {
+        self.remote
+    }
+}
@@ -6,7 +6,7 @@ use request::Request;
 use outcome::{self, IntoOutcome};
 use outcome::Outcome::*;
 
-use http::{Status, ContentType, Accept, Method, Cookies, uri::Origin};
+use http::{Status, ContentType, Accept, Method, Protocol, Cookies, uri::Origin};
 
 /// Type alias for the `Outcome` of a `FromRequest` conversion.
 pub type Outcome<S, E> = outcome::Outcome<S, (Status, E), ()>;
@@ -104,6 +104,12 @@ impl<S, E> IntoOutcome<S, (Status, E), ()> for Result<S, E> {
 ///
 ///     _This implementation always returns successfully._
 ///
+///   * **Protocol**
+///
+///     Extracts the [`Protocol`] the incoming request was received over.
+///
+///     _This implementation always returns successfully._
+///
 ///   * **&Origin**
 ///
 ///     Extracts the [`Origin`] URI from the incoming request.
@@ -365,6 +371,14 @@ impl<'a, 'r> FromRequest<'a, 'r> for Method {
     }
 }
 
+impl<'a, 'r> FromRequest<'a, 'r> for Protocol {
+    type Error = !;
+
+    fn from_request(request: &'a Request<'r>) -> Outcome<Self, Self::Error> {
+        Success(request.protocol())
+    }
+}
+
 impl<'a, 'r> FromRequest<'a, 'r> for &'a Origin<'a> {
     type Error = !;
 
@@ -6,6 +6,7 @@ mod form;
 mod from_request;
 mod state;
 mod query;
+mod bad_request;
 
 #[cfg(test)]
 mod tests;
@@ -20,6 +21,10 @@ pub use self::form::{Form, LenientForm, FormItems, FormItem};
 pub use self::form::{FormError, FormParseError, FormDataError};
 pub use self::state::State;
 pub use self::query::{Query, FromQuery};
+pub use self::bad_request::{BadRequestInfo, BadRequestError};
 
 #[doc(inline)]
 pub use response::flash::FlashMessage;
+
+#[doc(inline)]
+pub use response::event_stream::LastEventId;
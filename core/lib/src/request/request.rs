@@ -9,12 +9,14 @@ use state::{Container, Storage};
 
 use request::{FromParam, FromSegments, FromRequest, Outcome};
 use request::{FromFormValue, FormItems, FormItem};
+use request::{BadRequestInfo, BadRequestError};
 
 use rocket::Rocket;
 use router::Route;
+use error::UrlForError;
 use config::{Config, Limits};
-use http::{hyper, uri::{Origin, Segments}};
-use http::{Method, Header, HeaderMap, Cookies};
+use http::{hyper, uri::{Origin, Authority, Segments}, ext::IntoOwned};
+use http::{Method, Protocol, Header, HeaderMap, Cookies};
 use http::{RawStr, ContentType, Accept, MediaType};
 use http::private::{Indexed, SmallVec, CookieJar};
 
@@ -32,11 +34,14 @@ pub struct Request<'r> {
     uri: Origin<'r>,
     headers: HeaderMap<'r>,
     remote: Option<SocketAddr>,
+    protocol: Protocol,
+    connect_target: Option<Authority<'r>>,
     crate state: RequestState<'r>,
 }
 
 #[derive(Clone)]
 crate struct RequestState<'r> {
+    crate rocket: &'r Rocket,
     crate config: &'r Config,
     crate managed: &'r Container,
     crate path_segments: SmallVec<[Indices; 12]>,
@@ -68,9 +73,12 @@ impl<'r> Request<'r> {
             uri: uri,
             headers: HeaderMap::new(),
             remote: None,
+            protocol: Protocol::Http1_1,
+            connect_target: None,
             state: RequestState {
                 path_segments: SmallVec::new(),
                 query_items: None,
+                rocket: rocket,
                 config: &rocket.config,
                 managed: &rocket.state,
                 route: Cell::new(None),
@@ -211,6 +219,62 @@ impl<'r> Request<'r> {
         self.remote = Some(address);
     }
 
+    /// Returns the version of the HTTP protocol the request was received over.
+    /// Requests are assumed to be HTTP/1.1 requests unless set otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// use rocket::http::{Method, Protocol};
+    ///
+    /// # Request::example(Method::Get, "/uri", |request| {
+    /// assert_eq!(request.protocol(), Protocol::Http1_1);
+    /// # });
+    /// ```
+    #[inline(always)]
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    /// Sets the version of the HTTP protocol of `self` to `protocol`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// use rocket::http::{Method, Protocol};
+    ///
+    /// # Request::example(Method::Get, "/uri", |mut request| {
+    /// request.set_protocol(Protocol::Http2);
+    /// assert_eq!(request.protocol(), Protocol::Http2);
+    /// # });
+    /// ```
+    #[inline(always)]
+    pub fn set_protocol(&mut self, protocol: Protocol) {
+        self.protocol = protocol;
+    }
+
+    /// Returns the target of a `CONNECT` request: the authority of the server
+    /// the client wants a tunnel to. Returns `None` for all other requests.
+    ///
+    /// `CONNECT` requests don't have a path. They are routed as if their URI
+    /// were `/`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// # Request::example(Method::Get, "/uri", |request| {
+    /// assert!(request.connect_target().is_none());
+    /// # });
+    /// ```
+    #[inline(always)]
+    pub fn connect_target(&self) -> Option<&Authority<'r>> {
+        self.connect_target.as_ref()
+    }
+
     /// Returns the IP address in the "X-Real-IP" header of the request if such
     /// a header exists and contains a valid IP address.
     ///
@@ -499,6 +563,33 @@ impl<'r> Request<'r> {
         self.state.route.get()
     }
 
+    /// Returns the URI of the mounted route named `name` with its dynamic
+    /// parameters set to the values in `params`. See
+    /// [`Rocket::url_for()`](::Rocket::url_for()) for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #![feature(proc_macro_hygiene, decl_macro)]
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::Request;
+    /// use rocket::http::uri::Origin;
+    ///
+    /// #[get("/<id>/profile")]
+    /// fn user_profile(id: usize) { /* .. */ }
+    ///
+    /// fn profile_link(request: &Request, id: usize) -> Option<Origin<'static>> {
+    ///     request.url_for("user_profile", vec![("id", id)]).ok()
+    /// }
+    /// # fn main() {  }
+    /// ```
+    #[inline(always)]
+    pub fn url_for<I, K, V>(&self, name: &str, params: I) -> Result<Origin<'static>, UrlForError>
+        where I: IntoIterator<Item = (K, V)>, K: AsRef<str>, V: fmt::Display
+    {
+        self.state.rocket.url_for(name, params)
+    }
+
     /// Invokes the request guard implementation for `T`, returning its outcome.
     ///
     /// # Example
@@ -643,6 +734,42 @@ impl<'r> Request<'r> {
         Some(T::from_segments(self.raw_segments(n)?))
     }
 
+    /// Retrieves and parses into `T` the 0-indexed `n`th label of the host in
+    /// the request's `Host` header, ignoring any port. Returns `None` if the
+    /// request has no `Host` header or `n` is greater than the number of
+    /// labels. Returns `Some(Err(T::Error))` if the parameter type `T` failed
+    /// to be parsed from the `n`th label.
+    ///
+    /// This method exists only to be used by manual routing. To retrieve host
+    /// parameters from a request, use Rocket's code generation facilities.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::{Request, http::Method};
+    /// use rocket::http::{RawStr, Header};
+    ///
+    /// # Request::example(Method::Get, "/", |req| {
+    /// fn label<'s>(req: &'s mut Request, host: &'static str, n: usize) -> &'s RawStr {
+    ///     req.replace_header(Header::new("Host", host));
+    ///
+    ///     req.get_host_param(n)
+    ///         .and_then(|r| r.ok())
+    ///         .unwrap_or("none".into())
+    /// }
+    ///
+    /// assert_eq!(label(req, "acme.example.com", 0).as_str(), "acme");
+    /// assert_eq!(label(req, "acme.example.com:8000", 2).as_str(), "com");
+    /// assert_eq!(label(req, "acme.example.com", 3).as_str(), "none");
+    /// # });
+    /// ```
+    #[inline]
+    pub fn get_host_param<'a, T>(&'a self, n: usize) -> Option<Result<T, T::Error>>
+        where T: FromParam<'a>
+    {
+        Some(T::from_param(self.raw_host_label(n)?))
+    }
+
     /// Retrieves and parses into `T` the query value with key `key`. `T` must
     /// implement [`FromFormValue`], which is used to parse the query's value.
     /// Key matching is performed case-sensitively. If there are multiple pairs
@@ -737,6 +864,13 @@ impl<'r> Request<'r> {
             .map(|(i, _)| Segments(&self.uri.path()[i..]) )
     }
 
+    /// Get the `n`th label, 0-indexed, of the host in the `Host` header, if it
+    /// exists. Used by codegen.
+    #[inline]
+    pub fn raw_host_label(&self, n: usize) -> Option<&RawStr> {
+        self.raw_host_labels().nth(n)
+    }
+
     // Returns an iterator over the raw segments of the path URI. Does not take
     // into account the current route. This is used during routing.
     #[inline]
@@ -746,6 +880,19 @@ impl<'r> Request<'r> {
             .map(move |(i, j)| path[i..j].into())
     }
 
+    // Returns an iterator over the labels of the host in the `Host` header,
+    // without any port. This is used during routing.
+    crate fn raw_host_labels(&self) -> impl Iterator<Item = &RawStr> {
+        let host = self.headers().get_one("Host").unwrap_or("");
+        let host = match host.rfind(':') {
+            // The colons of a bracketed IPv6 address aren't a port separator.
+            Some(i) if !host[i..].contains(']') => &host[..i],
+            _ => host
+        };
+
+        host.split('.').filter(|label| !label.is_empty()).map(|label| label.into())
+    }
+
     #[inline]
     fn routed_path_segment(&self, n: usize) -> Option<(usize, usize)> {
         let mount_segments = self.route()
@@ -766,6 +913,15 @@ impl<'r> Request<'r> {
         })
     }
 
+    // Returns `true` if the client announced that it accepts trailers in a
+    // chunked response by sending `TE: trailers`.
+    crate fn accepts_trailers(&self) -> bool {
+        self.headers().get("TE")
+            .flat_map(|value| value.split(','))
+            .filter_map(|coding| coding.split(';').next())
+            .any(|coding| coding.trim().eq_ignore_ascii_case("trailers"))
+    }
+
     /// Set `self`'s parameters given that the route used to reach this request
     /// was `route`. Use during routing when attempting a given route.
     #[inline(always)]
@@ -786,26 +942,50 @@ impl<'r> Request<'r> {
         h_method: hyper::Method,
         h_headers: hyper::header::Headers,
         h_uri: hyper::RequestUri,
-        h_addr: SocketAddr,
-    ) -> Result<Request<'r>, String> {
-        // Get a copy of the URI for later use.
-        let uri = match h_uri {
-            hyper::RequestUri::AbsolutePath(s) => s,
-            _ => return Err(format!("Bad URI: {}", h_uri)),
+        h_addr: Option<SocketAddr>,
+    ) -> Result<Request<'r>, BadRequestInfo> {
+        let bad_request = |uri: String, error| {
+            let method = Some(h_method.to_string());
+            BadRequestInfo::new(method, Some(uri.into_bytes()), error, h_addr)
+        };
+
+        // Get a copy of the URI for later use. The target of a `CONNECT`
+        // request is an authority instead; such requests are routed to `/`.
+        let (uri, connect_target) = match h_uri {
+            hyper::RequestUri::AbsolutePath(s) => (s, None),
+            hyper::RequestUri::Authority(ref s) if h_method == hyper::Method::Connect => {
+                match Authority::parse(s) {
+                    Ok(authority) => ("/".to_string(), Some(authority.into_owned())),
+                    Err(e) => {
+                        let error = BadRequestError::InvalidUri(e.to_string());
+                        return Err(bad_request(s.clone(), error));
+                    }
+                }
+            }
+            _ => return Err(bad_request(h_uri.to_string(), BadRequestError::UnsupportedUri)),
         };
 
-        // Ensure that the method is known. TODO: Allow made-up methods?
+        // Ensure that the method is valid. Extension methods are allowed.
         let method = match Method::from_hyp(&h_method) {
             Some(method) => method,
-            None => return Err(format!("Invalid method: {}", h_method))
+            None => return Err(bad_request(uri, BadRequestError::UnknownMethod)),
         };
 
         // We need to re-parse the URI since we don't trust Hyper... :(
-        let uri = Origin::parse_owned(uri).map_err(|e| e.to_string())?;
+        let uri = match Origin::parse_owned(uri.clone()) {
+            Ok(uri) => uri,
+            Err(e) => {
+                let error = BadRequestError::InvalidUri(e.to_string());
+                return Err(bad_request(uri, error));
+            }
+        };
 
         // Construct the request object.
         let mut request = Request::new(rocket, method, uri);
-        request.set_remote(h_addr);
+        request.connect_target = connect_target;
+        if let Some(addr) = h_addr {
+            request.set_remote(addr);
+        }
 
         // Set the request cookies, if they exist.
         if let Some(cookie_headers) = h_headers.get_raw("Cookie") {
@@ -849,6 +1029,7 @@ impl<'r> fmt::Debug for Request<'r> {
             .field("uri", &self.uri)
             .field("headers", &self.headers())
             .field("remote", &self.remote())
+            .field("protocol", &self.protocol())
             .finish()
     }
 }
@@ -22,7 +22,7 @@ macro_rules! assert_headers {
         // Dispatch the request and check that the headers are what we expect.
         let config = Config::development();
         let r = Rocket::custom(config);
-        let req = Request::from_hyp(&r, h_method, h_headers, h_uri, h_addr).unwrap();
+        let req = Request::from_hyp(&r, h_method, h_headers, h_uri, Some(h_addr)).unwrap();
         let actual_headers = req.headers();
         for (key, values) in expected.iter() {
             let actual: Vec<_> = actual_headers.get(key).collect();
@@ -0,0 +1,374 @@
+use std::io::{self, Read, Cursor};
+use std::ops::Deref;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use outcome::Outcome;
+use request::{self, Request, FromRequest};
+use response::{self, Response, Responder, DEFAULT_CHUNK_SIZE};
+use http::ContentType;
+
+/// A single Server-Sent Event.
+///
+/// An event consists of optional `data`, an optional `id`, which clients send
+/// back in the `Last-Event-ID` header when they reconnect, an optional `event`
+/// name, and an optional `retry` time, which clients wait for before
+/// reconnecting. Events are sent via an [`EventStream`].
+///
+/// Line breaks in `data` are preserved; line breaks in the `id` and `event`
+/// name are removed.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use rocket::response::Event;
+///
+/// # #[allow(unused_variables)]
+/// let event = Event::data("Hello!")
+///     .with_id("1")
+///     .with_event("greeting")
+///     .with_retry(Duration::from_secs(5));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Event {
+    id: Option<String>,
+    event: Option<String>,
+    data: Option<String>,
+    retry: Option<Duration>,
+}
+
+impl Event {
+    /// Creates an event that carries `data`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::response::Event;
+    ///
+    /// # #[allow(unused_variables)]
+    /// let event = Event::data("line one\nline two");
+    /// ```
+    #[inline]
+    pub fn data<S: Into<String>>(data: S) -> Event {
+        Event { data: Some(data.into()), ..Event::default() }
+    }
+
+    /// Sets the id of `self` to `id`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::response::Event;
+    ///
+    /// # #[allow(unused_variables)]
+    /// let event = Event::data("Hello!").with_id("1");
+    /// ```
+    #[inline]
+    pub fn with_id<S: Into<String>>(mut self, id: S) -> Event {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets the event name of `self` to `event`. Clients dispatch events
+    /// without a name as `message` events.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::response::Event;
+    ///
+    /// # #[allow(unused_variables)]
+    /// let event = Event::data("Hello!").with_event("greeting");
+    /// ```
+    #[inline]
+    pub fn with_event<S: Into<String>>(mut self, event: S) -> Event {
+        self.event = Some(event.into());
+        self
+    }
+
+    /// Sets the reconnection time of `self` to `retry`. The time is sent in
+    /// whole milliseconds.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use rocket::response::Event;
+    ///
+    /// // An event that only changes the client's reconnection time.
+    /// # #[allow(unused_variables)]
+    /// let event = Event::default().with_retry(Duration::from_secs(10));
+    /// ```
+    #[inline]
+    pub fn with_retry(mut self, retry: Duration) -> Event {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Appends the wire format of `self` to `buf`.
+    fn encode(&self, buf: &mut Vec<u8>) {
+        fn field(buf: &mut Vec<u8>, name: &str, value: &str) {
+            buf.extend_from_slice(name.as_bytes());
+            buf.extend_from_slice(b": ");
+            buf.extend(value.bytes().filter(|&b| b != b'\r' && b != b'\n'));
+            buf.push(b'\n');
+        }
+
+        if let Some(retry) = self.retry {
+            let millis = retry.as_secs() * 1000 + retry.subsec_millis() as u64;
+            field(buf, "retry", &millis.to_string());
+        }
+
+        if let Some(ref id) = self.id {
+            field(buf, "id", &id.replace('\0', ""));
+        }
+
+        if let Some(ref event) = self.event {
+            field(buf, "event", event);
+        }
+
+        if let Some(ref data) = self.data {
+            for line in data.replace("\r\n", "\n").split(|c| c == '\r' || c == '\n') {
+                field(buf, "data", line);
+            }
+        }
+
+        buf.push(b'\n');
+    }
+}
+
+/// Streams [`Server-Sent Events`] to a client.
+///
+/// An `EventStream` is created from an iterator of [`Event`]s, such as the
+/// receiving end of a channel. Each event is sent to the client, and flushed,
+/// as soon as the iterator produces it. The stream ends when the iterator
+/// does. The response has a `Content-Type` of `text/event-stream` and is not
+/// to be cached.
+///
+/// Clients that reconnect send the id of the last event they received in the
+/// `Last-Event-ID` header. Use the [`LastEventId`](::request::LastEventId)
+/// request guard to resume the stream from there.
+///
+/// [`Server-Sent Events`]: https://html.spec.whatwg.org/multipage/server-sent-events.html
+///
+/// # Heartbeats
+///
+/// Proxies may close connections that are idle for too long. To keep the
+/// connection alive, [`heartbeat()`](EventStream::heartbeat()) sends a comment,
+/// which clients ignore, whenever no event was produced for some time.
+///
+/// # Example
+///
+/// ```rust
+/// # #![feature(proc_macro_hygiene, decl_macro)]
+/// # #[macro_use] extern crate rocket;
+/// use std::time::Duration;
+/// use rocket::request::LastEventId;
+/// use rocket::response::{Event, EventStream};
+///
+/// #[get("/countdown")]
+/// fn countdown(last: Option<LastEventId>) -> EventStream<impl Iterator<Item = Event>> {
+///     // Resume after the last event the client saw, if any.
+///     let start = last.and_then(|id| id.parse::<u64>().ok()).unwrap_or(11);
+///     let events = (0..start).rev().map(|i| {
+///         std::thread::sleep(Duration::from_secs(1));
+///         Event::data(i.to_string()).with_id(i.to_string())
+///     });
+///
+///     EventStream::from(events).heartbeat(Duration::from_secs(15))
+/// }
+/// # fn main() {  }
+/// ```
+pub struct EventStream<I> {
+    source: Source<I>,
+}
+
+enum Source<I> {
+    Events(I),
+    Heartbeat(Receiver<Event>, Duration),
+}
+
+impl<I: Iterator<Item = Event> + Send + 'static> EventStream<I> {
+    /// Sends a comment to the client whenever no event was produced for
+    /// `interval`. To do so, the iterator is driven on a separate thread.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::sync::mpsc;
+    /// use std::time::Duration;
+    /// use rocket::response::{Event, EventStream};
+    ///
+    /// let (sender, receiver) = mpsc::channel::<Event>();
+    /// # #[allow(unused_variables)]
+    /// let stream = EventStream::from(receiver).heartbeat(Duration::from_secs(15));
+    /// # drop(sender);
+    /// ```
+    pub fn heartbeat(self, interval: Duration) -> EventStream<I> {
+        let receiver = match self.source {
+            Source::Heartbeat(receiver, _) => receiver,
+            Source::Events(events) => {
+                let (sender, receiver) = mpsc::channel();
+                thread::spawn(move || {
+                    for event in events {
+                        if sender.send(event).is_err() {
+                            break;
+                        }
+                    }
+                });
+
+                receiver
+            }
+        };
+
+        EventStream { source: Source::Heartbeat(receiver, interval) }
+    }
+}
+
+/// Creates an `EventStream` from an iterator, or anything that can be turned
+/// into one, of events.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::response::{Event, EventStream};
+///
+/// let events = vec![Event::data("one"), Event::data("two")];
+/// # #[allow(unused_variables)]
+/// let stream = EventStream::from(events);
+/// ```
+impl<I: IntoIterator<Item = Event>> From<I> for EventStream<I::IntoIter> {
+    fn from(events: I) -> Self {
+        EventStream { source: Source::Events(events.into_iter()) }
+    }
+}
+
+// Reads the encoding of one event at a time from an event source.
+struct EventReader<I> {
+    source: Source<I>,
+    pending: Cursor<Vec<u8>>,
+}
+
+impl<I: Iterator<Item = Event>> Read for EventReader<I> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pending.position() as usize == self.pending.get_ref().len() {
+            let mut next = vec![];
+            match self.source {
+                Source::Events(ref mut events) => match events.next() {
+                    Some(event) => event.encode(&mut next),
+                    None => return Ok(0),
+                },
+                Source::Heartbeat(ref receiver, interval) => {
+                    match receiver.recv_timeout(interval) {
+                        Ok(event) => event.encode(&mut next),
+                        Err(RecvTimeoutError::Timeout) => next.extend_from_slice(b":\n\n"),
+                        Err(RecvTimeoutError::Disconnected) => return Ok(0),
+                    }
+                }
+            }
+
+            self.pending = Cursor::new(next);
+        }
+
+        self.pending.read(buf)
+    }
+}
+
+/// Sends a streamed response with a `Content-Type` of `text/event-stream` and
+/// a `Cache-Control` of `no-cache`. Every event is sent as soon as it's
+/// available.
+///
+/// # Failure
+///
+/// If the client disconnects, the response is abandoned the next time an
+/// event, or a heartbeat, is sent.
+impl<'r, I: Iterator<Item = Event> + 'r> Responder<'r> for EventStream<I> {
+    fn respond_to(self, _: &Request) -> response::Result<'r> {
+        let mut response = Response::build()
+            .header(ContentType::EventStream)
+            .raw_header("Cache-Control", "no-cache")
+            .finalize();
+
+        let reader = EventReader { source: self.source, pending: Cursor::new(vec![]) };
+        response.set_flushed_body(reader, DEFAULT_CHUNK_SIZE);
+        Ok(response)
+    }
+}
+
+/// The id of the last event a reconnecting client received, from the
+/// `Last-Event-ID` header.
+///
+/// The guard forwards if the header isn't present; use `Option<LastEventId>`
+/// to handle both first connections and reconnections. See [`EventStream`]
+/// for an example.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LastEventId(String);
+
+impl LastEventId {
+    /// Consumes `self` and returns the id.
+    #[inline(always)]
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl Deref for LastEventId {
+    type Target = str;
+
+    #[inline(always)]
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for LastEventId {
+    type Error = !;
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, !> {
+        match request.headers().get_one("Last-Event-ID") {
+            Some(id) => Outcome::Success(LastEventId(id.to_string())),
+            None => Outcome::Forward(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(event: Event) -> String {
+        let mut buf = vec![];
+        event.encode(&mut buf);
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn encodes_events() {
+        assert_eq!(encode(Event::data("hi")), "data: hi\n\n");
+        assert_eq!(encode(Event::data("")), "data: \n\n");
+        assert_eq!(encode(Event::data("a\nb\r\nc\rd\n")), "data: a\ndata: b\ndata: c\ndata: d\ndata: \n\n");
+        assert_eq!(encode(Event::default()), "\n");
+
+        let event = Event::data("x")
+            .with_id("4\n2")
+            .with_event("up\r\ndate")
+            .with_retry(Duration::from_millis(1500));
+
+        assert_eq!(encode(event), "retry: 1500\nid: 42\nevent: update\ndata: x\n\n");
+    }
+
+    #[test]
+    fn reads_one_event_at_a_time() {
+        let events = vec![Event::data("one"), Event::data("two")].into_iter();
+        let mut reader = EventReader { source: Source::Events(events), pending: Cursor::new(vec![]) };
+
+        let mut buf = [0; 64];
+        let n = reader.read(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"data: one\n\n");
+        let n = reader.read(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"data: two\n\n");
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+    }
+}
@@ -27,6 +27,7 @@ mod stream;
 mod response;
 
 crate mod flash;
+crate mod event_stream;
 
 pub mod content;
 pub mod status;
@@ -34,11 +35,14 @@ pub mod status;
 #[doc(hidden)] pub use rocket_codegen::Responder;
 
 pub use self::response::{Response, ResponseBuilder, Body, DEFAULT_CHUNK_SIZE};
+crate use self::response::Trailers;
 pub use self::responder::Responder;
 pub use self::redirect::Redirect;
 pub use self::flash::Flash;
 pub use self::named_file::NamedFile;
-pub use self::stream::Stream;
+pub use self::stream::{Stream, StreamSender, StreamReceiver};
+pub use self::event_stream::{EventStream, Event};
+pub use upgrade::Upgraded;
 #[doc(inline)] pub use self::content::Content;
 
 /// Type alias for the `Result` of a `Responder::respond` call.
@@ -240,12 +240,19 @@ impl<'r> Responder<'r> for Vec<u8> {
 }
 
 /// Returns a response with a sized body for the file. Always returns `Ok`.
+///
+/// On Linux, the body of a regular file is sent to plaintext connections with
+/// `sendfile()`, without copying it through userspace.
 impl<'r> Responder<'r> for File {
     fn respond_to(self, _: &Request) -> response::Result<'r> {
-        let (metadata, file) = (self.metadata(), BufReader::new(self));
-        match metadata {
-            Ok(md) => Response::build().raw_body(Body::Sized(file, md.len())).ok(),
-            Err(_) => Response::build().streamed_body(file).ok()
+        match self.metadata() {
+            Ok(ref md) if md.is_file() => {
+                let mut response = Response::new();
+                response.set_file_body(self, md.len());
+                Ok(response)
+            }
+            Ok(md) => Response::build().raw_body(Body::Sized(BufReader::new(self), md.len())).ok(),
+            Err(_) => Response::build().streamed_body(BufReader::new(self)).ok()
         }
     }
 }
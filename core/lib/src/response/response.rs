@@ -1,7 +1,10 @@
 use std::{io, fmt, str};
 use std::borrow::Cow;
+use std::fs::File;
+use std::sync::Arc;
 
 use response::Responder;
+use upgrade::{Upgrade, Upgraded};
 use http::{Header, HeaderMap, Status, ContentType, Cookie};
 
 /// The default size, in bytes, of a chunk for streamed responses.
@@ -432,6 +435,89 @@ impl<'r> ResponseBuilder<'r> {
         self
     }
 
+    /// Sets the callback that takes over the connection once the `Response`
+    /// has been written. See [`Response::set_upgrade()`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::io::Write;
+    ///
+    /// use rocket::Response;
+    /// use rocket::http::Status;
+    ///
+    /// # #[allow(unused_variables)]
+    /// let response = Response::build()
+    ///     .status(Status::SwitchingProtocols)
+    ///     .raw_header("Connection", "Upgrade")
+    ///     .raw_header("Upgrade", "hello")
+    ///     .upgrade(|mut conn| { let _ = conn.write_all(b"Hello!"); })
+    ///     .finalize();
+    /// ```
+    #[inline(always)]
+    pub fn upgrade<F>(&mut self, upgrade: F) -> &mut ResponseBuilder<'r>
+        where F: FnOnce(Upgraded) + Send + 'static
+    {
+        self.response.set_upgrade(upgrade);
+        self
+    }
+
+    /// Sets the trailers of the `Response` being built to `trailers`. See
+    /// [`Response::set_trailers()`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::io::Cursor;
+    ///
+    /// use rocket::Response;
+    /// use rocket::http::{Header, HeaderMap};
+    ///
+    /// let mut trailers = HeaderMap::new();
+    /// trailers.add(Header::new("X-Status", "done"));
+    ///
+    /// # #[allow(unused_variables)]
+    /// let response = Response::build()
+    ///     .streamed_body(Cursor::new("Hello, world!"))
+    ///     .trailers(trailers)
+    ///     .finalize();
+    /// ```
+    #[inline(always)]
+    pub fn trailers(&mut self, trailers: HeaderMap<'r>) -> &mut ResponseBuilder<'r> {
+        self.response.set_trailers(trailers);
+        self
+    }
+
+    /// Sets the trailers of the `Response` being built to those returned by
+    /// `f` once the body has been written. See
+    /// [`Response::set_trailers_with()`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::io::Cursor;
+    ///
+    /// use rocket::Response;
+    /// use rocket::http::{Header, HeaderMap};
+    ///
+    /// # #[allow(unused_variables)]
+    /// let response = Response::build()
+    ///     .streamed_body(Cursor::new("Hello, world!"))
+    ///     .trailers_with(|| {
+    ///         let mut trailers = HeaderMap::new();
+    ///         trailers.add(Header::new("X-Checksum", "1234"));
+    ///         trailers
+    ///     })
+    ///     .finalize();
+    /// ```
+    #[inline(always)]
+    pub fn trailers_with<F>(&mut self, f: F) -> &mut ResponseBuilder<'r>
+        where F: FnOnce() -> HeaderMap<'static> + 'r
+    {
+        self.response.set_trailers_with(f);
+        self
+    }
+
     /// Merges the `other` `Response` into `self` by setting any fields in
     /// `self` to the corresponding value in `other` if they are set in `other`.
     /// Fields in `self` are unchanged if they are not set in `other`. If a
@@ -561,6 +647,37 @@ pub struct Response<'r> {
     status: Option<Status>,
     headers: HeaderMap<'r>,
     body: Option<Body<Box<io::Read + 'r>>>,
+    flush_reads: bool,
+    file: Option<Arc<File>>,
+    trailers: Option<Trailers<'r>>,
+    upgrade: Option<Upgrade>,
+}
+
+/// Trailing headers of a response: either a fixed map of headers or a closure
+/// that produces them once the body has been written.
+crate enum Trailers<'r> {
+    Map(HeaderMap<'r>),
+    With(Box<FnOnce() -> HeaderMap<'static> + 'r>),
+}
+
+impl<'r> Trailers<'r> {
+    /// Returns the trailers, calling the closure that produces them if needed.
+    crate fn into_headers(self) -> HeaderMap<'r> {
+        match self {
+            Trailers::Map(map) => map,
+            Trailers::With(f) => f(),
+        }
+    }
+}
+
+/// Reads the body from a file that is shared with the `Response`.
+struct FileBody(Arc<File>);
+
+impl io::Read for FileBody {
+    #[inline(always)]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        io::Read::read(&mut &*self.0, buf)
+    }
 }
 
 impl<'r> Response<'r> {
@@ -587,6 +704,10 @@ impl<'r> Response<'r> {
             status: None,
             headers: HeaderMap::new(),
             body: None,
+            flush_reads: false,
+            file: None,
+            trailers: None,
+            upgrade: None,
         }
     }
 
@@ -967,6 +1088,7 @@ impl<'r> Response<'r> {
     /// ```
     #[inline(always)]
     pub fn take_body(&mut self) -> Option<Body<Box<io::Read + 'r>>> {
+        self.file = None;
         self.body.take()
     }
 
@@ -982,6 +1104,113 @@ impl<'r> Response<'r> {
         }
     }
 
+    /// Sets the callback that takes over the connection once `self` has been
+    /// written out. The callback is passed the [`Upgraded`] connection, the
+    /// raw stream to the client. It runs on a thread of its own, and the
+    /// connection is closed once it returns.
+    ///
+    /// The callback is only called if the status of `self` is `101 Switching
+    /// Protocols` or if `self` is a successful (`2xx`) response to a `CONNECT`
+    /// request, which establishes a tunnel. Such responses are written without
+    /// a body. Connections can't be upgraded over HTTP/2 and local requests
+    /// are never upgraded; the callback is dropped in both cases.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::io::{self, Write};
+    ///
+    /// use rocket::Response;
+    /// use rocket::http::{Header, Status};
+    ///
+    /// let mut response = Response::new();
+    /// response.set_status(Status::SwitchingProtocols);
+    /// response.set_header(Header::new("Connection", "Upgrade"));
+    /// response.set_header(Header::new("Upgrade", "echo"));
+    /// response.set_upgrade(|mut conn| {
+    ///     let mut reader = conn.try_clone().expect("clone");
+    ///     let _ = io::copy(&mut reader, &mut conn);
+    /// });
+    /// ```
+    #[inline]
+    pub fn set_upgrade<F>(&mut self, upgrade: F)
+        where F: FnOnce(Upgraded) + Send + 'static
+    {
+        self.upgrade = Some(Box::new(upgrade));
+    }
+
+    // Moves the upgrade callback out of `self`, if there is one.
+    #[inline(always)]
+    crate fn take_upgrade(&mut self) -> Option<Upgrade> {
+        self.upgrade.take()
+    }
+
+    /// Sets the trailers of `self` to `trailers`: headers that are sent after
+    /// a chunked body. Any trailers previously set are replaced.
+    ///
+    /// Trailers are only sent if the body of `self` is chunked and the client
+    /// announced that it accepts them by sending `TE: trailers`. Otherwise,
+    /// they are dropped. Headers that are needed to frame, route, or process
+    /// the response, such as `Content-Length`, must not be sent as trailers.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::io::Cursor;
+    ///
+    /// use rocket::Response;
+    /// use rocket::http::{Header, HeaderMap};
+    ///
+    /// let mut trailers = HeaderMap::new();
+    /// trailers.add(Header::new("X-Status", "done"));
+    ///
+    /// let mut response = Response::new();
+    /// response.set_streamed_body(Cursor::new("Hello, world!"));
+    /// response.set_trailers(trailers);
+    /// ```
+    #[inline]
+    pub fn set_trailers(&mut self, trailers: HeaderMap<'r>) {
+        self.trailers = Some(Trailers::Map(trailers));
+    }
+
+    /// Sets the trailers of `self` to those returned by `f`, which is called
+    /// once the body of `self` has been written out. This allows trailers to
+    /// depend on the body, as is the case for a checksum of the body. Any
+    /// trailers previously set are replaced.
+    ///
+    /// Trailers are sent under the same conditions as those set via
+    /// [`set_trailers()`](#method.set_trailers); if they aren't sent, `f` is
+    /// never called.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::io::Cursor;
+    ///
+    /// use rocket::Response;
+    /// use rocket::http::{Header, HeaderMap};
+    ///
+    /// let mut response = Response::new();
+    /// response.set_streamed_body(Cursor::new("Hello, world!"));
+    /// response.set_trailers_with(|| {
+    ///     let mut trailers = HeaderMap::new();
+    ///     trailers.add(Header::new("X-Checksum", "1234"));
+    ///     trailers
+    /// });
+    /// ```
+    #[inline]
+    pub fn set_trailers_with<F>(&mut self, f: F)
+        where F: FnOnce() -> HeaderMap<'static> + 'r
+    {
+        self.trailers = Some(Trailers::With(Box::new(f)));
+    }
+
+    // Moves the trailers out of `self`, if there are any.
+    #[inline(always)]
+    crate fn take_trailers(&mut self) -> Option<Trailers<'r>> {
+        self.trailers.take()
+    }
+
     /// Sets the body of `self` to be the fixed-sized `body`. The size of the
     /// body is obtained by `seek`ing to the end and then `seek`ing back to the
     /// start.
@@ -1011,6 +1240,8 @@ impl<'r> Response<'r> {
         body.seek(io::SeekFrom::Start(0))
             .expect("Attempted to reset body by seeking after getting size.");
         self.body = Some(Body::Sized(Box::new(body.take(size)), size));
+        self.flush_reads = false;
+        self.file = None;
     }
 
     /// Sets the body of `self` to be `body`, which will be streamed. The chunk
@@ -1050,6 +1281,42 @@ impl<'r> Response<'r> {
     pub fn set_chunked_body<B>(&mut self, body: B, chunk_size: u64)
             where B: io::Read + 'r {
         self.body = Some(Body::Chunked(Box::new(body), chunk_size));
+        self.flush_reads = false;
+        self.file = None;
+    }
+
+    // Sets the body of `self` to be the chunked `body`. Whatever a single read
+    // of `body` returns, up to `chunk_size` bytes, is sent to the client right
+    // away, so reads should return whole messages, such as events, as soon as
+    // they're available.
+    #[inline(always)]
+    crate fn set_flushed_body<B>(&mut self, body: B, chunk_size: u64)
+            where B: io::Read + 'r {
+        self.set_chunked_body(body, chunk_size);
+        self.flush_reads = true;
+    }
+
+    // Returns `true` if the body was set via `set_flushed_body()`.
+    #[inline(always)]
+    crate fn flushes_reads(&self) -> bool {
+        self.flush_reads
+    }
+
+    // Sets the body of `self` to be the first `size` bytes of `file`, from its
+    // current position. The file is kept around so that it can be sent to the
+    // client without copying it through userspace.
+    #[inline]
+    crate fn set_file_body(&mut self, file: File, size: u64) {
+        let file = Arc::new(file);
+        self.set_raw_body(Body::Sized(FileBody(file.clone()), size));
+        self.file = Some(file);
+    }
+
+    // Returns the file the body of `self` reads from if the body was set via
+    // `set_file_body()` and hasn't been replaced since.
+    #[inline(always)]
+    crate fn body_file(&self) -> Option<Arc<File>> {
+        self.file.clone()
     }
 
     /// Sets the body of `self` to be `body`. This method should typically not
@@ -1075,6 +1342,9 @@ impl<'r> Response<'r> {
             Body::Sized(b, n) => Body::Sized(Box::new(b.take(n)), n),
             Body::Chunked(b, n) => Body::Chunked(Box::new(b), n),
         });
+
+        self.flush_reads = false;
+        self.file = None;
     }
 
     /// Replaces this response's status and body with that of `other`, if they
@@ -1119,6 +1389,16 @@ impl<'r> Response<'r> {
 
         if let Some(body) = other.body {
             self.body = Some(body);
+            self.flush_reads = other.flush_reads;
+            self.file = other.file;
+        }
+
+        if let Some(trailers) = other.trailers {
+            self.trailers = Some(trailers);
+        }
+
+        if let Some(upgrade) = other.upgrade {
+            self.upgrade = Some(upgrade);
         }
 
         for (name, values) in other.headers.into_iter_raw() {
@@ -1168,6 +1448,16 @@ impl<'r> Response<'r> {
 
         if self.body.is_none() {
             self.body = other.body;
+            self.flush_reads = other.flush_reads;
+            self.file = other.file;
+        }
+
+        if self.trailers.is_none() {
+            self.trailers = other.trailers;
+        }
+
+        if self.upgrade.is_none() {
+            self.upgrade = other.upgrade;
         }
 
         for (name, mut values) in other.headers.into_iter_raw() {
@@ -1,5 +1,7 @@
-use std::io::Read;
+use std::io::{self, Read, Write, Cursor};
 use std::fmt::{self, Debug};
+use std::mem;
+use std::sync::mpsc::{self, SyncSender, Receiver};
 
 use request::Request;
 use response::{Response, Responder, DEFAULT_CHUNK_SIZE};
@@ -11,7 +13,15 @@ use http::Status;
 /// 4KiB. This means that at most 4KiB are stored in memory while the response
 /// is being sent. This type should be used when sending responses that are
 /// arbitrarily large in size, such as when streaming from a local socket.
-pub struct Stream<T: Read>(T, u64);
+///
+/// # Flushing
+///
+/// By default, data is only sent to the client once a full chunk has been read
+/// or the reader is exhausted. For low-latency streams, such as progress logs,
+/// [`Stream::flushed()`] sends whatever each read returns right away, and
+/// [`Stream::channel()`] lets a producer decide when to send data by flushing
+/// a [`StreamSender`].
+pub struct Stream<T: Read>(T, u64, bool);
 
 impl<T: Read> Stream<T> {
     /// Create a new stream from the given `reader` and sets the chunk size for
@@ -30,7 +40,82 @@ impl<T: Read> Stream<T> {
     /// let response = Stream::chunked(io::stdin(), 10);
     /// ```
     pub fn chunked(reader: T, chunk_size: u64) -> Stream<T> {
-        Stream(reader, chunk_size)
+        Stream(reader, chunk_size, false)
+    }
+
+    /// Create a new stream from the given `reader` that sends whatever a
+    /// single read of `reader` returns to the client, and flushes it, right
+    /// away. Each read is at most 4KiB in size.
+    ///
+    /// # Example
+    ///
+    /// Stream a response from whatever is in `stdin`, sending each line as
+    /// soon as it's entered. Note: you probably shouldn't do this.
+    ///
+    /// ```rust
+    /// use std::io;
+    /// use rocket::response::Stream;
+    ///
+    /// # #[allow(unused_variables)]
+    /// let response = Stream::flushed(io::stdin());
+    /// ```
+    pub fn flushed(reader: T) -> Stream<T> {
+        Stream(reader, DEFAULT_CHUNK_SIZE, true)
+    }
+}
+
+impl Stream<StreamReceiver> {
+    /// Creates a stream whose data is produced by writing to the returned
+    /// [`StreamSender`], typically from another thread.
+    ///
+    /// Written data is buffered by the sender until it's flushed, or until a
+    /// full chunk of 4KiB has been written, and then sent to the client right
+    /// away. At most `bound` such chunks are queued for the client; once the
+    /// queue is full, writes block until the client catches up.
+    ///
+    /// The response ends when the sender is dropped. If the client disconnects,
+    /// or the response is otherwise abandoned, writes to the sender fail with
+    /// an error of kind `BrokenPipe`, signaling the producer to stop. Because a
+    /// disconnect is only detected when data is sent to the client, a write
+    /// or two may succeed before the first failure.
+    ///
+    /// # Example
+    ///
+    /// Stream the progress of a long-running job, stopping the job if the
+    /// client goes away:
+    ///
+    /// ```rust
+    /// # #![feature(proc_macro_hygiene, decl_macro)]
+    /// # #[macro_use] extern crate rocket;
+    /// use std::io::Write;
+    /// use std::thread;
+    /// use rocket::response::{Stream, StreamReceiver};
+    ///
+    /// # fn do_step(_: usize) {  }
+    /// #[get("/progress")]
+    /// fn progress() -> Stream<StreamReceiver> {
+    ///     let (mut sender, stream) = Stream::channel(1);
+    ///     thread::spawn(move || {
+    ///         for step in 0..100 {
+    ///             do_step(step);
+    ///             let sent = writeln!(sender, "step {} done", step)
+    ///                 .and_then(|_| sender.flush());
+    ///
+    ///             if sent.is_err() {
+    ///                 return;
+    ///             }
+    ///         }
+    ///     });
+    ///
+    ///     stream
+    /// }
+    /// # fn main() {  }
+    /// ```
+    pub fn channel(bound: usize) -> (StreamSender, Stream<StreamReceiver>) {
+        let (sender, receiver) = mpsc::sync_channel(bound);
+        let sender = StreamSender { sender, buffer: vec![] };
+        let receiver = StreamReceiver { receiver, pending: Cursor::new(vec![]) };
+        (sender, Stream::flushed(receiver))
     }
 }
 
@@ -56,7 +141,7 @@ impl<T: Read + Debug> Debug for Stream<T> {
 /// ```
 impl<T: Read> From<T> for Stream<T> {
     fn from(reader: T) -> Self {
-        Stream(reader, DEFAULT_CHUNK_SIZE)
+        Stream(reader, DEFAULT_CHUNK_SIZE, false)
     }
 }
 
@@ -70,6 +155,125 @@ impl<T: Read> From<T> for Stream<T> {
 /// to the console with an indication of what went wrong.
 impl<'r, T: Read + 'r> Responder<'r> for Stream<T> {
     fn respond_to(self, _: &Request) -> Result<Response<'r>, Status> {
-        Response::build().chunked_body(self.0, self.1).ok()
+        let mut response = Response::new();
+        if self.2 {
+            response.set_flushed_body(self.0, self.1);
+        } else {
+            response.set_chunked_body(self.0, self.1);
+        }
+
+        Ok(response)
+    }
+}
+
+/// The writing half of a [`Stream::channel()`].
+///
+/// Data written to a `StreamSender` is sent to the client when the sender is
+/// flushed, when a full chunk of 4KiB has been buffered, or when the sender is
+/// dropped. Dropping the sender ends the response.
+pub struct StreamSender {
+    sender: SyncSender<Vec<u8>>,
+    buffer: Vec<u8>,
+}
+
+impl StreamSender {
+    fn send_buffer(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let chunk = mem::replace(&mut self.buffer, vec![]);
+        self.sender.send(chunk).map_err(|_| {
+            io::Error::new(io::ErrorKind::BrokenPipe, "the client is no longer connected")
+        })
+    }
+}
+
+impl Write for StreamSender {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let room = DEFAULT_CHUNK_SIZE as usize - self.buffer.len();
+        let n = ::std::cmp::min(room, buf.len());
+        self.buffer.extend_from_slice(&buf[..n]);
+        if self.buffer.len() == DEFAULT_CHUNK_SIZE as usize {
+            self.send_buffer()?;
+        }
+
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.send_buffer()
+    }
+}
+
+impl Drop for StreamSender {
+    fn drop(&mut self) {
+        let _ = self.send_buffer();
+    }
+}
+
+impl Debug for StreamSender {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "StreamSender {{ buffered: {} }}", self.buffer.len())
+    }
+}
+
+/// The reading half of a [`Stream::channel()`], returning one flushed chunk of
+/// data at a time.
+pub struct StreamReceiver {
+    receiver: Receiver<Vec<u8>>,
+    pending: Cursor<Vec<u8>>,
+}
+
+impl Read for StreamReceiver {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pending.position() as usize == self.pending.get_ref().len() {
+            match self.receiver.recv() {
+                Ok(chunk) => self.pending = Cursor::new(chunk),
+                Err(_) => return Ok(0),
+            }
+        }
+
+        self.pending.read(buf)
+    }
+}
+
+impl Debug for StreamReceiver {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "StreamReceiver")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sender_buffers_until_flushed() {
+        let (mut sender, Stream(mut receiver, ..)) = Stream::channel(4);
+        sender.write_all(b"hello, ").unwrap();
+        sender.write_all(b"world").unwrap();
+        sender.flush().unwrap();
+        sender.flush().unwrap();
+        sender.write_all(&[b'a'; 5000]).unwrap();
+        sender.write_all(b"!").unwrap();
+        drop(sender);
+
+        let mut buf = [0; 8192];
+        let n = receiver.read(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"hello, world");
+        assert_eq!(receiver.read(&mut buf).unwrap(), 4096);
+        assert_eq!(receiver.read(&mut buf).unwrap(), 905);
+        assert_eq!(receiver.read(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn sender_fails_once_receiver_is_gone() {
+        let (mut sender, stream) = Stream::channel(4);
+        drop(stream);
+
+        sender.write_all(b"hello").unwrap();
+        let error = sender.flush().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::BrokenPipe);
     }
 }
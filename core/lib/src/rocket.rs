@@ -2,30 +2,72 @@ use std::collections::HashMap;
 use std::str::from_utf8;
 use std::cmp::min;
 use std::io::{self, Write};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
 use std::mem;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
 
 use yansi::Paint;
 use state::Container;
 
-#[cfg(feature = "tls")] use http::tls::TlsServer;
-
-use {logger, handler};
+use {logger, handler, server};
+#[cfg(feature = "http2")] use http2;
+use handler::BadRequestHandler;
 use ext::ReadExt;
 use config::{self, Config, LoggedValue};
-use request::{Request, FormItems};
-use data::Data;
-use response::{Body, Response};
+use request::{Request, FormItems, BadRequestInfo, BadRequestError};
+use data::{Data, NetStream};
+use response::{Body, Response, Trailers};
 use router::{Router, Route};
 use catcher::{self, Catcher};
 use outcome::Outcome;
-use error::{LaunchError, LaunchErrorKind};
+use error::{LaunchError, LaunchErrorKind, UrlForError};
 use fairing::{Fairing, Fairings};
+use shutdown::Shutdown;
+use saturation::Saturation;
+use listener::{Listener, Acceptor, Source};
+use upgrade::Upgrade;
+use sendfile::Socket;
 
-use http::{Method, Status, Header};
+use http::{Method, Protocol, Status, StatusClass, Header, HeaderMap};
 use http::hyper::{self, header};
 use http::uri::Origin;
 
+/// The value, in seconds, of the `Retry-After` header sent with a `503` to
+/// requests rejected because too many connections were pending.
+const RETRY_AFTER_SECS: u32 = 1;
+
+// Ends the chunked body being written to `stream` with `trailers`. Hyper only
+// ever writes an empty trailer section, so the last chunk is written here.
+fn end_with_trailers(
+    stream: hyper::Response<hyper::net::Streaming>,
+    trailers: &HeaderMap
+) -> io::Result<()> {
+    let (_, body, _, _) = stream.deconstruct();
+    let mut writer = match body {
+        hyper::h1::HttpWriter::ChunkedWriter(writer) => writer,
+        body => return body.end().map(|_| ()).map_err(io::Error::from),
+    };
+
+    let mut end = b"0\r\n".to_vec();
+    for header in trailers.iter() {
+        write!(end, "{}\r\n", header)?;
+    }
+
+    end.extend_from_slice(b"\r\n");
+    writer.write_all(&end)?;
+    writer.flush()
+}
+
+// Formats `methods` as the value of an `Allow` header.
+fn allow_header_value(methods: &[Method]) -> String {
+    let names: Vec<&str> = methods.iter().map(|m| m.as_str()).collect();
+    names.join(", ")
+}
+
 /// The main `Rocket` type: used to mount routes and catchers and launch the
 /// application.
 pub struct Rocket {
@@ -33,103 +75,246 @@ pub struct Rocket {
     router: Router,
     default_catchers: HashMap<u16, Catcher>,
     catchers: HashMap<u16, Catcher>,
+    bad_request_handler: BadRequestHandler,
     crate state: Container,
     fairings: Fairings,
 }
 
 #[doc(hidden)]
 impl hyper::Handler for Rocket {
+    fn handle<'h, 'k>(
+        &self,
+        hyp_req: hyper::Request<'h, 'k>,
+        res: hyper::FreshResponse<'h>,
+    ) {
+        let remote = hyp_req.remote_addr;
+        self.handle_hyper(hyp_req, res, Some(remote));
+    }
+}
+
+impl Rocket {
     // This function tries to hide all of the Hyper-ness from Rocket. It
     // essentially converts Hyper types into Rocket types, then calls the
     // `dispatch` function, which knows nothing about Hyper. Because responding
     // depends on the `HyperResponse` type, this function does the actual
-    // response processing.
-    fn handle<'h, 'k>(
+    // response processing. `remote` is `None` when the connection has no
+    // remote address, as is the case for Unix domain sockets. Returns the
+    // callback that takes over the connection if the response upgraded it.
+    crate fn handle_hyper<'h, 'k>(
         &self,
         hyp_req: hyper::Request<'h, 'k>,
-        res: hyper::FreshResponse<'h>,
-    ) {
+        mut res: hyper::FreshResponse<'h>,
+        remote: Option<SocketAddr>,
+    ) -> Option<Upgrade> {
         // Get all of the information from Hyper.
-        let (h_addr, h_method, h_headers, h_uri, _, h_body) = hyp_req.deconstruct();
+        let (_, h_method, h_headers, h_uri, h_version, h_body) = hyp_req.deconstruct();
+
+        // The client may be waiting for a `100 Continue` to send the body.
+        let expects_continue = h_version == hyper::HttpVersion::Http11
+            && h_headers.get() == Some(&header::Expect::Continue);
 
         // Convert the Hyper request into a Rocket request.
-        let req_res = Request::from_hyp(self, h_method, h_headers, h_uri, h_addr);
+        let req_res = Request::from_hyp(self, h_method, h_headers, h_uri, remote);
         let mut req = match req_res {
             Ok(req) => req,
-            Err(e) => {
-                error!("Bad incoming request: {}", e);
-                // TODO: We don't have a request to pass in, so we just
-                // fabricate one. This is weird. We should let the user know
-                // that we failed to parse a request (by invoking some special
-                // handler) instead of doing this.
-                let dummy = Request::new(self, Method::Get, Origin::dummy());
-                let r = self.handle_error(Status::BadRequest, &dummy);
-                return self.issue_response(r, res);
+            Err(info) => {
+                self.handle_bad_request(&info, res);
+                return None;
             }
         };
 
+        req.set_protocol(Protocol::from_hyp(h_version));
+
+        // Files may be sent to plaintext sockets directly.
+        let socket = h_body.get_ref().get_ref().downcast_ref::<NetStream>().and_then(Socket::of);
+
         // Retrieve the data from the hyper body.
-        let data = match Data::from_hyp(h_body) {
+        let read_timeout = self.config.read_timeout.map(|s| Duration::from_secs(s as u64));
+        let data = match Data::from_hyp(h_body, read_timeout, expects_continue) {
             Ok(data) => data,
             Err(reason) => {
                 error_!("Bad data in request: {}", reason);
                 let r = self.handle_error(Status::InternalServerError, &req);
-                return self.issue_response(r, res);
+                self.issue_response(r, res, socket);
+                return None;
             }
         };
 
-        // Dispatch the request to get a response, then write that response out.
-        let response = self.dispatch(&mut req, data);
-        self.issue_response(response, res)
+        // Dispatch the request to get a response. Only a `101 Switching
+        // Protocols` response or a successful response to a `CONNECT` request
+        // can take over the connection.
+        let closed = data.close_flag();
+        let mut response = self.dispatch(&mut req, data);
+        let takes_over = match response.status() {
+            Status::SwitchingProtocols => true,
+            status => req.method() == Method::Connect && status.class() == StatusClass::Success
+        };
+
+        if !takes_over {
+            let _ = response.take_upgrade();
+        }
+
+        // If the body was left unread, the client may still be sending it, so
+        // the connection can't be reused. Say so instead of just hanging up.
+        if closed.load(Ordering::Relaxed) && !takes_over {
+            res.headers_mut().set(header::Connection::close());
+        }
+
+        // Trailers are only sent to clients that announced they accept them.
+        if !req.accepts_trailers() {
+            let _ = response.take_trailers();
+        }
+
+        // Write the response out.
+        self.issue_response(response, res, socket)
     }
-}
 
-// This macro is a terrible hack to get around Hyper's Server<L> type. What we
-// want is to use almost exactly the same launch code when we're serving over
-// HTTPS as over HTTP. But Hyper forces two different types, so we can't use the
-// same code, at least not trivially. These macros get around that by passing in
-// the same code as a continuation in `$continue`. This wouldn't work as a
-// regular function taking in a closure because the types of the inputs to the
-// closure would be different depending on whether TLS was enabled or not.
-#[cfg(not(feature = "tls"))]
-macro_rules! serve {
-    ($rocket:expr, $addr:expr, |$server:ident, $proto:ident| $continue:expr) => ({
-        let ($proto, $server) = ("http://", hyper::Server::http($addr));
-        $continue
-    })
-}
+    /// Like `handle_hyper`, but for a request received over HTTP/2. The
+    /// response is written out via `res`.
+    #[cfg(feature = "http2")]
+    crate fn handle_h2(
+        &self,
+        head: http2::RequestHead,
+        remote: Option<SocketAddr>,
+        body: http2::Body,
+        res: http2::Responder,
+    ) {
+        let http2::RequestHead { method, uri, headers } = head;
+        let mut req = match Request::from_hyp(self, method, headers, uri, remote) {
+            Ok(req) => req,
+            Err(info) => {
+                error!("Bad incoming request: {}", info.error());
+                let response = (self.bad_request_handler)(&info);
+                return self.issue_h2_response(response, res);
+            }
+        };
 
-#[cfg(feature = "tls")]
-macro_rules! serve {
-    ($rocket:expr, $addr:expr, |$server:ident, $proto:ident| $continue:expr) => ({
-        if let Some(tls) = $rocket.config.tls.clone() {
-            let tls = TlsServer::new(tls.certs, tls.key);
-            let ($proto, $server) = ("https://", hyper::Server::https($addr, tls));
-            $continue
-        } else {
-            let ($proto, $server) = ("http://", hyper::Server::http($addr));
-            $continue
+        req.set_protocol(Protocol::Http2);
+        let mut response = self.dispatch(&mut req, Data::from_h2(body));
+        if !req.accepts_trailers() {
+            let _ = response.take_trailers();
         }
-    })
-}
 
-impl Rocket {
+        self.issue_h2_response(response, res)
+    }
+
     #[inline]
-    fn issue_response(&self, response: Response, hyp_res: hyper::FreshResponse) {
-        match self.write_response(response, hyp_res) {
+    #[cfg(feature = "http2")]
+    fn issue_h2_response(&self, response: Response, res: http2::Responder) {
+        match res.respond(response) {
             Ok(_) => info_!("{}", Paint::green("Response succeeded.")),
             Err(e) => error_!("Failed to write response: {:?}.", e),
         }
     }
+}
+
+impl Rocket {
+    /// Responds to a request that arrived while too many connections were
+    /// waiting for a worker with the `503` catcher, without dispatching it.
+    /// The connection should be closed afterwards.
+    crate fn handle_overloaded<'h, 'k>(
+        &self,
+        hyp_req: hyper::Request<'h, 'k>,
+        mut res: hyper::FreshResponse<'h>,
+        remote: Option<SocketAddr>,
+    ) {
+        let (_, h_method, h_headers, h_uri, _, _) = hyp_req.deconstruct();
+        let req = match Request::from_hyp(self, h_method, h_headers, h_uri, remote) {
+            Ok(req) => req,
+            Err(info) => return self.handle_bad_request(&info, res),
+        };
+
+        warn!("{}:", req);
+        warn_!("Too many pending connections. Rejecting request.");
+        res.headers_mut().set(header::Connection::close());
+        let mut response = self.handle_error(Status::ServiceUnavailable, &req);
+        if !response.headers().contains("Retry-After") {
+            response.set_raw_header("Retry-After", RETRY_AFTER_SECS.to_string());
+        }
+
+        if !req.accepts_trailers() {
+            let _ = response.take_trailers();
+        }
+
+        self.issue_response(response, res, None);
+    }
+
+    /// Responds to a request whose head couldn't be read in full, failing with
+    /// `error`, though its request line, `h_method` and `h_uri`, was. The
+    /// request is handled by the catcher for `error`'s status. If the request
+    /// line doesn't make for a valid request, the bad request handler responds
+    /// instead. The connection should be closed afterwards.
+    crate fn handle_incomplete_head(
+        &self,
+        h_method: hyper::Method,
+        h_uri: hyper::RequestUri,
+        error: BadRequestError,
+        mut res: hyper::FreshResponse,
+        remote: Option<SocketAddr>,
+    ) {
+        let h_headers = header::Headers::new();
+        let req = match Request::from_hyp(self, h_method, h_headers, h_uri, remote) {
+            Ok(req) => req,
+            Err(info) => return self.handle_bad_request(&info, res),
+        };
+
+        warn!("{}:", req);
+        error_!("Bad incoming request: {}.", error);
+        res.headers_mut().set(header::Connection::close());
+        let mut response = self.handle_error(error.status(), &req);
+        let _ = response.take_trailers();
+        self.issue_response(response, res, None);
+    }
+
+    /// Responds to a request that couldn't be parsed using the application's
+    /// bad request handler. The connection should be closed afterwards.
+    crate fn handle_bad_request(&self, info: &BadRequestInfo, mut res: hyper::FreshResponse) {
+        error!("Bad incoming request: {}", info.error());
+        res.headers_mut().set(header::Connection::close());
+        let mut response = (self.bad_request_handler)(info);
+        let _ = response.take_trailers();
+        self.issue_response(response, res, None);
+    }
+
+    // Writes `response` out, sending a file body to `socket` directly if there
+    // is one. If that succeeded, returns the callback that takes over the
+    // connection, if `response` has one.
+    #[inline]
+    fn issue_response(
+        &self,
+        mut response: Response,
+        hyp_res: hyper::FreshResponse,
+        socket: Option<Socket>
+    ) -> Option<Upgrade> {
+        let upgrade = response.take_upgrade();
+        match self.write_response(response, hyp_res, socket, upgrade.is_some()) {
+            Ok(_) => {
+                info_!("{}", Paint::green("Response succeeded."));
+                upgrade
+            }
+            Err(e) => {
+                error_!("Failed to write response: {:?}.", e);
+                None
+            }
+        }
+    }
 
     #[inline]
     fn write_response(
         &self,
         mut response: Response,
         mut hyp_res: hyper::FreshResponse,
+        socket: Option<Socket>,
+        upgrading: bool,
     ) -> io::Result<()> {
         *hyp_res.status_mut() = hyper::StatusCode::from_u16(response.status().code);
 
+        // The response to an upgrade names the protocol being switched to in
+        // its `Connection` header. The connection won't be closed.
+        if upgrading {
+            hyp_res.headers_mut().remove::<header::Connection>();
+        }
+
         for header in response.headers().iter() {
             // FIXME: Using hyper here requires two allocations.
             let name = header.name.into_string();
@@ -137,6 +322,21 @@ impl Rocket {
             hyp_res.headers_mut().append_raw(name, value);
         }
 
+        // An upgrading response ends with its head: whatever follows is spoken
+        // in the new protocol. Hyper insists on framing a body for responses
+        // other than `1xx`, as is the case for a tunnel established via
+        // `CONNECT`; clients ignore the framing of such responses.
+        if upgrading {
+            if response.status().class() != StatusClass::Informational {
+                hyp_res.headers_mut().set(header::ContentLength(0));
+            }
+
+            return hyp_res.start()?.end();
+        }
+
+        let flush_reads = response.flushes_reads();
+        let trailers = response.take_trailers();
+        let file = response.body_file();
         match response.body() {
             None => {
                 hyp_res.headers_mut().set(header::ContentLength(0));
@@ -145,6 +345,13 @@ impl Rocket {
             Some(Body::Sized(body, size)) => {
                 hyp_res.headers_mut().set(header::ContentLength(size));
                 let mut stream = hyp_res.start()?;
+                if let (Some(socket), Some(file)) = (socket, file) {
+                    stream.flush()?;
+                    if socket.send_file(&file, size)? {
+                        return stream.end();
+                    }
+                }
+
                 io::copy(body, &mut stream)?;
                 stream.end()
             }
@@ -155,17 +362,41 @@ impl Rocket {
                     return Err(io::Error::new(io::ErrorKind::Other, msg));
                 }
 
-                // The buffer stores the current chunk being written out.
+                // Trailers with known names are announced ahead of time.
+                if let Some(Trailers::Map(ref map)) = trailers {
+                    let mut names: Vec<_> = map.iter().map(|h| h.name().to_string()).collect();
+                    names.dedup();
+                    if !names.is_empty() && hyp_res.headers().get_raw("Trailer").is_none() {
+                        let names = names.join(", ").into_bytes();
+                        hyp_res.headers_mut().set_raw("Trailer", vec![names]);
+                    }
+                }
+
+                // The buffer stores the current chunk being written out. Bodies
+                // that ask for it have every read sent to the client right away.
                 let mut buffer = vec![0; chunk_size as usize];
                 let mut stream = hyp_res.start()?;
                 loop {
-                    match body.read_max(&mut buffer)? {
+                    let read = if flush_reads {
+                        body.read(&mut buffer)?
+                    } else {
+                        body.read_max(&mut buffer)?
+                    };
+
+                    match read {
                         0 => break,
                         n => stream.write_all(&buffer[..n])?,
                     }
+
+                    if flush_reads {
+                        stream.flush()?;
+                    }
                 }
 
-                stream.end()
+                match trailers {
+                    Some(trailers) => end_with_trailers(stream, &trailers.into_headers()),
+                    None => stream.end()
+                }
             }
         }
     }
@@ -204,17 +435,27 @@ impl Rocket {
     ) -> Response<'r> {
         info!("{}:", request);
 
-        // Do a bit of preprocessing before routing.
-        self.preprocess_request(request, &data);
+        // A request whose headers exceed the configured limits is rejected
+        // before fairings or handlers get to see it.
+        let too_large = self.exceeds_header_limits(request);
+        if !too_large {
+            // Do a bit of preprocessing before routing.
+            self.preprocess_request(request, &data);
 
-        // Run the request fairings.
-        self.fairings.handle_request(request, &data);
+            // Run the request fairings.
+            self.fairings.handle_request(request, &data);
+        }
 
         // Remember if the request is a `HEAD` request for later body stripping.
         let was_head_request = request.method() == Method::Head;
 
         // Route the request and run the user's handlers.
-        let mut response = self.route_and_process(request, data);
+        let mut response = if too_large {
+            error_!("Request headers exceed the configured limits.");
+            self.handle_error(Status::RequestHeaderFieldsTooLarge, request)
+        } else {
+            self.catch_route_and_process(request, data)
+        };
 
         // Add a default 'Server' header if it isn't already there.
         // TODO: If removing Hyper, write out `Date` header too.
@@ -225,6 +466,16 @@ impl Rocket {
         // Run the response fairings.
         self.fairings.handle_response(request, &mut response);
 
+        // HTTP/2 forbids headers that are specific to an HTTP/1 connection.
+        #[cfg(feature = "http2")]
+        {
+            if request.protocol() == Protocol::Http2 {
+                for name in http2::CONNECTION_HEADERS {
+                    response.remove_header(name);
+                }
+            }
+        }
+
         // Strip the body if this is a `HEAD` request.
         if was_head_request {
             response.strip_body();
@@ -233,12 +484,65 @@ impl Rocket {
         response
     }
 
+    /// Returns `true` if the number or total size of the headers in `request`
+    /// exceeds the `headers` or `header_bytes` limit, respectively.
+    fn exceeds_header_limits(&self, request: &Request) -> bool {
+        let limits = &self.config.limits;
+        let headers = request.headers();
+        if headers.len() as u64 > limits.headers {
+            return true;
+        }
+
+        let bytes: usize = headers.iter().map(|h| h.name().len() + h.value().len()).sum();
+        bytes as u64 > limits.header_bytes
+    }
+
+    /// Like `route_and_process`, but catches panics from request guards, data
+    /// guards, and handlers, responding with a `500` instead.
+    fn catch_route_and_process<'s, 'r>(
+        &'s self,
+        request: &'r Request<'s>,
+        data: Data
+    ) -> Response<'r> {
+        // A panic may leave request-local state half-updated. That's okay: the
+        // request is only used to report the panic and respond with a 500.
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            self.route_and_process(request, data)
+        }));
+
+        let payload = match result {
+            Ok(response) => return response,
+            Err(payload) => payload
+        };
+
+        let message = payload.downcast_ref::<&str>().map(|s| *s)
+            .or_else(|| payload.downcast_ref::<String>().map(|s| s.as_str()))
+            .unwrap_or("(non-string payload)");
+
+        match request.route() {
+            Some(route) => error_!("Route {} panicked: {}", route, Paint::default(message).bold()),
+            None => error_!("Request processing panicked: {}", Paint::default(message).bold()),
+        }
+
+        self.fairings.handle_panic(request, &*payload);
+        self.handle_error(Status::InternalServerError, request)
+    }
+
     /// Route the request and process the outcome to eventually get a response.
     fn route_and_process<'s, 'r>(
         &'s self,
         request: &'r Request<'s>,
         data: Data
     ) -> Response<'r> {
+        // The client was too slow sending the body to even begin routing.
+        if data.timed_out() {
+            error_!("Timed out reading request body.");
+            return self.handle_error(Status::RequestTimeout, request);
+        }
+
+        // A handler may time out reading the body. Such a handler's failure
+        // is the client's fault and is reported as such.
+        let timed_out = data.timeout_flag();
         match self.route(request, data) {
             Outcome::Success(mut response) => {
                 // A user's route responded! Set the cookies.
@@ -256,11 +560,22 @@ impl Rocket {
                     // Dispatch the request again with Method `GET`.
                     request._set_method(Method::Get);
                     self.route_and_process(request, data)
+                } else if timed_out.load(Ordering::Relaxed) {
+                    error_!("Timed out reading request body.");
+                    self.handle_error(Status::RequestTimeout, request)
+                } else if let Some(response) = self.handle_options(request) {
+                    response
+                } else if let Some(response) = self.handle_wrong_method(request) {
+                    response
                 } else {
                     // No match was found and it can't be autohandled. 404.
                     self.handle_error(Status::NotFound, request)
                 }
             }
+            Outcome::Failure(_) if timed_out.load(Ordering::Relaxed) => {
+                error_!("Timed out reading request body.");
+                self.handle_error(Status::RequestTimeout, request)
+            }
             Outcome::Failure(status) => self.handle_error(status, request)
         }
     }
@@ -305,6 +620,47 @@ impl Rocket {
         Outcome::Forward(data)
     }
 
+    // If `req` is an `OPTIONS` request for a path and host with routes, responds
+    // with an empty 200 response whose `Allow` header lists the methods of
+    // those routes. Otherwise returns `None`.
+    fn handle_options<'r>(&self, req: &'r Request) -> Option<Response<'r>> {
+        if req.method() != Method::Options {
+            return None;
+        }
+
+        let allowed = self.router.allowed_methods(req);
+        if allowed.is_empty() {
+            return None;
+        }
+
+        info_!("Autohandling {} request.", Paint::default("OPTIONS").bold());
+        Some(Response::build()
+            .status(Status::Ok)
+            .raw_header("Allow", allow_header_value(&allowed))
+            .finalize())
+    }
+
+    // If `auto_405` is enabled and routes for the request's path and host exist
+    // only under other methods, executes the 405 catcher for `req` and lists
+    // those methods in the response's `Allow` header. Otherwise returns `None`.
+    fn handle_wrong_method<'r>(&self, req: &'r Request) -> Option<Response<'r>> {
+        if !self.config.auto_405 {
+            return None;
+        }
+
+        let allowed = self.router.allowed_methods(req);
+        if allowed.is_empty() || allowed.contains(&req.method()) {
+            return None;
+        }
+
+        let mut response = self.handle_error(Status::MethodNotAllowed, req);
+        if response.status() == Status::MethodNotAllowed {
+            response.set_raw_header("Allow", allow_header_value(&allowed));
+        }
+
+        Some(response)
+    }
+
     // Finds the error catcher for the status `status` and executes it for the
     // given request `req`. If a user has registered a catcher for `status`, the
     // catcher is called. If the catcher fails to return a good response, the
@@ -402,11 +758,37 @@ impl Rocket {
         launch_info_!("secret key: {}", Paint::default(&config.secret_key).bold());
         launch_info_!("limits: {}", Paint::default(&config.limits).bold());
 
+        match config.max_pending {
+            Some(v) => launch_info_!("max pending: {}", Paint::default(v).bold()),
+            None => launch_info_!("max pending: {}", Paint::default("unbounded").bold()),
+        }
+
         match config.keep_alive {
             Some(v) => launch_info_!("keep-alive: {}", Paint::default(format!("{}s", v)).bold()),
             None => launch_info_!("keep-alive: {}", Paint::default("disabled").bold()),
         }
 
+        let timeouts = [
+            ("header read timeout", config.header_read_timeout),
+            ("read timeout", config.read_timeout),
+            ("write timeout", config.write_timeout),
+        ];
+
+        for &(name, timeout) in timeouts.iter() {
+            match timeout {
+                Some(v) => launch_info_!("{}: {}", name, Paint::default(format!("{}s", v)).bold()),
+                None => launch_info_!("{}: {}", name, Paint::default("disabled").bold()),
+            }
+        }
+
+        let http2_configured = config.http2 || config.h2c;
+        if http2_configured && cfg!(feature = "http2") {
+            launch_info_!("http2: {}", Paint::default("enabled (experimental)").bold());
+        } else if http2_configured {
+            error_!("http2: {}", Paint::default("disabled").bold());
+            error_!("http2 is configured, but the http2 feature is disabled");
+        }
+
         let tls_configured = config.tls.is_some();
         if tls_configured && cfg!(feature = "tls") {
             launch_info_!("tls: {}", Paint::default("enabled").bold());
@@ -427,12 +809,19 @@ impl Rocket {
                           Paint::default(LoggedValue(value)).bold());
         }
 
+        // Every application manages its own `Shutdown` handle and
+        // `Saturation` gauge.
+        let state = Container::new();
+        state.set(Shutdown::new());
+        state.set(Saturation::new(config.workers, config.max_pending));
+
         Rocket {
             config,
             router: Router::new(),
             default_catchers: catcher::defaults::get(),
             catchers: catcher::defaults::get(),
-            state: Container::new(),
+            bad_request_handler: catcher::defaults::bad_request,
+            state,
             fairings: Fairings::new(),
         }
     }
@@ -524,6 +913,61 @@ impl Rocket {
         self
     }
 
+    /// Mounts all of the routes in the supplied vector at the given `base`
+    /// path, restricted to requests whose `Host` matches the pattern `host`.
+    /// Routes that already have a host pattern keep it. See
+    /// [`Route::set_host()`] for the syntax of host patterns.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `host` is not a valid host pattern or if `base` is not a
+    /// valid mount point. See [`Rocket::mount()`].
+    ///
+    /// # Examples
+    ///
+    /// Serve the `api` routes only on `api.example.com`, and the `tenant`
+    /// routes on any subdomain of `tenants.example.com`:
+    ///
+    /// ```rust
+    /// # #![feature(proc_macro_hygiene, decl_macro)]
+    /// # #[macro_use] extern crate rocket;
+    /// #
+    /// #[get("/status")]
+    /// fn status() -> &'static str {
+    ///     "OK"
+    /// }
+    ///
+    /// #[get("/", host = "<tenant>.tenants.example.com")]
+    /// fn tenant(tenant: String) -> String {
+    ///     format!("Welcome, {}!", tenant)
+    /// }
+    ///
+    /// #[get("/about")]
+    /// fn about() -> &'static str {
+    ///     "A tenant of example.com."
+    /// }
+    ///
+    /// fn main() {
+    /// # if false { // We don't actually want to launch the server in an example.
+    ///     rocket::ignite()
+    ///         .mount_host("api.example.com", "/", routes![status])
+    ///         .mount_host("*.tenants.example.com", "/", routes![tenant, about])
+    /// #       .launch();
+    /// # }
+    /// }
+    /// ```
+    pub fn mount_host<R: Into<Vec<Route>>>(self, host: &str, base: &str, routes: R) -> Self {
+        let mut routes = routes.into();
+        for route in routes.iter_mut().filter(|route| route.host().is_none()) {
+            if let Err(e) = route.set_host(host.to_string()) {
+                error_!("Invalid host pattern '{}' used as mount host.", host);
+                panic!("Error: {}", e);
+            }
+        }
+
+        self.mount(base, routes)
+    }
+
     /// Registers all of the catchers in the supplied vector.
     ///
     /// # Examples
@@ -567,6 +1011,39 @@ impl Rocket {
         self
     }
 
+    /// Sets the handler to call when an incoming request cannot be parsed.
+    ///
+    /// Catchers can't handle such requests since there is no [`Request`] to
+    /// pass to them. Instead, `handler` is called with a [`BadRequestInfo`]
+    /// describing the raw request and the reason it couldn't be parsed. The
+    /// returned response is sent to the client, after which the connection is
+    /// closed. Fairings are not run. By default, Rocket responds with a
+    /// generic `400 Bad Request` page.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::Response;
+    /// use rocket::http::Status;
+    /// use rocket::request::BadRequestInfo;
+    ///
+    /// fn bad_request(info: &BadRequestInfo) -> Response<'static> {
+    ///     eprintln!("Bad request from {:?}: {}", info.remote(), info.error());
+    ///     Response::build().status(Status::BadRequest).finalize()
+    /// }
+    ///
+    /// fn main() {
+    /// # if false { // We don't actually want to launch the server in an example.
+    ///     let _ = rocket::ignite().on_bad_request(bad_request).launch();
+    /// # }
+    /// }
+    /// ```
+    #[inline]
+    pub fn on_bad_request(mut self, handler: BadRequestHandler) -> Self {
+        self.bad_request_handler = handler;
+        self
+    }
+
     /// Add `state` to the state managed by this instance of Rocket.
     ///
     /// This method can be called any number of times as long as each call
@@ -663,7 +1140,15 @@ impl Rocket {
 
     /// Starts the application server and begins listening for and dispatching
     /// requests to mounted routes and catchers. Unless there is an error, this
-    /// function does not return and blocks until program termination.
+    /// function blocks until the application is shut down via its
+    /// [`Shutdown`] handle, at which point it returns `Ok(())`.
+    ///
+    /// Rocket binds to the configured address and port unless
+    /// [`listen_fds`](Config::listen_fds) is enabled and a listening socket was
+    /// passed to the process via `LISTEN_FDS`, as done by systemd's socket
+    /// activation. In that case, Rocket serves on the inherited socket. Rocket
+    /// additionally binds to and serves on every configured
+    /// [endpoint](config::Endpoint), each with its own TLS configuration.
     ///
     /// # Error
     ///
@@ -676,57 +1161,122 @@ impl Rocket {
     ///
     /// ```rust
     /// # if false {
-    /// rocket::ignite().launch();
+    /// let _ = rocket::ignite().launch();
     /// # }
     /// ```
-    pub fn launch(mut self) -> LaunchError {
-        self = match self.prelaunch_check() {
-            Ok(rocket) => rocket,
-            Err(launch_error) => return launch_error
+    pub fn launch(self) -> Result<(), LaunchError> {
+        let bind = |address: &str, port: u16| Listener::bind(address, port)
+            .map_err(|e| LaunchError::new(LaunchErrorKind::Bind(e.into())));
+
+        let inherited = if self.config.listen_fds {
+            Listener::inherited()?
+        } else {
+            None
         };
 
-        self.fairings.pretty_print_counts();
+        let (listener, source) = match inherited {
+            Some(listener) => (listener, Source::Inherited),
+            None => (bind(&self.config.address, self.config.port)?, Source::Bound)
+        };
 
-        let full_addr = format!("{}:{}", self.config.address, self.config.port);
-        serve!(self, &full_addr, |server, proto| {
-            let mut server = match server {
-                Ok(server) => server,
-                Err(e) => return LaunchError::new(LaunchErrorKind::Bind(e)),
-            };
+        let http2 = self.config.http2;
+        let mut acceptors = vec![Acceptor::new(listener, source, self.config.tls.as_ref(), http2)?];
+        for endpoint in self.config.endpoints() {
+            let listener = bind(&endpoint.address, endpoint.port)?;
+            acceptors.push(Acceptor::new(listener, Source::Bound, endpoint.tls.as_ref(), http2)?);
+        }
 
-            // Determine the address and port we actually binded to.
-            match server.local_addr() {
-                Ok(server_addr) => self.config.port = server_addr.port(),
-                Err(e) => return LaunchError::from(e),
-            }
+        self.launch_with(acceptors)
+    }
 
-            // Set the keep-alive.
-            let timeout = self.config.keep_alive.map(|s| Duration::from_secs(s as u64));
-            server.keep_alive(timeout);
+    /// Starts the application server on the already-bound `listener` and
+    /// begins dispatching requests to mounted routes and catchers. `listener`
+    /// can be any type that converts into a [`Listener`]: a
+    /// [`std::net::TcpListener`] or, on Unix platforms, a
+    /// [`std::os::unix::net::UnixListener`]. The configured address, port, and
+    /// additional endpoints are ignored; the configured port is updated to the
+    /// one `listener` is bound to, if any. Like [`Rocket::launch()`], this
+    /// function blocks until the application is shut down.
+    ///
+    /// # Error
+    ///
+    /// If there is a problem starting the application, a [`LaunchError`] is
+    /// returned. See [`Rocket::launch()`] for details.
+    ///
+    /// # Example
+    ///
+    /// Binding to a port chosen by the operating system:
+    ///
+    /// ```rust
+    /// # if false {
+    /// use std::net::TcpListener;
+    ///
+    /// let listener = TcpListener::bind("127.0.0.1:0").expect("bound");
+    /// let _ = rocket::ignite().launch_on(listener);
+    /// # }
+    /// ```
+    ///
+    /// Serving on a Unix domain socket:
+    ///
+    /// ```rust
+    /// # if false {
+    /// # #[cfg(unix)] {
+    /// use std::os::unix::net::UnixListener;
+    ///
+    /// let listener = UnixListener::bind("/tmp/rocket.sock").expect("bound");
+    /// let _ = rocket::ignite().launch_on(listener);
+    /// # }
+    /// # }
+    /// ```
+    pub fn launch_on<L: Into<Listener>>(self, listener: L) -> Result<(), LaunchError> {
+        let tls = self.config.tls.as_ref();
+        let acceptor = Acceptor::new(listener.into(), Source::Supplied, tls, self.config.http2)?;
+        self.launch_with(vec![acceptor])
+    }
 
-            // Freeze managed state for synchronization-free accesses later.
-            self.state.freeze();
+    fn launch_with(mut self, acceptors: Vec<Acceptor>) -> Result<(), LaunchError> {
+        self = self.prelaunch_check()?;
 
-            // Run the launch fairings.
-            self.fairings.handle_launch(&self);
+        self.fairings.pretty_print_counts();
 
-            let full_addr = format!("{}:{}", self.config.address, self.config.port);
-            launch_info!("{}{} {}{}",
-                         Paint::masked("🚀 "),
-                         Paint::default("Rocket has launched from").bold(),
-                         Paint::default(proto).bold().underline(),
-                         Paint::default(&full_addr).bold().underline());
+        // Determine the port we're actually bound to, if any.
+        if let Some(port) = acceptors[0].listener().port()? {
+            self.config.port = port;
+        }
 
-            // Restore the log level back to what it originally was.
-            logger::pop_max_level();
+        // Freeze managed state for synchronization-free accesses later.
+        self.state.freeze();
 
-            let threads = self.config.workers as usize;
-            if let Err(e) = server.handle_threads(self, threads) {
-                return LaunchError::from(e);
+        // Run the launch fairings.
+        self.fairings.handle_launch(&self);
+
+        for (i, acceptor) in acceptors.iter().enumerate() {
+            let full_addr = acceptor.listener().address()?;
+            let proto = Paint::default(acceptor.protocol()).bold().underline();
+            let addr = Paint::default(&full_addr).bold().underline();
+            if i == 0 {
+                launch_info!("{}{} {}{}", Paint::masked("🚀 "),
+                             Paint::default("Rocket has launched from").bold(),
+                             proto, addr);
+            } else {
+                launch_info_!("also serving on {}{}", proto, addr);
             }
 
-            unreachable!("the call to `handle_threads` should block on success")
-        })
+            if acceptor.source() == Source::Inherited {
+                launch_info_!("socket: {}", Paint::default("inherited via LISTEN_FDS").bold());
+            }
+        }
+
+        // Restore the log level back to what it originally was.
+        logger::pop_max_level();
+
+        // Serve until shutdown, then run the shutdown fairings.
+        let rocket = Arc::new(self);
+        server::serve(rocket.clone(), acceptors)?;
+        rocket.fairings.handle_shutdown(&rocket);
+        info_!("{}", Paint::green("Shutdown complete."));
+
+        Ok(())
     }
 
     /// Returns an iterator over all of the routes mounted on this instance of
@@ -766,6 +1316,119 @@ impl Rocket {
         self.router.routes()
     }
 
+    /// Returns the URI of the mounted route named `name`, including its mount
+    /// point, with its dynamic parameters set to the values in `params`. This
+    /// is the runtime counterpart of the [`uri!`] macro.
+    ///
+    /// Routes generated by code generation are named after their function.
+    /// Other routes are named by setting [`Route::name`].
+    ///
+    /// Each parameter in `params` is a `(name, value)` pair. The values are
+    /// percent-encoded. Every dynamic path parameter of the route must be given
+    /// a value; the value of a `<param..>` path parameter may contain `/` to
+    /// produce several segments. Dynamic query parameters without a value are
+    /// left out. Parameters the route doesn't have are appended to the query
+    /// if the route's query ends in a `<param..>` parameter.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no route, or more than one route, is named `name`,
+    /// if the route has a host pattern, if a dynamic path parameter is not
+    /// given a value or is given one that is empty or doesn't satisfy its
+    /// constraint, or if a parameter that the route doesn't have can't be
+    /// added to the query. A route mounted at several mount points is
+    /// ambiguous; use [`Rocket::mounted_url_for()`](::Rocket::mounted_url_for())
+    /// to pick a mount point.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #![feature(proc_macro_hygiene, decl_macro)]
+    /// # #[macro_use] extern crate rocket;
+    /// #[get("/<id>/profile?<tab>")]
+    /// fn user_profile(id: usize, tab: Option<String>) { /* .. */ }
+    ///
+    /// fn main() {
+    ///     let rocket = rocket::ignite().mount("/users", routes![user_profile]);
+    ///
+    ///     let uri = rocket.url_for("user_profile", vec![("id", "10"), ("tab", "recent posts")]);
+    ///     assert_eq!(uri.unwrap().to_string(), "/users/10/profile?tab=recent%20posts");
+    ///
+    ///     let uri = rocket.url_for("user_profile", vec![("id", 10)]);
+    ///     assert_eq!(uri.unwrap().to_string(), "/users/10/profile");
+    ///
+    ///     assert!(rocket.url_for("user_profile", vec![("tab", "posts")]).is_err());
+    /// }
+    /// ```
+    ///
+    /// [`uri!`]: ../rocket_codegen/macro.uri.html
+    pub fn url_for<I, K, V>(&self, name: &str, params: I) -> Result<Origin<'static>, UrlForError>
+        where I: IntoIterator<Item = (K, V)>, K: AsRef<str>, V: fmt::Display
+    {
+        let routes = self.router.routes().filter(|route| route.name == Some(name));
+        Rocket::uri_for_only(routes, name, params)
+    }
+
+    /// Like [`Rocket::url_for()`](::Rocket::url_for()), but only considers the
+    /// routes mounted at `base`, exactly as it was passed to
+    /// [`Rocket::mount()`](::Rocket::mount()). This picks out one of the routes
+    /// that share a name because they're mounted at several mount points.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #![feature(proc_macro_hygiene, decl_macro)]
+    /// # #[macro_use] extern crate rocket;
+    /// #[get("/<id>")]
+    /// fn post(id: usize) { /* .. */ }
+    ///
+    /// fn main() {
+    ///     let rocket = rocket::ignite()
+    ///         .mount("/posts", routes![post])
+    ///         .mount("/drafts", routes![post]);
+    ///
+    ///     assert!(rocket.url_for("post", vec![("id", 3)]).is_err());
+    ///
+    ///     let uri = rocket.mounted_url_for("/drafts", "post", vec![("id", 3)]);
+    ///     assert_eq!(uri.unwrap().to_string(), "/drafts/3");
+    /// }
+    /// ```
+    pub fn mounted_url_for<I, K, V>(
+        &self,
+        base: &str,
+        name: &str,
+        params: I
+    ) -> Result<Origin<'static>, UrlForError>
+        where I: IntoIterator<Item = (K, V)>, K: AsRef<str>, V: fmt::Display
+    {
+        let routes = self.router.routes()
+            .filter(|route| route.name == Some(name) && route.base() == base);
+
+        Rocket::uri_for_only(routes, name, params)
+    }
+
+    // Returns the URI of the only route in `routes`, named `name`, with its
+    // dynamic parameters set to `params`.
+    fn uri_for_only<'a, R, I, K, V>(
+        mut routes: R,
+        name: &str,
+        params: I
+    ) -> Result<Origin<'static>, UrlForError>
+        where R: Iterator<Item = &'a Route>,
+              I: IntoIterator<Item = (K, V)>, K: AsRef<str>, V: fmt::Display
+    {
+        let route = routes.next().ok_or_else(|| UrlForError::UnknownRoute(name.into()))?;
+        if routes.next().is_some() {
+            return Err(UrlForError::AmbiguousRoute(name.into()));
+        }
+
+        let params = params.into_iter()
+            .map(|(name, value)| (name.as_ref().to_string(), value.to_string()))
+            .collect();
+
+        route.uri_for(params)
+    }
+
     /// Returns `Some` of the managed state value for the type `T` if it is
     /// being managed by `self`. Otherwise, returns `None`.
     ///
@@ -786,6 +1449,44 @@ impl Rocket {
         self.state.try_get()
     }
 
+    /// Returns a [`Shutdown`] handle for this application. Calling
+    /// [`Shutdown::notify()`] on the handle gracefully shuts down the
+    /// application once it has launched.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # if false {
+    /// use std::thread;
+    ///
+    /// let rocket = rocket::ignite();
+    /// let handle = rocket.shutdown();
+    /// thread::spawn(move || handle.notify());
+    ///
+    /// // Returns as soon as the application has shut down.
+    /// rocket.launch().expect("clean shutdown");
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn shutdown(&self) -> Shutdown {
+        self.state.get::<Shutdown>().clone()
+    }
+
+    /// Returns the [`Saturation`] gauge for this application, which reports
+    /// how busy the application's workers are once it has launched.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let rocket = rocket::ignite();
+    /// let saturation = rocket.saturation();
+    /// assert_eq!(saturation.busy(), 0);
+    /// ```
+    #[inline(always)]
+    pub fn saturation(&self) -> Saturation {
+        self.state.get::<Saturation>().clone()
+    }
+
     /// Returns the active configuration.
     ///
     /// # Example
@@ -1,7 +1,8 @@
 use super::Route;
 
-use http::MediaType;
-use http::route::Kind;
+use http::{MediaType, RawStr};
+use http::route::{RouteSegment, Kind};
+use http::uri::Path;
 use request::Request;
 
 impl Route {
@@ -15,14 +16,22 @@ impl Route {
     ///   * If route specifies a format, it only gets requests for that format.
     ///   * If route doesn't specify a format, it gets requests for any format.
     ///
+    /// A constrained dynamic path segment doesn't collide with static segments
+    /// that don't satisfy its constraint, nor with segments whose constraint
+    /// it excludes, such as `<a: int>` and `<b: alpha>`. A route whose dynamic
+    /// segments are all constrained doesn't collide with a route of the same
+    /// rank that isn't constrained, as it is always tried first.
+    ///
     /// Because query parsing is lenient, and dynamic query parameters can be
-    /// missing, queries do not impact whether two routes collide.
+    /// missing, queries do not impact whether two routes collide. Hosts work
+    /// like formats: a route without a host pattern gets requests for any host.
     #[doc(hidden)]
     pub fn collides_with(&self, other: &Route) -> bool {
         self.method == other.method
-            && self.rank == other.rank
+            && self.order() == other.order()
             && paths_collide(self, other)
             && formats_collide(self, other)
+            && hosts_collide(self, other)
     }
 
     /// Determines if this route matches against the given request. This means
@@ -34,15 +43,33 @@ impl Route {
     ///     - If route doesn't specify format, it gets requests for any format.
     ///   * All static components in the route's path match the corresponding
     ///     components in the same position in the incoming request.
+    ///   * All constrained dynamic components in the route's path are
+    ///     satisfied by the corresponding, percent-decoded components.
     ///   * All static components in the route's query string are also in the
     ///     request query string, though in any position.
     ///     - If no query in route, requests with/without queries match.
+    ///   * The route's host pattern (if any) matches the request's `Host`.
+    ///     - Static labels are compared case-insensitively.
+    ///     - The host must have as many labels as the pattern.
+    ///     - Requests without a `Host` only match routes without a pattern.
     #[doc(hidden)]
     pub fn matches(&self, req: &Request) -> bool {
         self.method == req.method()
             && paths_match(self, req)
             && queries_match(self, req)
             && formats_match(self, req)
+            && hosts_match(self, req)
+    }
+
+    /// Determines if this route serves the resource identified by the given
+    /// request, regardless of the request's method. Only the route's path and
+    /// host pattern are considered: a route whose query or format doesn't
+    /// match the request still serves the same resource.
+    ///
+    /// This is used to determine the methods allowed for a resource when no
+    /// route matches a request.
+    crate fn matches_resource(&self, req: &Request) -> bool {
+        paths_match(self, req) && hosts_match(self, req)
     }
 }
 
@@ -54,16 +81,35 @@ fn paths_collide(route: &Route, other: &Route) -> bool {
             return true;
         }
 
-        if seg_a.kind == Kind::Static && seg_b.kind == Kind::Static {
-            if seg_a.string != seg_b.string {
-                return false;
-            }
+        if !segments_collide(seg_a, seg_b) {
+            return false;
         }
     }
 
     a_segments.len() == b_segments.len()
 }
 
+fn segments_collide(seg_a: &RouteSegment<Path>, seg_b: &RouteSegment<Path>) -> bool {
+    match (seg_a.kind, seg_b.kind) {
+        (Kind::Static, Kind::Static) => seg_a.string == seg_b.string,
+        (Kind::Static, _) => satisfies_constraint(seg_b, RawStr::from_str(&seg_a.string)),
+        (_, Kind::Static) => satisfies_constraint(seg_a, RawStr::from_str(&seg_b.string)),
+        _ => match (seg_a.constraint.as_ref(), seg_b.constraint.as_ref()) {
+            (Some(a), Some(b)) => !a.is_disjoint_from(b),
+            _ => true
+        }
+    }
+}
+
+fn satisfies_constraint(segment: &RouteSegment<Path>, value: &RawStr) -> bool {
+    match segment.constraint {
+        Some(ref constraint) => value.percent_decode()
+            .map(|value| constraint.is_satisfied_by(&value))
+            .unwrap_or(false),
+        None => true
+    }
+}
+
 fn paths_match(route: &Route, request: &Request) -> bool {
     let route_segments = &route.metadata.path_segments;
     if route_segments.len() > request.state.path_segments.len() {
@@ -75,6 +121,7 @@ fn paths_match(route: &Route, request: &Request) -> bool {
         match route_seg.kind {
             Kind::Multi => return true,
             Kind::Static if &*route_seg.string != req_seg.as_str() => return false,
+            Kind::Single if !satisfies_constraint(route_seg, req_seg) => return false,
             _ => continue,
         }
     }
@@ -109,6 +156,44 @@ fn queries_match(route: &Route, request: &Request) -> bool {
     true
 }
 
+fn hosts_collide(route: &Route, other: &Route) -> bool {
+    let a_segments = match route.metadata.host_segments {
+        Some(ref segments) => segments,
+        None => return true
+    };
+
+    let b_segments = match other.metadata.host_segments {
+        Some(ref segments) => segments,
+        None => return true
+    };
+
+    if a_segments.len() != b_segments.len() {
+        return false;
+    }
+
+    a_segments.iter().zip(b_segments.iter()).all(|(seg_a, seg_b)| {
+        seg_a.kind != Kind::Static || seg_b.kind != Kind::Static || seg_a.string == seg_b.string
+    })
+}
+
+fn hosts_match(route: &Route, request: &Request) -> bool {
+    let route_segments = match route.metadata.host_segments {
+        Some(ref segments) => segments,
+        None => return true
+    };
+
+    let mut request_labels = request.raw_host_labels();
+    for route_seg in route_segments.iter() {
+        match request_labels.next() {
+            Some(_) if route_seg.kind != Kind::Static => continue,
+            Some(label) if label.as_str().eq_ignore_ascii_case(&route_seg.string) => continue,
+            _ => return false
+        }
+    }
+
+    request_labels.next().is_none()
+}
+
 fn formats_collide(route: &Route, other: &Route) -> bool {
     // When matching against the `Accept` header, the client can always provide
     // a media type that will cause a collision through non-specificity.
@@ -157,7 +242,7 @@ mod tests {
     use config::Config;
     use request::Request;
     use router::{dummy_handler, route::Route};
-    use http::{Method, MediaType, ContentType, Accept};
+    use http::{Method, MediaType, ContentType, Accept, Header};
     use http::uri::Origin;
     use http::Method::*;
 
@@ -258,6 +343,32 @@ mod tests {
         assert!(!unranked_collide("/", "/a"));
     }
 
+    #[test]
+    fn constrained_collisions() {
+        assert!(unranked_collide("/<a: int>", "/<b>"));
+        assert!(unranked_collide("/<a: int>", "/<b: uint>"));
+        assert!(unranked_collide("/<a: int>", "/<b: alnum>"));
+        assert!(unranked_collide("/<a: alpha>", "/<b: alnum>"));
+        assert!(unranked_collide("/<a: int>", "/-12"));
+        assert!(unranked_collide("/<a: uint>", "/012"));
+        assert!(unranked_collide("/<a: alpha>", "/hello"));
+        assert!(unranked_collide("/<a: regex([a-z]+)>", "/<b: int>"));
+        assert!(unranked_collide("/<a: regex([a-z]+)>", "/hi"));
+        assert!(unranked_collide("/a/<b: int>", "/<a>/<b: uint>"));
+    }
+
+    #[test]
+    fn constrained_non_collisions() {
+        assert!(!unranked_collide("/<a: int>", "/<b: alpha>"));
+        assert!(!unranked_collide("/<a: uint>", "/<b: alpha>"));
+        assert!(!unranked_collide("/<a: int>", "/hello"));
+        assert!(!unranked_collide("/<a: uint>", "/-12"));
+        assert!(!unranked_collide("/<a: alpha>", "/a1"));
+        assert!(!unranked_collide("/<a: alnum>", "/a-1"));
+        assert!(!unranked_collide("/<a: regex([a-z]+)>", "/HI"));
+        assert!(!unranked_collide("/a/<b: int>", "/<a>/<b: alpha>"));
+    }
+
     #[test]
     fn query_non_collisions() {
         assert!(!unranked_collide("/a?<b>", "/b"));
@@ -474,6 +585,28 @@ mod tests {
         route.matches(&req)
     }
 
+    #[test]
+    fn test_req_route_constrained_matching() {
+        assert!(req_route_path_match("/user/12", "/user/<id: int>"));
+        assert!(req_route_path_match("/user/-12", "/user/<id: int>"));
+        assert!(req_route_path_match("/user/+12", "/user/<id: int>"));
+        assert!(req_route_path_match("/user/12", "/user/<id: uint>"));
+        assert!(req_route_path_match("/user/bob", "/user/<id: alpha>"));
+        assert!(req_route_path_match("/user/bob42", "/user/<id: alnum>"));
+        assert!(req_route_path_match("/user/bob-42", "/user/<id: regex([a-z]+-[0-9]+)>"));
+        assert!(req_route_path_match("/user/b%C3%B6b", "/user/<id: regex(b.b)>"));
+
+        assert!(!req_route_path_match("/user/bob", "/user/<id: int>"));
+        assert!(!req_route_path_match("/user/1-2", "/user/<id: int>"));
+        assert!(!req_route_path_match("/user/+-2", "/user/<id: int>"));
+        assert!(!req_route_path_match("/user/-", "/user/<id: int>"));
+        assert!(!req_route_path_match("/user/-12", "/user/<id: uint>"));
+        assert!(!req_route_path_match("/user/bob42", "/user/<id: alpha>"));
+        assert!(!req_route_path_match("/user/b%20b", "/user/<id: alnum>"));
+        assert!(!req_route_path_match("/user/xbob-42", "/user/<id: regex(bob-[0-9]+)>"));
+        assert!(!req_route_path_match("/user/bob-42x", "/user/<id: regex(bob-[0-9]+)>"));
+    }
+
     #[test]
     fn test_req_route_query_collisions() {
         assert!(req_route_path_match("/a/b?a=b", "/a/b?<c>"));
@@ -509,4 +642,68 @@ mod tests {
         assert!(!req_route_path_match("/a/b", "/a/b?foo&<rest..>"));
         assert!(!req_route_path_match("/a/b", "/a/b?<a>&b&<rest..>"));
     }
+
+    fn host_route(host: Option<&'static str>) -> Route {
+        let mut route = Route::ranked(0, Get, "/", dummy_handler);
+        if let Some(host) = host {
+            route.set_host(host).expect("valid host pattern");
+        }
+
+        route
+    }
+
+    fn host_routes_collide<H1, H2>(a: H1, b: H2) -> bool
+        where H1: Into<Option<&'static str>>, H2: Into<Option<&'static str>>
+    {
+        host_route(a.into()).collides_with(&host_route(b.into()))
+    }
+
+    #[test]
+    fn test_host_collisions() {
+        assert!(host_routes_collide(None, None));
+        assert!(host_routes_collide(None, "api.example.com"));
+        assert!(host_routes_collide("api.example.com", None));
+        assert!(host_routes_collide("api.example.com", "api.example.com"));
+        assert!(host_routes_collide("api.example.com", "API.Example.com"));
+        assert!(host_routes_collide("*.example.com", "api.example.com"));
+        assert!(host_routes_collide("<a>.example.com", "*.example.com"));
+        assert!(host_routes_collide("api.<b>.com", "<a>.example.com"));
+
+        assert!(!host_routes_collide("api.example.com", "www.example.com"));
+        assert!(!host_routes_collide("api.example.com", "example.com"));
+        assert!(!host_routes_collide("*.example.com", "example.com"));
+        assert!(!host_routes_collide("*.example.com", "*.example.org"));
+        assert!(!host_routes_collide("*.example.com", "a.b.example.com"));
+    }
+
+    fn req_route_host_match<H>(host: H, pattern: &'static str) -> bool
+        where H: Into<Option<&'static str>>
+    {
+        let rocket = Rocket::custom(Config::development());
+        let mut req = Request::new(&rocket, Get, Origin::parse("/").expect("valid URI"));
+        if let Some(host) = host.into() {
+            req.add_header(Header::new("Host", host));
+        }
+
+        host_route(Some(pattern)).matches(&req)
+    }
+
+    #[test]
+    fn test_req_route_host_matching() {
+        assert!(req_route_host_match("api.example.com", "api.example.com"));
+        assert!(req_route_host_match("API.EXAMPLE.COM", "api.example.com"));
+        assert!(req_route_host_match("api.example.com", "Api.Example.Com"));
+        assert!(req_route_host_match("api.example.com.", "api.example.com"));
+        assert!(req_route_host_match("api.example.com:8000", "api.example.com"));
+        assert!(req_route_host_match("acme.tenants.example.com", "*.tenants.example.com"));
+        assert!(req_route_host_match("acme.tenants.example.com", "<t>.tenants.example.com"));
+        assert!(req_route_host_match("acme.tenants.example.com", "<t>.<u>.example.com"));
+
+        assert!(!req_route_host_match(None, "api.example.com"));
+        assert!(!req_route_host_match("www.example.com", "api.example.com"));
+        assert!(!req_route_host_match("example.com", "api.example.com"));
+        assert!(!req_route_host_match("tenants.example.com", "*.tenants.example.com"));
+        assert!(!req_route_host_match("a.b.tenants.example.com", "*.tenants.example.com"));
+        assert!(!req_route_host_match("acme.tenants.example.org", "*.tenants.example.com"));
+    }
 }
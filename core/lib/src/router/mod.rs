@@ -1,5 +1,6 @@
 mod collider;
 mod route;
+mod trie;
 
 use std::collections::hash_map::HashMap;
 
@@ -7,6 +8,9 @@ pub use self::route::Route;
 
 use request::Request;
 use http::Method;
+use http::private::SmallVec;
+
+use self::trie::Trie;
 
 // type Selector = (Method, usize);
 type Selector = Method;
@@ -19,59 +23,128 @@ crate fn dummy_handler<'r>(r: &'r ::Request, _: ::Data) -> ::handler::Outcome<'r
 #[derive(Default)]
 pub struct Router {
     routes: HashMap<Selector, Vec<Route>>,
+    tries: HashMap<Selector, Trie>,
 }
 
 impl Router {
     pub fn new() -> Router {
-        Router { routes: HashMap::new() }
+        Router { routes: HashMap::new(), tries: HashMap::new() }
     }
 
     pub fn add(&mut self, route: Route) {
         let selector = route.method;
         let entries = self.routes.entry(selector).or_insert_with(|| vec![]);
-        let i = entries.binary_search_by_key(&route.rank, |r| r.rank)
+        let i = entries.binary_search_by_key(&route.order(), |r| r.order())
             .unwrap_or_else(|i| i);
 
+        // Indices into `entries` have shifted. The trie is rebuilt by `index`.
+        self.tries.remove(&selector);
         entries.insert(i, route);
     }
 
+    // Builds the tries for the methods whose routes changed since the tries
+    // were last built. The prelaunch check calls this before any routing.
+    crate fn index(&mut self) {
+        let tries = &mut self.tries;
+        for (selector, routes) in &self.routes {
+            tries.entry(*selector).or_insert_with(|| Trie::new(routes));
+        }
+    }
+
+    // Returns, in order, the indices of the `selector` routes that may match
+    // the raw path `segments`. Routes that haven't been indexed yet are all
+    // candidates: that's slower than using the trie, but never wrong.
+    fn candidates(&self, selector: Selector, segments: &[&str]) -> Vec<usize> {
+        match self.tries.get(&selector) {
+            Some(trie) => trie.candidates(segments),
+            None => (0..self.routes.get(&selector).map_or(0, |r| r.len())).collect()
+        }
+    }
+
     pub fn route<'b>(&'b self, req: &Request) -> Vec<&'b Route> {
-        // Note that routes are presorted by rank on each `add`.
-        let matches = self.routes.get(&req.method()).map_or(vec![], |routes| {
-            routes.iter()
-                .filter(|r| r.matches(req))
-                .collect()
-        });
+        // Note that routes are presorted by `order` on each `add`, and that the
+        // trie returns the candidates for the path in that same order.
+        let selector = req.method();
+        let matches = match self.routes.get(&selector) {
+            Some(routes) => {
+                let segments: SmallVec<[&str; 12]> = req.raw_path_segments()
+                    .map(|segment| segment.as_str())
+                    .collect();
+
+                self.candidates(selector, &segments).into_iter()
+                    .map(|i| &routes[i])
+                    .filter(|r| r.matches(req))
+                    .collect()
+            }
+            None => vec![]
+        };
 
         trace_!("Routing the request: {}", req);
         trace_!("All matches: {:?}", matches);
         matches
     }
 
-    crate fn collisions(mut self) -> Result<Router, Vec<(Route, Route)>> {
-        let mut collisions = vec![];
-        for routes in self.routes.values_mut() {
-            for i in 0..routes.len() {
-                let (left, right) = routes.split_at_mut(i);
-                for a_route in left.iter_mut() {
-                    for b_route in right.iter_mut() {
-                        if a_route.collides_with(b_route) {
-                            let dummy_a = Route::new(Method::Get, "/", dummy_handler);
-                            let a = ::std::mem::replace(a_route, dummy_a);
-                            let dummy_b = Route::new(Method::Get, "/", dummy_handler);
-                            let b = ::std::mem::replace(b_route, dummy_b);
-                            collisions.push((a, b));
-                        }
+    /// Returns the methods of every route serving the resource identified by
+    /// `req`, regardless of the request's method, sorted by name. Since `HEAD`
+    /// and `OPTIONS` requests are autohandled, `HEAD` is included whenever
+    /// `GET` is, and `OPTIONS` is included whenever any method is.
+    pub fn allowed_methods(&self, req: &Request) -> Vec<Method> {
+        let segments: SmallVec<[&str; 12]> = req.raw_path_segments()
+            .map(|segment| segment.as_str())
+            .collect();
+
+        let mut methods = vec![];
+        for (selector, routes) in &self.routes {
+            let allowed = self.candidates(*selector, &segments).into_iter()
+                .any(|i| routes[i].matches_resource(req));
+
+            if allowed {
+                methods.push(*selector);
+            }
+        }
+
+        if methods.contains(&Method::Get) && !methods.contains(&Method::Head) {
+            methods.push(Method::Head);
+        }
+
+        if !methods.is_empty() && !methods.contains(&Method::Options) {
+            methods.push(Method::Options);
+        }
+
+        methods.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        methods
+    }
+
+    // Returns the selector and indices of every pair of colliding routes.
+    fn colliding_pairs(&self) -> Vec<(Selector, usize, usize)> {
+        let mut pairs = vec![];
+        for (selector, routes) in &self.routes {
+            let trie = &self.tries[selector];
+            for (j, route) in routes.iter().enumerate() {
+                for i in trie.colliding(route).into_iter().take_while(|&i| i < j) {
+                    if routes[i].collides_with(route) {
+                        pairs.push((*selector, i, j));
                     }
                 }
             }
         }
 
-        if collisions.is_empty() {
-            Ok(self)
-        } else {
-            Err(collisions)
+        pairs
+    }
+
+    crate fn collisions(mut self) -> Result<Router, Vec<(Route, Route)>> {
+        self.index();
+        let pairs = self.colliding_pairs();
+        if pairs.is_empty() {
+            return Ok(self);
         }
+
+        Err(pairs.into_iter()
+            .map(|(selector, i, j)| {
+                let routes = &self.routes[&selector];
+                (routes[i].clone(), routes[j].clone())
+            })
+            .collect())
     }
 
     #[inline]
@@ -79,20 +152,10 @@ impl Router {
         self.routes.values().flat_map(|v| v.iter())
     }
 
-    // This is slow. Don't expose this publicly; only for tests.
+    // Don't expose this publicly; only for tests.
     #[cfg(test)]
     fn has_collisions(&self) -> bool {
-        for routes in self.routes.values() {
-            for (i, a_route) in routes.iter().enumerate() {
-                for b_route in routes.iter().skip(i + 1) {
-                    if a_route.collides_with(b_route) {
-                        return true;
-                    }
-                }
-            }
-        }
-
-        false
+        !self.colliding_pairs().is_empty()
     }
 }
 
@@ -114,6 +177,7 @@ mod test {
             router.add(route);
         }
 
+        router.index();
         router
     }
 
@@ -124,6 +188,7 @@ mod test {
             router.add(route);
         }
 
+        router.index();
         router
     }
 
@@ -134,6 +199,7 @@ mod test {
             router.add(route);
         }
 
+        router.index();
         router
     }
 
@@ -206,6 +272,8 @@ mod test {
     #[test]
     fn test_no_collision_when_ranked() {
         assert!(!default_rank_route_collisions(&["/<a>", "/hello"]));
+        assert!(!default_rank_route_collisions(&["/<a>", "/<a: int>"]));
+        assert!(!default_rank_route_collisions(&["/<a: int>", "/hello/<b: int>", "/hello/<b>"]));
         assert!(!default_rank_route_collisions(&["/hello/bob", "/hello/<b>"]));
         assert!(!default_rank_route_collisions(&["/a/b/c/d", "/<a>/<b>/c/d"]));
         assert!(!default_rank_route_collisions(&["/hi", "/<hi>"]));
@@ -265,6 +333,7 @@ mod test {
         router.add(Route::new(Put, "/hello".to_string(), dummy_handler));
         router.add(Route::new(Post, "/hello".to_string(), dummy_handler));
         router.add(Route::new(Delete, "/hello".to_string(), dummy_handler));
+        router.index();
         assert!(route(&router, Put, "/hello").is_some());
         assert!(route(&router, Post, "/hello").is_some());
         assert!(route(&router, Delete, "/hello").is_some());
@@ -276,6 +345,25 @@ mod test {
         assert!(route(&router, Get, "/a/b/c/d/e/f").is_some());
     }
 
+    #[test]
+    fn test_routing_before_and_after_indexing() {
+        let mut router = Router::new();
+        router.add(Route::new(Get, "/<a>", dummy_handler));
+        router.add(Route::new(Put, "/a", dummy_handler));
+        assert_eq!(route(&router, Get, "/a").unwrap().uri.path(), "/<a>");
+        assert_eq!(allowed(&router, Get, "/a"), vec!["GET", "HEAD", "OPTIONS", "PUT"]);
+
+        // Adding a route drops its method's trie. The route is found anyway.
+        router.index();
+        router.add(Route::new(Get, "/a", dummy_handler));
+        assert_eq!(route(&router, Get, "/a").unwrap().uri.path(), "/a");
+        assert_eq!(matches(&router, Get, "/a").len(), 2);
+
+        router.index();
+        assert_eq!(route(&router, Get, "/a").unwrap().uri.path(), "/a");
+        assert_eq!(matches(&router, Get, "/b").len(), 1);
+    }
+
     #[test]
     fn test_err_routing() {
         let router = router_with_routes(&["/hello"]);
@@ -333,6 +421,11 @@ mod test {
         assert_ranked_routes!(&["/a?<c>&b", "/a?<b>"], "/a", "/a?<b>");
         assert_ranked_routes!(&["/a?<c>&b", "/a?<b>"], "/a?b", "/a?<c>&b");
         assert_ranked_routes!(&["/a?<c>&b", "/a?<b>"], "/a?c", "/a?<b>");
+        assert_ranked_routes!(&["/<a>", "/<a: int>"], "/12", "/<a: int>");
+        assert_ranked_routes!(&["/<a: int>", "/<a>"], "/ab", "/<a>");
+        assert_ranked_routes!(&["/<a: int>", "/12"], "/12", "/12");
+        assert_ranked_routes!(&["/<a: int>/<b>", "/<a>/b"], "/12/b", "/<a>/b");
+        assert_ranked_routes!(&["/<a: int>/<b: alpha>", "/<a>/b"], "/12/b", "/<a: int>/<b: alpha>");
     }
 
     fn ranked_collisions(routes: &[(isize, &'static str)]) -> bool {
@@ -350,6 +443,8 @@ mod test {
         assert!(!ranked_collisions(&[(0, "/a/<b..>"), (2, "/a/<b..>")]));
         assert!(!ranked_collisions(&[(5, "/a/<b..>"), (2, "/a/<b..>")]));
         assert!(!ranked_collisions(&[(1, "/<a..>"), (2, "/<a..>")]));
+        assert!(!ranked_collisions(&[(1, "/<a: int>"), (1, "/<a>")]));
+        assert!(!ranked_collisions(&[(1, "/a/<b: int>"), (1, "/a/<b..>")]));
     }
 
     #[test]
@@ -357,6 +452,8 @@ mod test {
         assert!(ranked_collisions(&[(2, "/a/<b..>"), (2, "/a/<b..>")]));
         assert!(ranked_collisions(&[(2, "/a/c/<b..>"), (2, "/a/<b..>")]));
         assert!(ranked_collisions(&[(2, "/<b..>"), (2, "/a/<b..>")]));
+        assert!(ranked_collisions(&[(2, "/<a: int>"), (2, "/<b: uint>")]));
+        assert!(ranked_collisions(&[(2, "/<a: int>/<b>"), (2, "/<a>/<b>")]));
     }
 
     macro_rules! assert_ranked_routing {
@@ -380,6 +477,12 @@ mod test {
             expect: (1, "/a/<b>"), (2, "/a/<b>")
         );
 
+        assert_ranked_routing!(
+            to: "/12",
+            with: [(1, "/<a>"), (1, "/<a: int>"), (0, "/<b>")],
+            expect: (0, "/<b>"), (1, "/<a: int>"), (1, "/<a>")
+        );
+
         assert_ranked_routing!(
             to: "/b/b",
             with: [(1, "/a/<b>"), (2, "/b/<b>"), (3, "/b/b")],
@@ -473,4 +576,93 @@ mod test {
             expect: "/a/b?c", "/a/b?<c>", "/a/b", "/a/<b>?c", "/a/<b>?<c>", "/<a>/<b>"
         );
     }
+
+    fn allowed(router: &Router, method: Method, uri: &str) -> Vec<String> {
+        let rocket = Rocket::custom(Config::development());
+        let request = Request::new(&rocket, method, Origin::parse(uri).unwrap());
+        router.allowed_methods(&request).iter()
+            .map(|m| m.as_str().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_allowed_methods() {
+        let mut router = Router::new();
+        router.add(Route::new(Get, "/a", dummy_handler));
+        router.add(Route::new(Post, "/a", dummy_handler));
+        router.add(Route::new(Put, "/a/<b>", dummy_handler));
+        router.add(Route::new(Delete, "/<a>/<b: int>", dummy_handler));
+        router.add(Route::new(Patch, "/c?d", dummy_handler));
+        router.add(Route::new(Head, "/e", dummy_handler));
+        router.add(Route::new(Get, "/e", dummy_handler));
+        router.add(Route::new(Options, "/f", dummy_handler));
+        router.index();
+
+        assert_eq!(allowed(&router, Delete, "/a"), vec!["GET", "HEAD", "OPTIONS", "POST"]);
+        assert_eq!(allowed(&router, Put, "/a/b"), vec!["OPTIONS", "PUT"]);
+        assert_eq!(allowed(&router, Get, "/a/12"), vec!["DELETE", "OPTIONS", "PUT"]);
+        assert_eq!(allowed(&router, Get, "/b/12"), vec!["DELETE", "OPTIONS"]);
+        assert_eq!(allowed(&router, Get, "/c"), vec!["OPTIONS", "PATCH"]);
+        assert_eq!(allowed(&router, Get, "/e"), vec!["GET", "HEAD", "OPTIONS"]);
+        assert_eq!(allowed(&router, Get, "/f"), vec!["OPTIONS"]);
+        assert_eq!(allowed(&router, Get, "/b/c"), Vec::<String>::new());
+        assert_eq!(allowed(&router, Get, "/"), Vec::<String>::new());
+    }
+
+    const MIXED_ROUTES: &[&str] = &[
+        "/", "/a", "/a/b", "/a/b/c", "/<a>", "/a/<b>", "/<a>/b", "/<a>/<b>",
+        "/a/<b..>", "/<a..>", "/a/b/<c..>", "/a/<b>/c", "/b/<a>/<c..>", "/a/b?c",
+        "/a/b?<c>", "/<a>?c=d", "/c/d/e/f", "/c/<d>/e/<f>", "/c/d/<e..>",
+        "/<a: int>/b", "/c/<d: alpha>/e/<f>",
+    ];
+
+    #[test]
+    fn test_trie_agrees_with_linear_scan() {
+        let requests = &[
+            "/", "/a", "/b", "/a/b", "/a/c", "/b/b", "/a/b/c", "/a/b/d", "/a/x/c",
+            "/b/x/c", "/b/x/c/d", "/c/d/e/f", "/c/x/e/y", "/c/d/e", "/c/d/e/f/g",
+            "/a/b?c", "/a/b?c=d", "/x?c=d", "/a//b/", "/a/b%20c", "/12/b",
+        ];
+
+        for &rank in &[None, Some(0)] {
+            let mut router = Router::new();
+            for path in MIXED_ROUTES {
+                let route = match rank {
+                    Some(rank) => Route::ranked(rank, Get, path.to_string(), dummy_handler),
+                    None => Route::new(Get, path.to_string(), dummy_handler),
+                };
+
+                router.add(route);
+            }
+
+            router.index();
+            let rocket = Rocket::custom(Config::development());
+            for uri in requests {
+                let request = Request::new(&rocket, Get, Origin::parse(uri).unwrap());
+                let expected: Vec<_> = router.routes[&Get].iter()
+                    .filter(|r| r.matches(&request))
+                    .map(|r| r.uri.to_string())
+                    .collect();
+
+                let routed: Vec<_> = router.route(&request).iter()
+                    .map(|r| r.uri.to_string())
+                    .collect();
+
+                assert_eq!(routed, expected, "routing {}", uri);
+            }
+
+            let routes = &router.routes[&Get];
+            let mut expected = vec![];
+            for j in 0..routes.len() {
+                for i in 0..j {
+                    if routes[i].collides_with(&routes[j]) {
+                        expected.push((Get, i, j));
+                    }
+                }
+            }
+
+            expected.sort_by_key(|&(_, i, j)| (j, i));
+            assert_eq!(router.colliding_pairs(), expected);
+        }
+    }
 }
@@ -1,15 +1,16 @@
 use std::fmt::{self, Display};
 use std::convert::From;
+use std::borrow::Cow;
 
 use yansi::Paint;
 
 use codegen::StaticRouteInfo;
 use handler::Handler;
 use http::{Method, MediaType};
-use http::route::{RouteSegment, Kind};
-use error::RouteUriError;
+use http::route::{RouteSegment, HostSegment, Kind};
+use error::{RouteUriError, UrlForError};
 use http::ext::IntoOwned;
-use http::uri::{Origin, Path, Query};
+use http::uri::{Uri, Origin, Path, Query};
 
 /// A route: a method, its handler, path, rank, and format/media type.
 #[derive(Clone)]
@@ -29,6 +30,8 @@ pub struct Route {
     pub rank: isize,
     /// The media type this route matches against, if any.
     pub format: Option<MediaType>,
+    /// The host pattern this route matches against, if any.
+    host: Option<Cow<'static, str>>,
     /// Cached metadata that aids in routing later.
     crate metadata: Metadata
 }
@@ -38,6 +41,7 @@ crate struct Metadata {
     crate path_segments: Vec<RouteSegment<'static, Path>>,
     crate query_segments: Option<Vec<RouteSegment<'static, Query>>>,
     crate fully_dynamic_query: bool,
+    crate host_segments: Option<Vec<HostSegment<'static>>>,
 }
 
 impl Metadata {
@@ -58,7 +62,23 @@ impl Metadata {
             None => (None, true)
         };
 
-        Ok(Metadata { path_segments, query_segments, fully_dynamic_query: dyn })
+        let host_segments = match route.host {
+            Some(ref host) => {
+                let segments = HostSegment::parse_many(host)
+                    .map(|res| res.map(|s| s.into_owned()))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| RouteUriError::Host)?;
+
+                if segments.is_empty() {
+                    return Err(RouteUriError::Host);
+                }
+
+                Some(segments)
+            }
+            None => None
+        };
+
+        Ok(Metadata { path_segments, query_segments, fully_dynamic_query: dyn, host_segments })
     }
 }
 
@@ -102,6 +122,10 @@ impl Route {
     /// | no          | fully dynamic | -2   |
     /// | no          | none          | -1   |
     ///
+    /// Of two routes with the same rank, a route whose dynamic path parameters
+    /// are all constrained, such as `/user/<id: int>`, is tried before a route
+    /// with an unconstrained one. Such routes don't collide.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -136,6 +160,10 @@ impl Route {
     /// // this is a rank -1 route (dynamic path, no query)
     /// let route = Route::new(Method::Get, "/<bar>/foo/<baz..>", handler);
     /// assert_eq!(route.rank, -1);
+    ///
+    /// // this is also a rank -1 route, but it's tried before the one above
+    /// let route = Route::new(Method::Get, "/<bar: int>/foo", handler);
+    /// assert_eq!(route.rank, -1);
     /// ```
     ///
     /// # Panics
@@ -182,6 +210,7 @@ impl Route {
         let mut route = Route {
             name: None,
             format: None,
+            host: None,
             base: Origin::dummy(),
             handler: Box::new(handler),
             metadata: Metadata::default(),
@@ -278,12 +307,188 @@ impl Route {
 
         Ok(())
     }
+
+    /// Retrieves the host pattern of this route, if it has one.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::Route;
+    /// use rocket::http::Method;
+    /// # use rocket::{Request, Data};
+    /// # use rocket::handler::Outcome;
+    /// #
+    /// # fn handler<'r>(request: &'r Request, _data: Data) -> Outcome<'r> {
+    /// #     Outcome::from(request, "Hello, world!")
+    /// # }
+    ///
+    /// let mut index = Route::new(Method::Get, "/", handler);
+    /// assert_eq!(index.host(), None);
+    ///
+    /// index.set_host("API.example.com").unwrap();
+    /// assert_eq!(index.host(), Some("API.example.com"));
+    /// ```
+    #[inline]
+    pub fn host(&self) -> Option<&str> {
+        self.host.as_ref().map(|host| &**host)
+    }
+
+    /// Restricts this route to requests whose `Host` header, ignoring any
+    /// port, matches the pattern `host`.
+    ///
+    /// A pattern is a list of dot-separated labels, each of which is either
+    /// static, matched case-insensitively, or dynamic. A dynamic label is
+    /// either a named parameter, `<param>`, or the wildcard `*`, and matches
+    /// exactly one label of the request's host. Dynamic labels can be
+    /// retrieved with [`Request::get_host_param()`](::Request::get_host_param())
+    /// or, when the route is generated, as handler arguments.
+    ///
+    /// A route without a host pattern matches requests to any host, and so
+    /// collides with a route with a host pattern and an otherwise colliding
+    /// path. Such routes must be ranked.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `host` is empty or contains a label that is neither
+    /// a valid host name label, a `<param>`, nor `*`. The route is left
+    /// unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::Route;
+    /// use rocket::http::Method;
+    /// # use rocket::{Request, Data};
+    /// # use rocket::handler::Outcome;
+    /// #
+    /// # fn handler<'r>(request: &'r Request, _data: Data) -> Outcome<'r> {
+    /// #     Outcome::from(request, "Hello, world!")
+    /// # }
+    ///
+    /// let mut index = Route::new(Method::Get, "/", handler);
+    /// assert!(index.set_host("*.tenants.example.com").is_ok());
+    /// assert!(index.set_host("<tenant>.example.com").is_ok());
+    /// assert!(index.set_host("example.com:8000").is_err());
+    /// assert!(index.set_host("").is_err());
+    /// ```
+    pub fn set_host<H>(&mut self, host: H) -> Result<(), RouteUriError>
+        where H: Into<Cow<'static, str>>
+    {
+        let old_host = ::std::mem::replace(&mut self.host, Some(host.into()));
+        if let Err(e) = self.update_metadata() {
+            self.host = old_host;
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if the route's path has dynamic parameters and all of
+    /// them are constrained. Such a route is tried before routes of the same
+    /// rank that aren't.
+    crate fn is_constrained(&self) -> bool {
+        let segments = &self.metadata.path_segments;
+        segments.iter().any(|s| s.constraint.is_some())
+            && segments.iter().all(|s| s.kind == Kind::Static || s.constraint.is_some())
+    }
+
+    /// The order in which routes are tried: by rank, and then constrained
+    /// routes first.
+    crate fn order(&self) -> (isize, bool) {
+        (self.rank, !self.is_constrained())
+    }
+
+    /// Returns the URI that this route matches when its dynamic parameters
+    /// are given the values in `params`, in the form `(name, value)`. Values
+    /// are checked against the parameters' constraints and percent-encoded.
+    /// Dynamic query parameters without a value are left out, while values
+    /// for parameters the route doesn't have are appended to the query if it
+    /// ends in a `<param..>`. Routes with a host pattern have no such URI: an
+    /// origin URI can't name the host they're restricted to.
+    crate fn uri_for(&self, params: Vec<(String, String)>) -> Result<Origin<'static>, UrlForError> {
+        // Takes the value of the first parameter named `name` that's unused.
+        fn take(params: &mut [Option<(String, String)>], name: &str) -> Option<String> {
+            params.iter_mut()
+                .find(|param| param.as_ref().map_or(false, |param| param.0 == name))
+                .and_then(|param| param.take())
+                .map(|(_, value)| value)
+        }
+
+        if let Some(ref host) = self.host {
+            return Err(UrlForError::HostRoute(host.to_string()));
+        }
+
+        let mut params: Vec<_> = params.into_iter().map(Some).collect();
+
+        let mut segments = vec![];
+        for segment in &self.metadata.path_segments {
+            match segment.kind {
+                Kind::Static => segments.push(segment.string.to_string()),
+                Kind::Single | Kind::Multi => {
+                    let value = take(&mut params, &segment.name)
+                        .ok_or_else(|| UrlForError::MissingParameter(segment.name.to_string()))?;
+
+                    if let Some(ref constraint) = segment.constraint {
+                        if !constraint.is_satisfied_by(&value) {
+                            return Err(UrlForError::InvalidParameter(segment.name.to_string()));
+                        }
+                    }
+
+                    let pieces: Vec<_> = match segment.kind {
+                        Kind::Multi => value.split('/').filter(|s| !s.is_empty()).collect(),
+                        _ => vec![&*value],
+                    };
+
+                    // Empty segments are never matched, not even by `<param..>`.
+                    if pieces.is_empty() || pieces[0].is_empty() {
+                        return Err(UrlForError::InvalidParameter(segment.name.to_string()));
+                    }
+
+                    let encoded = pieces.into_iter().map(|s| Uri::percent_encode(s).into_owned());
+                    segments.extend(encoded);
+                }
+            }
+        }
+
+        let path = format!("/{}", segments.join("/"));
+
+        let mut query = vec![];
+        let mut takes_rest = false;
+        for segment in self.metadata.query_segments.iter().flat_map(|s| s.iter()) {
+            match segment.kind {
+                Kind::Static => query.push(segment.string.to_string()),
+                Kind::Single => {
+                    if let Some(value) = take(&mut params, &segment.name) {
+                        let value = Uri::percent_encode(&value);
+                        query.push(format!("{}={}", segment.name, value));
+                    }
+                }
+                Kind::Multi => takes_rest = true,
+            }
+        }
+
+        for (name, value) in params.into_iter().filter_map(|param| param) {
+            if !takes_rest {
+                return Err(UrlForError::UnknownParameter(name));
+            }
+
+            let (name, value) = (Uri::percent_encode(&name), Uri::percent_encode(&value));
+            query.push(format!("{}={}", name, value));
+        }
+
+        let query = if query.is_empty() { None } else { Some(query.join("&")) };
+        Ok(Origin::new(path, query))
+    }
 }
 
 impl fmt::Display for Route {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{} {}", Paint::green(&self.method), Paint::blue(&self.uri))?;
 
+        if let Some(ref host) = self.host {
+            write!(f, " {}{}", Paint::cyan("@"), Paint::blue(host))?;
+        }
+
         if self.rank > 1 {
             write!(f, " [{}]", Paint::default(&self.rank).bold())?;
         }
@@ -310,6 +515,7 @@ impl fmt::Debug for Route {
             .field("uri", &self.uri)
             .field("rank", &self.rank)
             .field("format", &self.format)
+            .field("host", &self.host)
             .field("metadata", &self.metadata)
             .finish()
     }
@@ -326,6 +532,10 @@ impl<'a> From<&'a StaticRouteInfo> for Route {
             route.rank = rank;
         }
 
+        if let Some(host) = info.host {
+            route.set_host(host).unwrap_or_else(|e| panic(host, e));
+        }
+
         route
     }
 }
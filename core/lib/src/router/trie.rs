@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use http::route::Kind;
+
+use super::Route;
+
+/// A prefix tree over the path segments of a set of routes, used to find the
+/// routes that may match a request, or collide with a route, without checking
+/// every route.
+///
+/// Routes are referred to by their index in the list of routes the trie was
+/// built from. Routes that are returned are only _candidates_: they are
+/// compatible with the path but must still be checked in full.
+#[derive(Debug, Default)]
+crate struct Trie {
+    root: Node,
+}
+
+#[derive(Debug, Default)]
+struct Node {
+    /// The children reached through a static segment, keyed by the segment.
+    statics: HashMap<String, Node>,
+    /// The child reached through a dynamic, single segment.
+    dynamic: Option<Box<Node>>,
+    /// The routes whose path ends at this node.
+    ends: Vec<usize>,
+    /// The routes with a multi-segment parameter following this node.
+    multis: Vec<usize>,
+}
+
+impl Trie {
+    /// Builds a trie over all of the routes in `routes`.
+    crate fn new(routes: &[Route]) -> Trie {
+        let mut trie = Trie::default();
+        for (index, route) in routes.iter().enumerate() {
+            trie.insert(index, route);
+        }
+
+        trie
+    }
+
+    // Adds `route`, which is at `index` in the list of routes.
+    fn insert(&mut self, index: usize, route: &Route) {
+        let mut node = &mut self.root;
+        for segment in &route.metadata.path_segments {
+            let parent = node;
+            node = match segment.kind {
+                Kind::Static => parent.statics.entry(segment.string.to_string()).or_default(),
+                Kind::Single => &mut **parent.dynamic.get_or_insert_with(Box::default),
+                Kind::Multi => {
+                    parent.multis.push(index);
+                    return;
+                }
+            };
+        }
+
+        node.ends.push(index);
+    }
+
+    /// Returns, in order, the routes whose path may match a request path
+    /// consisting of the raw `segments`.
+    crate fn candidates(&self, segments: &[&str]) -> Vec<usize> {
+        let mut routes = vec![];
+        self.root.collect_candidates(segments, &mut routes);
+        routes.sort();
+        routes
+    }
+
+    /// Returns, in order, the routes whose path may collide with the path of
+    /// `route`. This includes `route` itself if it's in the trie.
+    crate fn colliding(&self, route: &Route) -> Vec<usize> {
+        let mut routes = vec![];
+        self.root.collect_colliding(route, 0, &mut routes);
+        routes.sort();
+        routes
+    }
+}
+
+impl Node {
+    fn children(&self) -> impl Iterator<Item = &Node> {
+        self.statics.values().chain(self.dynamic.as_ref().map(|node| &**node))
+    }
+
+    fn collect_all(&self, routes: &mut Vec<usize>) {
+        routes.extend(&self.ends);
+        routes.extend(&self.multis);
+        self.children().for_each(|child| child.collect_all(routes));
+    }
+
+    fn collect_candidates(&self, segments: &[&str], routes: &mut Vec<usize>) {
+        let (segment, rest) = match segments.split_first() {
+            Some((segment, rest)) => (segment, rest),
+            None => {
+                routes.extend(&self.ends);
+                return;
+            }
+        };
+
+        // A multi-segment parameter matches one or more segments.
+        routes.extend(&self.multis);
+        if let Some(child) = self.statics.get(*segment) {
+            child.collect_candidates(rest, routes);
+        }
+
+        if let Some(ref child) = self.dynamic {
+            child.collect_candidates(rest, routes);
+        }
+    }
+
+    // Mirrors `paths_collide`: `self` is at depth `depth` in the trie.
+    fn collect_colliding(&self, route: &Route, depth: usize, routes: &mut Vec<usize>) {
+        let segment = match route.metadata.path_segments.get(depth) {
+            Some(segment) => segment,
+            None => {
+                routes.extend(&self.ends);
+                return;
+            }
+        };
+
+        routes.extend(&self.multis);
+        match segment.kind {
+            Kind::Multi => self.children().for_each(|child| child.collect_all(routes)),
+            Kind::Single => {
+                self.children().for_each(|child| child.collect_colliding(route, depth + 1, routes))
+            }
+            Kind::Static => {
+                if let Some(child) = self.statics.get(&*segment.string) {
+                    child.collect_colliding(route, depth + 1, routes);
+                }
+
+                if let Some(ref child) = self.dynamic {
+                    child.collect_colliding(route, depth + 1, routes);
+                }
+            }
+        }
+    }
+}
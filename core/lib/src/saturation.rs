@@ -0,0 +1,188 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use request::{self, FromRequest, Request};
+use outcome::Outcome;
+
+#[derive(Default)]
+struct SaturationState {
+    workers: usize,
+    max_pending: Option<usize>,
+    busy: AtomicUsize,
+    pending: AtomicUsize,
+    rejected: AtomicUsize,
+}
+
+/// A gauge reporting how saturated a launched Rocket application's workers
+/// are.
+///
+/// Each connection to a Rocket application is served by one of
+/// [`workers`](::Config::workers) worker threads. When all workers are busy,
+/// new connections wait for a worker to become available. If
+/// [`max_pending`](::Config::max_pending) is set and that many connections are
+/// already waiting, Rocket instead responds to the request immediately with a
+/// `503 Service Unavailable` error via the `503` catcher, setting the
+/// `Retry-After` header if the catcher doesn't, and closes the connection.
+///
+/// Every `Rocket` instance manages a `Saturation` gauge. It can be retrieved
+/// via [`Rocket::saturation()`], in a handler via the `Saturation` request
+/// guard or [`State<Saturation>`](::State), or from any structure that has
+/// access to the `Rocket` instance. Gauges are cheap to clone; all clones
+/// refer to the same application.
+///
+/// # Example
+///
+/// A route that reports the application's saturation:
+///
+/// ```rust
+/// # #![feature(proc_macro_hygiene, decl_macro)]
+/// # #[macro_use] extern crate rocket;
+/// use rocket::Saturation;
+///
+/// #[get("/load")]
+/// fn load(saturation: Saturation) -> String {
+///     format!("{}/{} busy, {} pending, {} rejected", saturation.busy(),
+///         saturation.workers(), saturation.pending(), saturation.rejected())
+/// }
+/// # fn main() { }
+/// ```
+#[derive(Clone, Default)]
+pub struct Saturation(Arc<SaturationState>);
+
+impl Saturation {
+    #[inline]
+    crate fn new(workers: u16, max_pending: Option<u32>) -> Saturation {
+        Saturation(Arc::new(SaturationState {
+            workers: workers as usize,
+            max_pending: max_pending.map(|n| n as usize),
+            ..SaturationState::default()
+        }))
+    }
+
+    /// Returns the number of worker threads.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging).workers(8).unwrap();
+    /// let saturation = rocket::custom(config).saturation();
+    /// assert_eq!(saturation.workers(), 8);
+    /// ```
+    #[inline]
+    pub fn workers(&self) -> usize {
+        self.0.workers
+    }
+
+    /// Returns the number of workers currently serving a connection.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let saturation = rocket::ignite().saturation();
+    /// assert_eq!(saturation.busy(), 0);
+    /// ```
+    #[inline]
+    pub fn busy(&self) -> usize {
+        self.0.busy.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of connections waiting for a worker.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let saturation = rocket::ignite().saturation();
+    /// assert_eq!(saturation.pending(), 0);
+    /// ```
+    #[inline]
+    pub fn pending(&self) -> usize {
+        self.0.pending.load(Ordering::Relaxed)
+    }
+
+    /// Returns the maximum number of connections that may wait for a worker,
+    /// or `None` if the number is unbounded.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging).max_pending(64).unwrap();
+    /// let saturation = rocket::custom(config).saturation();
+    /// assert_eq!(saturation.max_pending(), Some(64));
+    /// ```
+    #[inline]
+    pub fn max_pending(&self) -> Option<usize> {
+        self.0.max_pending
+    }
+
+    /// Returns the total number of connections rejected with a `503` because
+    /// too many connections were waiting for a worker.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let saturation = rocket::ignite().saturation();
+    /// assert_eq!(saturation.rejected(), 0);
+    /// ```
+    #[inline]
+    pub fn rejected(&self) -> usize {
+        self.0.rejected.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if new connections are currently being rejected because
+    /// too many connections are waiting for a worker.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let saturation = rocket::ignite().saturation();
+    /// assert!(!saturation.is_saturated());
+    /// ```
+    #[inline]
+    pub fn is_saturated(&self) -> bool {
+        self.0.max_pending.map_or(false, |max| self.pending() >= max)
+    }
+
+    /// Records a new connection waiting for a worker. Returns `false` and
+    /// counts the connection as rejected if too many are already waiting.
+    crate fn enqueue(&self) -> bool {
+        let pending = self.0.pending.fetch_add(1, Ordering::AcqRel);
+        if self.0.max_pending.map_or(false, |max| pending >= max) {
+            self.0.pending.fetch_sub(1, Ordering::AcqRel);
+            self.0.rejected.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+
+        true
+    }
+
+    /// Records that a waiting connection was picked up by a worker. The worker
+    /// is counted as busy until the returned guard is dropped, even if serving
+    /// the connection panics.
+    crate fn start(&self) -> Serving {
+        self.0.pending.fetch_sub(1, Ordering::AcqRel);
+        self.0.busy.fetch_add(1, Ordering::AcqRel);
+        Serving(self.clone())
+    }
+}
+
+/// Counts a worker as busy for as long as it is alive.
+crate struct Serving(Saturation);
+
+impl Drop for Serving {
+    fn drop(&mut self) {
+        (self.0).0.busy.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for Saturation {
+    type Error = !;
+
+    #[inline]
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        Outcome::Success(request.state.managed.get::<Saturation>().clone())
+    }
+}
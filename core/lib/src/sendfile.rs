@@ -0,0 +1,95 @@
+use std::fs::File;
+use std::io;
+
+use data::NetStream;
+
+#[cfg(target_os = "linux")] use std::cmp::min;
+#[cfg(target_os = "linux")] use std::io::{Seek, SeekFrom};
+#[cfg(target_os = "linux")] use std::os::unix::io::{AsRawFd, RawFd};
+
+#[cfg(target_os = "linux")] use libc;
+
+// Responses with a plain file as their body would otherwise be copied from the
+// file into a buffer and from there into the socket. On Linux, `sendfile()`
+// has the kernel copy the file into the socket directly instead.
+//
+// This is only possible when what's written to the socket is what the client
+// receives: plaintext TCP or Unix domain sockets. TLS streams and HTTP/2
+// connections, which frame the data, as well as bodies that wrap a file, take
+// the regular path.
+
+/// The most `sendfile()` transfers in one call.
+#[cfg(target_os = "linux")]
+const MAX_SENDFILE: u64 = 0x7fff_f000;
+
+/// A socket that files can be sent to directly.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, Copy)]
+crate struct Socket(RawFd);
+
+#[cfg(target_os = "linux")]
+impl Socket {
+    /// Returns the socket underlying `stream` if what's written to it reaches
+    /// the client unchanged. The socket isn't owned: it must not be used once
+    /// `stream` and its clones are closed.
+    crate fn of(stream: &NetStream) -> Option<Socket> {
+        match *stream {
+            NetStream::Http(ref stream) => Some(Socket(stream.0.as_raw_fd())),
+            NetStream::Unix(ref stream) => Some(Socket(stream.as_raw_fd())),
+            _ => None
+        }
+    }
+
+    /// Sends `size` bytes of `file`, starting at its current position, to the
+    /// socket. Anything buffered for the socket must be flushed beforehand.
+    /// Returns `Ok(false)`, having sent nothing, if `file` can't be sent this
+    /// way, in which case it should be copied as usual.
+    crate fn send_file(self, file: &File, size: u64) -> io::Result<bool> {
+        let start = (&*file).seek(SeekFrom::Current(0))?;
+        let end = match start.checked_add(size) {
+            Some(end) if end <= libc::off_t::max_value() as u64 => end,
+            _ => return Ok(false)
+        };
+
+        let mut offset = start as libc::off_t;
+        while (offset as u64) < end {
+            let count = min(end - offset as u64, MAX_SENDFILE) as usize;
+            let sent = unsafe { libc::sendfile(self.0, file.as_raw_fd(), &mut offset, count) };
+            if sent < 0 {
+                let error = io::Error::last_os_error();
+                match error.raw_os_error() {
+                    Some(libc::EINTR) => continue,
+                    Some(libc::EINVAL) | Some(libc::ENOSYS) if offset as u64 == start => {
+                        return Ok(false);
+                    }
+                    _ => return Err(error)
+                }
+            }
+
+            if sent == 0 {
+                let msg = "file ended before all of it was sent";
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, msg));
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+/// A socket that files can be sent to directly. There are none on this
+/// platform.
+#[cfg(not(target_os = "linux"))]
+#[derive(Debug, Clone, Copy)]
+crate enum Socket {  }
+
+#[cfg(not(target_os = "linux"))]
+impl Socket {
+    #[inline(always)]
+    crate fn of(_: &NetStream) -> Option<Socket> {
+        None
+    }
+
+    crate fn send_file(self, _: &File, _: u64) -> io::Result<bool> {
+        match self {  }
+    }
+}
@@ -0,0 +1,670 @@
+use std::cmp;
+use std::str;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::net::{SocketAddr, Ipv4Addr};
+use std::net::Shutdown as NetShutdown;
+use std::sync::{Arc, Mutex, Condvar, mpsc};
+use std::time::{Duration, Instant};
+use std::thread;
+use std::panic::{self, AssertUnwindSafe};
+
+use yansi::Paint;
+
+use {Rocket, Shutdown, Saturation};
+#[cfg(feature = "http2")] use http2;
+use data::{NetStream, is_timeout};
+use idle::Idle;
+use listener::Acceptor;
+use request::{BadRequestInfo, BadRequestError};
+use upgrade::{Upgrade, Upgraded};
+
+use http::hyper::{self, header};
+use http::hyper::buffer::BufReader;
+use http::hyper::net::NetworkStream;
+
+// Hyper's `Server` runs its accept loop on threads that can never be stopped,
+// so we run our own. Connections are accepted on a dedicated thread and handed
+// to a fixed pool of `workers` threads which run the keep-alive loop, parsing
+// requests with Hyper and handing them to `Rocket` as a `hyper::Handler`.
+//
+// A worker doesn't wait for the first request on a new connection, nor for the
+// next request on a connection idling in keep-alive. Instead, the connection is
+// parked with the "idle" thread, which watches all idle connections at once and
+// queues them for the workers again as soon as data arrives. Idle connections
+// thus don't occupy workers.
+//
+// Everything else does: a worker serves a connection from the moment its
+// request starts arriving until its response has been written. Upgraded
+// connections are the exception; they are handed to a thread of their own once
+// the response that upgraded them has been written. Connections that speak
+// HTTP/2 or stream an unbounded response keep their worker for as long as they
+// stay open. Freeing workers from those as well requires an asynchronous server.
+//
+// Connections that speak HTTP/2, negotiated via ALPN on TLS connections if
+// `http2` is enabled or announced by the client's preface on plaintext ones if
+// `h2c` is, are served by the `http2` module instead for as long as they stay open.
+//
+// If `max_pending` connections are already waiting for a worker, connections
+// are instead handed to a single "overload" thread which responds to their
+// first request with a 503 and hangs up. If even that thread can't keep up,
+// connections are closed without a response.
+
+/// The number of rejected connections that may wait for the overload thread.
+const OVERLOAD_QUEUE: usize = 32;
+
+/// How long the overload thread waits to read a request or write a response.
+const OVERLOAD_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How long an acceptor pauses after failing to accept a connection, such as
+/// when the process is out of file descriptors. The pause doubles with every
+/// consecutive failure, up to `MAX_ACCEPT_BACKOFF`.
+const MIN_ACCEPT_BACKOFF: Duration = Duration::from_millis(5);
+
+/// The longest an acceptor pauses after failing to accept a connection.
+const MAX_ACCEPT_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Counts the connections that are currently being served. A connection that
+/// is idling in keep-alive is not considered active.
+#[derive(Default)]
+struct Active {
+    count: Mutex<usize>,
+    condvar: Condvar,
+}
+
+/// Marks a connection as active for as long as it is alive and not idling.
+struct Busy {
+    active: Arc<Active>,
+    is_busy: bool,
+}
+
+impl Busy {
+    fn new(active: &Arc<Active>) -> Busy {
+        let mut busy = Busy { active: active.clone(), is_busy: false };
+        busy.set(true);
+        busy
+    }
+
+    fn set(&mut self, is_busy: bool) {
+        if self.is_busy == is_busy {
+            return;
+        }
+
+        let mut count = self.active.count.lock().expect("active lock");
+        if is_busy {
+            *count += 1;
+        } else {
+            *count -= 1;
+            if *count == 0 {
+                self.active.condvar.notify_all();
+            }
+        }
+
+        self.is_busy = is_busy;
+    }
+}
+
+impl Drop for Busy {
+    fn drop(&mut self) {
+        self.set(false);
+    }
+}
+
+impl Active {
+    /// Waits at most `timeout` for all connections to become inactive. Returns
+    /// the number of connections that are still active.
+    fn wait_idle(&self, timeout: Duration) -> usize {
+        let deadline = Instant::now() + timeout;
+        let mut count = self.count.lock().expect("active lock");
+        while *count > 0 {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+
+            count = self.condvar.wait_timeout(count, deadline - now)
+                .expect("active lock").0;
+        }
+
+        *count
+    }
+}
+
+/// Hands connections to the workers, or to the overload thread if too many
+/// connections are already waiting for a worker.
+#[derive(Clone)]
+struct Queue {
+    sender: mpsc::Sender<(NetStream, Busy)>,
+    overload: mpsc::SyncSender<NetStream>,
+    saturation: Saturation,
+    active: Arc<Active>,
+}
+
+impl Queue {
+    fn push(&self, stream: NetStream) {
+        if self.saturation.enqueue() {
+            let _ = self.sender.send((stream, Busy::new(&self.active)));
+        } else if self.overload.try_send(stream).is_err() {
+            warn_!("Too many pending connections. Dropping connection.");
+        }
+    }
+}
+
+/// Serves `rocket` on every acceptor in `acceptors` until a shutdown is
+/// requested via the application's `Shutdown` handle. Once requested, stops
+/// accepting new connections and waits up to the configured grace period for
+/// in-flight requests to complete.
+crate fn serve(rocket: Arc<Rocket>, acceptors: Vec<Acceptor>) -> io::Result<()> {
+    let shutdown = rocket.shutdown();
+    let active = Arc::new(Active::default());
+
+    let (overload, overload_receiver) = mpsc::sync_channel::<NetStream>(OVERLOAD_QUEUE);
+    let overload_rocket = rocket.clone();
+    thread::Builder::new().name("rocket-overload".into()).spawn(move || {
+        for stream in overload_receiver {
+            reject_connection(&overload_rocket, stream);
+        }
+    })?;
+
+    let (sender, receiver) = mpsc::channel::<(NetStream, Busy)>();
+    let saturation = rocket.saturation();
+    let queue = Queue { sender, overload, saturation, active: active.clone() };
+
+    let idle_queue = queue.clone();
+    let idle = Idle::spawn(move |stream| idle_queue.push(stream))?;
+    let first_request = rocket.config.header_read_timeout.map(|s| Duration::from_secs(s as u64));
+
+    let receiver = Arc::new(Mutex::new(receiver));
+    for i in 0..rocket.config.workers {
+        let (rocket, receiver, idle) = (rocket.clone(), receiver.clone(), idle.clone());
+        thread::Builder::new().name(format!("rocket-worker-{}", i)).spawn(move || {
+            let (shutdown, saturation) = (rocket.shutdown(), rocket.saturation());
+            loop {
+                let job = receiver.lock().expect("worker queue lock").recv();
+                match job {
+                    Ok((stream, busy)) => {
+                        // Panics during dispatch are caught and turned into a
+                        // 500 by `Rocket`. Anything else, like a panicking body
+                        // or catcher, costs the connection but not the worker.
+                        let _serving = saturation.start();
+                        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                            handle_connection(&rocket, &shutdown, &idle, stream, busy)
+                        }));
+
+                        if result.is_err() {
+                            error_!("Worker panicked while serving a connection.");
+                        }
+                    }
+                    Err(_) => break
+                }
+            }
+        })?;
+    }
+
+    // Each acceptor gets its own thread; all of them feed the same workers.
+    let mut accept_threads = vec![];
+    for (i, acceptor) in acceptors.into_iter().enumerate() {
+        let acceptor = Arc::new(acceptor);
+        let (shutdown, queue, idle) = (shutdown.clone(), queue.clone(), idle.clone());
+        let thread_acceptor = acceptor.clone();
+        let name = format!("rocket-acceptor-{}", i);
+        let thread = thread::Builder::new().name(name).spawn(move || {
+            let mut backoff = None;
+            loop {
+                let result = thread_acceptor.accept();
+                if shutdown.is_requested() {
+                    break;
+                }
+
+                match result {
+                    Ok(stream) => {
+                        backoff = None;
+
+                        // Until the client sends its first request, the
+                        // connection is as good as idle.
+                        if let Err(stream) = idle.park(stream, first_request) {
+                            queue.push(stream);
+                        }
+                    }
+                    Err(ref e) if is_transient_accept_error(e) => {
+                        debug_!("Failed to accept connection: {}", e);
+                    }
+                    Err(e) => {
+                        // Errors like running out of file descriptors persist
+                        // for a while. Don't spin on them.
+                        let pause = backoff.map_or(MIN_ACCEPT_BACKOFF, |pause: Duration| {
+                            cmp::min(pause * 2, MAX_ACCEPT_BACKOFF)
+                        });
+
+                        warn_!("Failed to accept connection: {}", e);
+                        thread::sleep(pause);
+                        backoff = Some(pause);
+                    }
+                }
+            }
+        })?;
+
+        accept_threads.push((acceptor, thread));
+    }
+
+    shutdown.wait();
+    info!("{}{}", Paint::masked("🛑 "), Paint::default("Shutdown requested.").bold());
+
+    // The acceptors are likely blocked in `accept()`. Wake them up so they see
+    // that a shutdown was requested and stop accepting connections.
+    for (acceptor, thread) in accept_threads {
+        match acceptor.wake() {
+            Ok(_) => { let _ = thread.join(); }
+            Err(e) => warn_!("Failed to wake connection acceptor: {}", e),
+        }
+    }
+
+    // Idle connections have no requests in flight. Close them right away.
+    idle.stop();
+
+    let grace = Duration::from_secs(rocket.config.shutdown_grace as u64);
+    info_!("Waiting up to {}s for active connections to complete.", grace.as_secs());
+    match active.wait_idle(grace) {
+        0 => info_!("All connections completed."),
+        n => warn_!("Grace period elapsed with {} connection(s) still active.", n),
+    }
+
+    Ok(())
+}
+
+/// Returns `true` if `error` only concerns the connection that failed to be
+/// accepted, so accepting another one can be attempted right away.
+fn is_transient_accept_error(error: &io::Error) -> bool {
+    match error.kind() {
+        io::ErrorKind::Interrupted
+            | io::ErrorKind::WouldBlock
+            | io::ErrorKind::ConnectionAborted => true,
+        _ => false
+    }
+}
+
+/// What becomes of a connection once it stops serving HTTP/1 requests.
+enum Outcome {
+    /// Park it until the client sends another request.
+    Park,
+    /// Close it.
+    Close,
+    /// Hand it to the upgrade callback along with any bytes already read.
+    Upgrade(Upgrade, Vec<u8>),
+}
+
+fn handle_connection(
+    rocket: &Rocket,
+    shutdown: &Shutdown,
+    idle: &Idle,
+    mut stream: NetStream,
+    mut busy: Busy
+) {
+    // Connections over Unix domain sockets don't have a remote address.
+    let remote = stream.peer_addr().ok();
+
+    let keep_alive = rocket.config.keep_alive.map(|s| Duration::from_secs(s as u64));
+    let write_timeout = rocket.config.write_timeout.map(|s| Duration::from_secs(s as u64));
+    if let Err(e) = stream.set_write_timeout(write_timeout) {
+        debug!("Failed to set write timeout: {:?}", e);
+    }
+
+    let outcome = {
+        let mut read_stream = stream.clone();
+        let mut reader = BufReader::new(&mut read_stream as &mut NetworkStream);
+
+        #[cfg(feature = "http2")]
+        {
+            if speaks_http2(rocket, &stream, &mut reader) {
+                let buffered = reader.get_buf().to_vec();
+                let mut set_busy = |is_busy| busy.set(is_busy);
+                http2::serve(rocket, shutdown, stream.clone(), buffered, remote, &mut set_busy);
+                return;
+            }
+        }
+
+        let mut writer = BufWriter::new(&mut stream);
+        loop {
+            match handle_request(rocket, shutdown, &mut reader, &mut writer, remote) {
+                Next::KeepAlive => {},
+                Next::Close => break Outcome::Close,
+                Next::Upgrade(upgrade) => {
+                    break Outcome::Upgrade(upgrade, reader.get_buf().to_vec());
+                }
+            }
+
+            // We're idling in keep-alive until more data arrives. Don't hold
+            // up a shutdown on account of this connection.
+            busy.set(false);
+            if shutdown.is_requested() {
+                break Outcome::Close;
+            }
+
+            // Unless the client already sent its next request, free up this
+            // worker while we wait for one.
+            if reader.get_buf().is_empty() && idle.can_park(writer.get_ref()) {
+                break Outcome::Park;
+            }
+
+            if reader.get_ref().set_read_timeout(keep_alive).is_err() {
+                break Outcome::Close;
+            }
+
+            match reader.fill_buf() {
+                Ok(buf) if !buf.is_empty() => busy.set(true),
+                _ => break Outcome::Close
+            }
+        }
+    };
+
+    match outcome {
+        Outcome::Park => {
+            stream = match idle.park(stream, keep_alive) {
+                Ok(()) => return,
+                Err(stream) => stream
+            };
+        }
+        Outcome::Upgrade(upgrade, buffered) => {
+            // The connection is no longer HTTP: timeouts are the upgrade's
+            // business. It may stay open indefinitely, so it gets a thread of
+            // its own instead of keeping the worker.
+            if let Err(e) = stream.set_read_timeout(None) {
+                debug!("Failed to clear read timeout: {:?}", e);
+            }
+
+            let upgraded = Upgraded::new(buffered, stream.clone());
+            let thread = thread::Builder::new().name("rocket-upgraded".into());
+            let spawned = thread.spawn(move || {
+                // A shutdown waits for upgraded connections like any other.
+                let _busy = busy;
+                if panic::catch_unwind(AssertUnwindSafe(|| upgrade(upgraded))).is_err() {
+                    error_!("Upgraded connection callback panicked.");
+                }
+
+                if let Err(e) = stream.close(NetShutdown::Both) {
+                    debug!("Failed to close network stream: {:?}", e);
+                }
+            });
+
+            if let Err(e) = spawned {
+                warn_!("Failed to spawn thread for upgraded connection: {}", e);
+            }
+
+            return;
+        }
+        Outcome::Close => {}
+    }
+
+    if let Err(e) = stream.close(NetShutdown::Both) {
+        debug!("Failed to close network stream: {:?}", e);
+    }
+}
+
+// Returns `true` if the client on `stream` speaks HTTP/2: either it negotiated
+// `h2` via ALPN, which is only offered if `http2` is enabled, or, if `h2c` is
+// enabled, it sent the HTTP/2 connection preface over plaintext. Bytes read
+// while checking remain buffered in `reader`.
+#[cfg(feature = "http2")]
+fn speaks_http2(
+    rocket: &Rocket,
+    stream: &NetStream,
+    reader: &mut BufReader<&mut NetworkStream>
+) -> bool {
+    let timeout = rocket.config.header_read_timeout.map(|s| Duration::from_secs(s as u64));
+    match *stream {
+        #[cfg(feature = "tls")]
+        NetStream::Https(ref tls) => {
+            if stream.set_read_timeout(timeout).is_err() {
+                return false;
+            }
+
+            match tls.alpn_protocol() {
+                Ok(protocol) => protocol.map_or(false, |p| p == "h2"),
+                Err(e) => {
+                    debug!("TLS handshake failed: {:?}", e);
+                    false
+                }
+            }
+        }
+        _ if rocket.config.h2c => {
+            if stream.set_read_timeout(timeout).is_err() {
+                return false;
+            }
+
+            // Read until the buffer either contains or diverges from the preface.
+            let preface = http2::PREFACE;
+            while reader.get_buf().len() < preface.len() && preface.starts_with(reader.get_buf()) {
+                match reader.read_into_buf() {
+                    Ok(0) | Err(_) => return false,
+                    Ok(_) => {}
+                }
+            }
+
+            reader.get_buf().starts_with(preface)
+        }
+        _ => false
+    }
+}
+
+// Responds to the first request on `stream` with a 503 without dispatching it,
+// then closes the connection. Clients get little time to send their request.
+fn reject_connection(rocket: &Rocket, mut stream: NetStream) {
+    let remote = stream.peer_addr().ok();
+    if let Err(e) = stream.set_write_timeout(Some(OVERLOAD_TIMEOUT)) {
+        debug!("Failed to set write timeout: {:?}", e);
+    }
+
+    {
+        let mut read_stream = stream.clone();
+        let mut reader = BufReader::new(&mut read_stream as &mut NetworkStream);
+        let mut writer = BufWriter::new(&mut stream);
+        if let Err(e) = read_head(&mut reader, OVERLOAD_TIMEOUT) {
+            debug!("Failed to read request head: {:?}", e);
+            return;
+        }
+
+        let addr = remote.unwrap_or_else(|| SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0));
+        match hyper::Request::new(&mut reader, addr) {
+            Ok(hyp_req) => {
+                let mut res_headers = header::Headers::new();
+                let hyp_res = hyper::Response::new(&mut writer as &mut Write, &mut res_headers);
+                rocket.handle_overloaded(hyp_req, hyp_res, remote);
+            }
+            Err(e) => debug!("Failed to read request: {:?}", e),
+        }
+
+        let _ = writer.flush();
+    }
+
+    if let Err(e) = stream.close(NetShutdown::Both) {
+        debug!("Failed to close network stream: {:?}", e);
+    }
+}
+
+// Returns `true` if `buf` contains the entirety of a request head, that is, if
+// it contains the empty line terminating the headers.
+fn head_complete(buf: &[u8]) -> bool {
+    buf.windows(4).any(|w| w == b"\r\n\r\n") || buf.windows(2).any(|w| w == b"\n\n")
+}
+
+// Buffers the request head in `reader`, failing with `TimedOut` if the entire
+// head doesn't arrive within `timeout`. Stops early when the buffer is full or
+// the stream ends, leaving it to Hyper to report the error.
+fn read_head(reader: &mut BufReader<&mut NetworkStream>, timeout: Duration) -> io::Result<()> {
+    let deadline = Instant::now() + timeout;
+    while !head_complete(reader.get_buf()) {
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+
+        reader.get_ref().set_read_timeout(Some(deadline - now))?;
+        if reader.read_into_buf()? == 0 {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+// Responds to a request that couldn't be parsed via the bad request handler.
+fn bad_request(
+    rocket: &Rocket,
+    writer: &mut Write,
+    error: BadRequestError,
+    remote: Option<SocketAddr>
+) {
+    let info = BadRequestInfo::new(None, None, error, remote);
+    let mut res_headers = header::Headers::new();
+    rocket.handle_bad_request(&info, hyper::Response::new(writer, &mut res_headers));
+    let _ = writer.flush();
+}
+
+// Parses the request line at the start of `buf`, if it arrived in full.
+fn request_line(buf: &[u8]) -> Option<(hyper::Method, hyper::RequestUri)> {
+    let end = buf.iter().position(|&b| b == b'\n')?;
+    let line = str::from_utf8(&buf[..end]).ok()?;
+    let mut parts = line.trim_right_matches('\r').split(' ');
+    let method = parts.next()?.parse().ok()?;
+    let uri = parts.next()?.parse().ok()?;
+    match (parts.next(), parts.next()) {
+        (Some(_), None) => Some((method, uri)),
+        _ => None
+    }
+}
+
+// Responds to a request whose head couldn't be read in full with `error`. The
+// request line may be among the `buffered` bytes all the same, in which case
+// the catcher for the error's status responds, as it would to any request.
+// Otherwise, there's no request to speak of, and the bad request handler does.
+fn incomplete_head(
+    rocket: &Rocket,
+    buffered: &[u8],
+    writer: &mut Write,
+    error: BadRequestError,
+    remote: Option<SocketAddr>
+) {
+    let (h_method, h_uri) = match request_line(buffered) {
+        Some(line) => line,
+        None => return bad_request(rocket, writer, error, remote),
+    };
+
+    let mut res_headers = header::Headers::new();
+    {
+        let res = hyper::Response::new(&mut *writer, &mut res_headers);
+        rocket.handle_incomplete_head(h_method, h_uri, error, res, remote);
+    }
+
+    let _ = writer.flush();
+}
+
+/// What to do with a connection once a request on it has been handled.
+enum Next {
+    KeepAlive,
+    Close,
+    /// The connection was upgraded: hand it to the callback.
+    Upgrade(Upgrade),
+}
+
+// Reads a single request from `reader` and responds to it. Returns what should
+// become of the connection.
+fn handle_request(
+    rocket: &Rocket,
+    shutdown: &Shutdown,
+    reader: &mut BufReader<&mut NetworkStream>,
+    writer: &mut Write,
+    remote: Option<SocketAddr>
+) -> Next {
+    // Enforce the header read timeout by buffering the head before Hyper sees
+    // it. Hyper would otherwise wait indefinitely for a trickling client.
+    if let Some(secs) = rocket.config.header_read_timeout {
+        match read_head(reader, Duration::from_secs(secs as u64)) {
+            Ok(()) => {},
+            Err(ref e) if is_timeout(e) => {
+                incomplete_head(rocket, reader.get_buf(), writer, BadRequestError::Timeout, remote);
+                return Next::Close;
+            }
+            Err(e) => {
+                debug!("Failed to read request head: {:?}", e);
+                return Next::Close;
+            }
+        }
+    }
+
+    // Hyper insists on a remote address. Use a placeholder when there is none.
+    // The parsed request borrows `reader` for the whole match, so a head that's
+    // too large is responded to after it.
+    let addr = remote.unwrap_or_else(|| SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0));
+    match hyper::Request::new(reader, addr) {
+        Ok(hyp_req) => return respond(rocket, shutdown, hyp_req, writer, remote),
+        Err(hyper::Error::Io(ref e)) if e.kind() == io::ErrorKind::ConnectionAborted => {
+            return Next::Close;
+        }
+        Err(hyper::Error::Io(e)) => {
+            debug!("Failed to read request: {:?}", e);
+            return Next::Close;
+        }
+        Err(hyper::Error::TooLarge) => {},
+        Err(e) => {
+            // The request isn't valid HTTP. Tell the client before hanging up.
+            bad_request(rocket, writer, BadRequestError::Malformed(e.to_string()), remote);
+            return Next::Close;
+        }
+    }
+
+    // Hyper won't parse more than 100 headers, nor a head larger than its
+    // buffer. The request line is likely intact nonetheless.
+    incomplete_head(rocket, reader.get_buf(), writer, BadRequestError::HeadersTooLarge, remote);
+    Next::Close
+}
+
+// Dispatches `hyp_req` and responds to it. Returns what should become of the
+// connection.
+fn respond(
+    rocket: &Rocket,
+    shutdown: &Shutdown,
+    hyp_req: hyper::Request,
+    writer: &mut Write,
+    remote: Option<SocketAddr>
+) -> Next {
+    // Unlike Hyper's server, we don't accept `Expect: 100-continue` here. The
+    // interim response is sent by `Data` once the body is first read.
+    let version = hyp_req.version;
+
+    // The keep-alive and header timeouts no longer apply to this request. Any
+    // further reads are of the body and are bounded by the read timeout, which
+    // `Data` enforces as a deadline for the body as a whole.
+    let read_timeout = rocket.config.read_timeout.map(|s| Duration::from_secs(s as u64));
+    if hyp_req.set_read_timeout(read_timeout).is_err() {
+        return Next::Close;
+    }
+
+    let mut keep_alive = rocket.config.keep_alive.is_some()
+        && !shutdown.is_requested()
+        && hyper::should_keep_alive(version, &hyp_req.headers);
+
+    let mut res_headers = header::Headers::new();
+    if !keep_alive {
+        res_headers.set(header::Connection::close());
+    }
+
+    let upgrade = {
+        let mut hyp_res = hyper::Response::new(writer, &mut res_headers);
+        hyp_res.version = version;
+        rocket.handle_hyper(hyp_req, hyp_res, remote)
+    };
+
+    if writer.flush().is_err() {
+        return Next::Close;
+    }
+
+    if let Some(upgrade) = upgrade {
+        return Next::Upgrade(upgrade);
+    }
+
+    // The response may have asked to close the connection.
+    if keep_alive {
+        keep_alive = hyper::should_keep_alive(version, &res_headers);
+    }
+
+    if keep_alive { Next::KeepAlive } else { Next::Close }
+}
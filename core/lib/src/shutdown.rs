@@ -0,0 +1,130 @@
+use std::sync::{Arc, Mutex, Condvar};
+
+use request::{self, FromRequest, Request};
+use outcome::Outcome;
+
+#[derive(Default)]
+struct ShutdownState {
+    requested: Mutex<bool>,
+    condvar: Condvar,
+}
+
+/// A handle used to gracefully shut down a launched Rocket application.
+///
+/// Every `Rocket` instance manages a `Shutdown` handle. It can be retrieved
+/// before launch via [`Rocket::shutdown()`], in a handler via the `Shutdown`
+/// request guard or [`State<Shutdown>`](::State), or from any structure that
+/// has access to the `Rocket` instance, such as a launch fairing. Handles are
+/// cheap to clone; all clones refer to the same application.
+///
+/// Calling [`Shutdown::notify()`] requests that the server shut down. When this
+/// happens, Rocket:
+///
+///   1. Stops accepting new connections.
+///   2. Waits for in-flight requests to complete for at most
+///      [`shutdown_grace`](::Config::shutdown_grace) seconds.
+///   3. Runs all [shutdown fairings](::fairing::Fairing::on_shutdown()).
+///   4. Returns from [`Rocket::launch()`].
+///
+/// Requesting a shutdown before launch causes the application to shut down
+/// immediately after launching. Requesting a shutdown more than once has no
+/// additional effect.
+///
+/// # Example
+///
+/// A route that shuts down the application when requested:
+///
+/// ```rust
+/// # #![feature(proc_macro_hygiene, decl_macro)]
+/// # #[macro_use] extern crate rocket;
+/// use rocket::Shutdown;
+///
+/// #[post("/shutdown")]
+/// fn shutdown(handle: Shutdown) -> &'static str {
+///     handle.notify();
+///     "Shutting down..."
+/// }
+///
+/// fn main() {
+/// # if false {
+///     let _ = rocket::ignite().mount("/", routes![shutdown]).launch();
+/// # }
+/// }
+/// ```
+///
+/// Shutting down from another thread, such as a signal handler:
+///
+/// ```rust
+/// # if false {
+/// use std::thread;
+///
+/// let rocket = rocket::ignite();
+/// let handle = rocket.shutdown();
+///
+/// thread::spawn(move || {
+///     // wait for some condition, then...
+///     handle.notify();
+/// });
+///
+/// rocket.launch().expect("clean shutdown");
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct Shutdown(Arc<ShutdownState>);
+
+impl Shutdown {
+    #[inline]
+    crate fn new() -> Shutdown {
+        Shutdown::default()
+    }
+
+    /// Requests that the Rocket application using this handle shut down
+    /// gracefully. This method returns immediately; it does not wait for the
+    /// shutdown to complete.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let rocket = rocket::ignite();
+    /// let handle = rocket.shutdown();
+    ///
+    /// assert!(!handle.is_requested());
+    /// handle.notify();
+    /// assert!(handle.is_requested());
+    /// ```
+    pub fn notify(&self) {
+        let mut requested = self.0.requested.lock().expect("shutdown lock");
+        *requested = true;
+        self.0.condvar.notify_all();
+    }
+
+    /// Returns `true` if a shutdown has been requested via
+    /// [`Shutdown::notify()`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let handle = rocket::ignite().shutdown();
+    /// assert!(!handle.is_requested());
+    /// ```
+    pub fn is_requested(&self) -> bool {
+        *self.0.requested.lock().expect("shutdown lock")
+    }
+
+    /// Blocks the current thread until a shutdown is requested.
+    crate fn wait(&self) {
+        let mut requested = self.0.requested.lock().expect("shutdown lock");
+        while !*requested {
+            requested = self.0.condvar.wait(requested).expect("shutdown lock");
+        }
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for Shutdown {
+    type Error = !;
+
+    #[inline]
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        Outcome::Success(request.state.managed.get::<Shutdown>().clone())
+    }
+}
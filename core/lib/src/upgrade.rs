@@ -0,0 +1,96 @@
+use std::io::{self, Read, Write, Cursor};
+use std::net::{SocketAddr, Shutdown};
+use std::time::Duration;
+
+use data::NetStream;
+use http::hyper::net::NetworkStream;
+
+/// A callback that takes over a connection once the response that upgraded it
+/// has been written.
+crate type Upgrade = Box<FnOnce(Upgraded) + Send>;
+
+/// A connection that was taken over from HTTP via a protocol upgrade.
+///
+/// An `Upgraded` connection is handed to the callback set via
+/// [`Response::set_upgrade()`](::Response::set_upgrade()) once the response
+/// head has been written. It is the raw, bidirectional stream to the client:
+/// for TLS connections, reads and writes go through the TLS session.
+///
+/// Bytes the client sent after its request but before the upgrade completed
+/// are returned by the first reads.
+///
+/// The callback runs on a thread of its own, not on the worker that served the
+/// request, and may run for as long as the connection stays open. When it
+/// returns or panics, the connection is closed, even if clones of it created
+/// via [`Upgraded::try_clone()`] remain.
+pub struct Upgraded {
+    buffered: Cursor<Vec<u8>>,
+    stream: NetStream,
+}
+
+impl Upgraded {
+    #[inline]
+    crate fn new(buffered: Vec<u8>, stream: NetStream) -> Upgraded {
+        Upgraded { buffered: Cursor::new(buffered), stream }
+    }
+
+    /// Returns a new handle to the same connection. Bytes that were buffered
+    /// before the upgrade are only read through `self`. This is useful for
+    /// reading from and writing to the connection on separate threads, over
+    /// TLS as well: a read that is waiting for the client doesn't hold up
+    /// writes through another handle.
+    #[inline]
+    pub fn try_clone(&self) -> io::Result<Upgraded> {
+        Ok(Upgraded::new(vec![], self.stream.clone()))
+    }
+
+    /// Returns the address of the remote peer, if there is one. Connections
+    /// over Unix domain sockets don't have a remote address.
+    #[inline]
+    pub fn peer_addr(&mut self) -> io::Result<SocketAddr> {
+        self.stream.peer_addr()
+    }
+
+    /// Sets the read timeout of the connection. By default, reads never time
+    /// out.
+    #[inline]
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.stream.set_read_timeout(timeout)
+    }
+
+    /// Sets the write timeout of the connection. It defaults to the configured
+    /// `write_timeout`.
+    #[inline]
+    pub fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.stream.set_write_timeout(timeout)
+    }
+
+    /// Shuts down the read half, the write half, or both halves of the
+    /// connection.
+    #[inline]
+    pub fn shutdown(&mut self, how: Shutdown) -> io::Result<()> {
+        self.stream.close(how)
+    }
+}
+
+impl Read for Upgraded {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if (self.buffered.position() as usize) < self.buffered.get_ref().len() {
+            return self.buffered.read(buf);
+        }
+
+        self.stream.read(buf)
+    }
+}
+
+impl Write for Upgraded {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
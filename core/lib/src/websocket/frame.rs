@@ -0,0 +1,171 @@
+use std::io::{self, Read, Write};
+
+// Opcodes. (RFC 6455, Section 5.2)
+crate const CONTINUATION: u8 = 0x0;
+crate const TEXT: u8 = 0x1;
+crate const BINARY: u8 = 0x2;
+crate const CLOSE: u8 = 0x8;
+crate const PING: u8 = 0x9;
+crate const PONG: u8 = 0xa;
+
+// Close codes. (RFC 6455, Section 7.4.1)
+crate const NORMAL: u16 = 1000;
+crate const PROTOCOL_ERROR: u16 = 1002;
+crate const INVALID_DATA: u16 = 1007;
+crate const TOO_BIG: u16 = 1009;
+
+/// The largest payload a control frame may carry.
+crate const MAX_CONTROL_PAYLOAD: usize = 125;
+
+/// An error that ends a WebSocket connection.
+#[derive(Debug)]
+crate enum Error {
+    /// Reading from or writing to the network failed.
+    Io(io::Error),
+    /// The client violated the protocol. Carries the close code to send to
+    /// the client and a description of the violation.
+    Protocol(u16, &'static str),
+}
+
+impl From<io::Error> for Error {
+    #[inline(always)]
+    fn from(error: io::Error) -> Error {
+        Error::Io(error)
+    }
+}
+
+/// A single, unmasked frame, as read from a client.
+crate struct Frame {
+    crate fin: bool,
+    crate opcode: u8,
+    crate payload: Vec<u8>,
+}
+
+impl Frame {
+    #[inline(always)]
+    crate fn is_control(&self) -> bool {
+        self.opcode & 0x8 != 0
+    }
+
+    /// Reads a frame sent by a client from `reader` and unmasks its payload.
+    /// Fails with a `TOO_BIG` error if the payload is larger than `max_size`.
+    crate fn read<R: Read>(reader: &mut R, max_size: u64) -> Result<Frame, Error> {
+        let mut head = [0u8; 2];
+        reader.read_exact(&mut head)?;
+
+        let (fin, opcode) = (head[0] & 0x80 != 0, head[0] & 0x0f);
+        if head[0] & 0x70 != 0 {
+            return Err(Error::Protocol(PROTOCOL_ERROR, "reserved bits set"));
+        }
+
+        match opcode {
+            CONTINUATION | TEXT | BINARY | CLOSE | PING | PONG => {},
+            _ => return Err(Error::Protocol(PROTOCOL_ERROR, "unknown opcode")),
+        }
+
+        if head[1] & 0x80 == 0 {
+            return Err(Error::Protocol(PROTOCOL_ERROR, "unmasked client frame"));
+        }
+
+        let len = match head[1] & 0x7f {
+            126 => read_uint(reader, 2)?,
+            127 => read_uint(reader, 8)?,
+            n => n as u64,
+        };
+
+        if opcode & 0x8 != 0 && (!fin || len > MAX_CONTROL_PAYLOAD as u64) {
+            return Err(Error::Protocol(PROTOCOL_ERROR, "fragmented or oversized control frame"));
+        }
+
+        if len > max_size {
+            return Err(Error::Protocol(TOO_BIG, "message exceeds maximum size"));
+        }
+
+        let mut mask = [0u8; 4];
+        reader.read_exact(&mut mask)?;
+
+        let mut payload = vec![0; len as usize];
+        reader.read_exact(&mut payload)?;
+        for (i, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[i % 4];
+        }
+
+        Ok(Frame { fin, opcode, payload })
+    }
+}
+
+/// Reads a big-endian unsigned integer that is `n` bytes long.
+fn read_uint<R: Read>(reader: &mut R, n: usize) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes[(8 - n)..])?;
+    Ok(bytes.iter().fold(0, |value, &byte| value << 8 | byte as u64))
+}
+
+/// Writes a single, unfragmented, unmasked frame to `writer`.
+crate fn write<W: Write>(writer: &mut W, opcode: u8, payload: &[u8]) -> io::Result<()> {
+    let len = payload.len() as u64;
+    let mut head = vec![0x80 | opcode];
+    if len < 126 {
+        head.push(len as u8);
+    } else if len <= u16::max_value() as u64 {
+        head.push(126);
+        head.extend_from_slice(&[(len >> 8) as u8, len as u8]);
+    } else {
+        head.push(127);
+        head.extend((0..8).rev().map(|i| (len >> (i * 8)) as u8));
+    }
+
+    writer.write_all(&head)?;
+    writer.write_all(payload)?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn masked(first: u8, len: &[u8], payload: &[u8]) -> Vec<u8> {
+        let mask = [0x37, 0xfa, 0x21, 0x3d];
+        let mut bytes = vec![first, 0x80 | len[0]];
+        bytes.extend_from_slice(&len[1..]);
+        bytes.extend_from_slice(&mask);
+        bytes.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        bytes
+    }
+
+    #[test]
+    fn reads_masked_frames() {
+        // The single-frame masked text message from RFC 6455, Section 5.7.
+        let bytes = [0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58];
+        let frame = Frame::read(&mut &bytes[..], 1024).unwrap();
+        assert!(frame.fin && !frame.is_control());
+        assert_eq!((frame.opcode, &*frame.payload), (TEXT, &b"Hello"[..]));
+
+        let payload = vec![7; 300];
+        let bytes = masked(0x02, &[126, 1, 44], &payload);
+        let frame = Frame::read(&mut &bytes[..], 1024).unwrap();
+        assert!(!frame.fin);
+        assert_eq!((frame.opcode, frame.payload), (BINARY, payload));
+        assert!(Frame::read(&mut &bytes[..], 299).is_err());
+
+        // Unmasked, reserved bits, and fragmented control frames are invalid.
+        assert!(Frame::read(&mut &[0x81, 0x00][..], 1024).is_err());
+        assert!(Frame::read(&mut &masked(0xc1, &[0], &[])[..], 1024).is_err());
+        assert!(Frame::read(&mut &masked(0x09, &[0], &[])[..], 1024).is_err());
+    }
+
+    #[test]
+    fn writes_unmasked_frames() {
+        let mut bytes = vec![];
+        write(&mut bytes, TEXT, b"Hello").unwrap();
+        assert_eq!(bytes, [0x81, 0x05, 0x48, 0x65, 0x6c, 0x6c, 0x6f]);
+
+        let mut bytes = vec![];
+        write(&mut bytes, BINARY, &[0; 256]).unwrap();
+        assert_eq!(&bytes[..4], &[0x82, 126, 1, 0]);
+
+        let mut bytes = vec![];
+        write(&mut bytes, BINARY, &[0; 65536]).unwrap();
+        assert_eq!(&bytes[..10], &[0x82, 127, 0, 0, 0, 0, 0, 1, 0, 0]);
+    }
+}
@@ -0,0 +1,201 @@
+//! WebSocket support: upgrading requests and exchanging messages.
+//!
+//! A route that accepts WebSocket connections takes a [`WebSocket`] request
+//! guard and returns the [`Channel`] created by [`WebSocket::channel()`]. The
+//! handler passed to `channel()` runs once the `101 Switching Protocols`
+//! response has been written and is handed a [`Stream`] of [`Message`]s:
+//!
+//! ```rust
+//! # #![feature(proc_macro_hygiene, decl_macro)]
+//! # #[macro_use] extern crate rocket;
+//! use rocket::websocket::{WebSocket, Channel, Message};
+//!
+//! #[get("/echo")]
+//! fn echo(ws: WebSocket) -> Channel {
+//!     ws.channel(|mut stream| {
+//!         while let Ok(Some(message)) = stream.recv() {
+//!             match message {
+//!                 Message::Text(_) | Message::Binary(_) => {
+//!                     if stream.send(message).is_err() {
+//!                         break;
+//!                     }
+//!                 }
+//!                 _ => continue,
+//!             }
+//!         }
+//!     })
+//! }
+//! # fn main() {  }
+//! ```
+//!
+//! Requests that aren't WebSocket upgrade requests are forwarded by the guard,
+//! so a route with the same path can serve regular requests. Malformed
+//! handshakes fail and are handled by the appropriate catcher, as are requests
+//! whose route fails for any other reason. Fairings see the upgrade request and
+//! its response as usual; if a fairing changes the status of the response to
+//! something other than `101`, the connection is not upgraded.
+//!
+//! The handler runs on a thread of its own rather than on one of the workers,
+//! so long-lived connections don't hold up other requests. When the handler
+//! returns, the connection is closed. Messages can be sent from other threads
+//! via a [`Sender`], obtained from [`Stream::sender()`], while the handler
+//! receives them. WebSockets are only supported over HTTP/1.1.
+//!
+//! # Limits
+//!
+//! The maximum size of a message, reassembled from its fragments, is
+//! determined by the `websocket` limit and defaults to 1MiB. Larger messages
+//! close the connection. The limit can be increased by setting the
+//! `limits.websocket` configuration parameter:
+//!
+//! ```toml
+//! [global.limits]
+//! websocket = 2097152
+//! ```
+
+mod frame;
+mod stream;
+
+use std::fmt;
+
+use base64;
+use sha1::Sha1;
+
+use outcome::Outcome;
+use request::{self, FromRequest, Request};
+use response::{self, Responder, Response};
+use http::Status;
+
+pub use self::stream::{Stream, Sender};
+
+/// The default maximum size of a message: 1MiB.
+const DEFAULT_MAX_SIZE: u64 = 1 << 20;
+
+/// Appended to the client's key to compute `Sec-WebSocket-Accept`.
+const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// A WebSocket message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    /// A text message.
+    Text(String),
+    /// A binary message.
+    Binary(Vec<u8>),
+    /// A ping. Pings received from the client are answered automatically.
+    Ping(Vec<u8>),
+    /// A pong, either in response to a ping or unsolicited.
+    Pong(Vec<u8>),
+    /// A close message with an optional status code and reason.
+    Close(Option<(u16, String)>),
+}
+
+/// An error in a WebSocket opening handshake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandshakeError {
+    /// The `Connection` header doesn't contain the `Upgrade` option.
+    NotAnUpgrade,
+    /// The `Sec-WebSocket-Key` header is missing or isn't a base64 encoded
+    /// 16-byte value.
+    BadKey,
+    /// The `Sec-WebSocket-Version` header is missing or isn't `13`.
+    UnsupportedVersion,
+}
+
+impl fmt::Display for HandshakeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HandshakeError::NotAnUpgrade => write!(f, "missing Upgrade connection option"),
+            HandshakeError::BadKey => write!(f, "invalid Sec-WebSocket-Key"),
+            HandshakeError::UnsupportedVersion => write!(f, "unsupported WebSocket version"),
+        }
+    }
+}
+
+/// A request guard for WebSocket upgrade requests.
+///
+/// The guard forwards requests without an `Upgrade: websocket` header. It
+/// fails with a status of `400 Bad Request` if the opening handshake is
+/// otherwise malformed and with `426 Upgrade Required` if the client requested
+/// a version of the protocol other than 13. (RFC 6455, Section 4.2.1)
+///
+/// See the [module level documentation](::websocket) for an example.
+#[derive(Debug)]
+pub struct WebSocket {
+    accept: String,
+    max_size: u64,
+}
+
+impl WebSocket {
+    /// Accepts the upgrade, returning a `Channel` that responds with `101
+    /// Switching Protocols` and then calls `handler` with the connection.
+    pub fn channel<F>(self, handler: F) -> Channel
+        where F: FnOnce(Stream) + Send + 'static
+    {
+        Channel { accept: self.accept, max_size: self.max_size, handler: Box::new(handler) }
+    }
+}
+
+// Returns `true` if the comma-separated header `value` contains `token`.
+fn has_token(value: &str, token: &str) -> bool {
+    value.split(',').any(|t| t.trim().eq_ignore_ascii_case(token))
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for WebSocket {
+    type Error = HandshakeError;
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        let headers = request.headers();
+        if !headers.get("Upgrade").any(|v| has_token(v, "websocket")) {
+            return Outcome::Forward(());
+        }
+
+        if !headers.get("Connection").any(|v| has_token(v, "Upgrade")) {
+            return Outcome::Failure((Status::BadRequest, HandshakeError::NotAnUpgrade));
+        }
+
+        if headers.get_one("Sec-WebSocket-Version").map(str::trim) != Some("13") {
+            let error = HandshakeError::UnsupportedVersion;
+            return Outcome::Failure((Status::UpgradeRequired, error));
+        }
+
+        let key = match headers.get_one("Sec-WebSocket-Key").map(str::trim) {
+            Some(key) if base64::decode(key).map(|k| k.len() == 16).unwrap_or(false) => key,
+            _ => return Outcome::Failure((Status::BadRequest, HandshakeError::BadKey)),
+        };
+
+        let digest = Sha1::from(format!("{}{}", key, GUID)).digest().bytes();
+        let max_size = request.limits().get("websocket").unwrap_or(DEFAULT_MAX_SIZE);
+        Outcome::Success(WebSocket { accept: base64::encode(&digest), max_size })
+    }
+}
+
+/// Completes a WebSocket upgrade. Created by [`WebSocket::channel()`].
+pub struct Channel {
+    accept: String,
+    max_size: u64,
+    handler: Box<FnOnce(Stream) + Send>,
+}
+
+impl<'r> Responder<'r> for Channel {
+    fn respond_to(self, _: &Request) -> response::Result<'r> {
+        let mut response = Response::build()
+            .status(Status::SwitchingProtocols)
+            .raw_header("Upgrade", "websocket")
+            .raw_header("Connection", "Upgrade")
+            .raw_header("Sec-WebSocket-Accept", self.accept)
+            .finalize();
+
+        let (handler, max_size) = (self.handler, self.max_size);
+        response.set_upgrade(move |io| match Stream::new(io, max_size) {
+            Ok(stream) => handler(stream),
+            Err(e) => error_!("Failed to set up WebSocket stream: {}", e),
+        });
+        Ok(response)
+    }
+}
+
+impl fmt::Debug for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Channel").field("accept", &self.accept).finish()
+    }
+}
@@ -0,0 +1,245 @@
+use std::io;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use upgrade::Upgraded;
+
+use super::Message;
+use super::frame::{self, Frame, Error};
+
+/// A message-oriented WebSocket connection.
+///
+/// A `Stream` is handed to the handler passed to
+/// [`WebSocket::channel()`](::websocket::WebSocket::channel()) once the
+/// connection has been upgraded. Messages are received via
+/// [`recv()`](Stream::recv()) and sent via [`send()`](Stream::send()).
+///
+/// Fragmented messages are reassembled before they are returned. Pings are
+/// answered automatically, and a close frame from the client is echoed back,
+/// as RFC 6455 requires. When the handler returns, the connection is closed.
+///
+/// To send messages from other threads while receiving on this one, use a
+/// [`Sender`] obtained via [`sender()`](Stream::sender()).
+pub struct Stream {
+    io: Upgraded,
+    max_size: u64,
+    partial: Option<(u8, Vec<u8>)>,
+    received_close: bool,
+    sender: Sender,
+}
+
+impl Stream {
+    #[inline]
+    crate fn new(io: Upgraded, max_size: u64) -> io::Result<Stream> {
+        let writer = Writer { io: io.try_clone()?, sent_close: false };
+        let sender = Sender { writer: Arc::new(Mutex::new(writer)) };
+        Ok(Stream { io, max_size, partial: None, received_close: false, sender })
+    }
+
+    /// Returns a handle for sending messages on this connection. The handle
+    /// can be cloned and sent to other threads. Messages sent via any handle
+    /// or via `self` are never interleaved.
+    #[inline]
+    pub fn sender(&self) -> Sender {
+        self.sender.clone()
+    }
+
+    /// Receives the next message from the client.
+    ///
+    /// Returns `Ok(None)` once the client has closed the connection; the
+    /// `Message::Close` it sent is returned before that. Messages that exceed
+    /// the `websocket` limit, invalid UTF-8 in text messages, and other
+    /// protocol violations close the connection with the appropriate code and
+    /// result in an error of kind `InvalidData`.
+    pub fn recv(&mut self) -> io::Result<Option<Message>> {
+        loop {
+            if self.received_close {
+                return Ok(None);
+            }
+
+            match self.next_message() {
+                Ok(Some(message)) => return Ok(Some(message)),
+                Ok(None) => continue,
+                Err(Error::Io(e)) => return Err(e),
+                Err(Error::Protocol(code, reason)) => {
+                    debug!("WebSocket protocol error: {}", reason);
+                    self.received_close = true;
+                    let _ = self.sender.reply(Message::Close(Some((code, String::new()))));
+
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, reason));
+                }
+            }
+        }
+    }
+
+    // Reads the next frame and returns the message it completes, if any.
+    fn next_message(&mut self) -> Result<Option<Message>, Error> {
+        let buffered = self.partial.as_ref().map_or(0, |(_, data)| data.len() as u64);
+        let frame = Frame::read(&mut self.io, self.max_size - buffered)?;
+        if frame.is_control() {
+            return self.control(frame).map(Some);
+        }
+
+        let (opcode, data) = match (self.partial.take(), frame.opcode) {
+            (None, frame::CONTINUATION) => {
+                return Err(Error::Protocol(frame::PROTOCOL_ERROR, "unexpected continuation"));
+            }
+            (Some(_), frame::TEXT) | (Some(_), frame::BINARY) => {
+                return Err(Error::Protocol(frame::PROTOCOL_ERROR, "expected continuation"));
+            }
+            (Some((opcode, mut data)), _) => {
+                data.extend_from_slice(&frame.payload);
+                (opcode, data)
+            }
+            (None, opcode) => (opcode, frame.payload),
+        };
+
+        if !frame.fin {
+            self.partial = Some((opcode, data));
+            return Ok(None);
+        }
+
+        match opcode {
+            frame::TEXT => match String::from_utf8(data) {
+                Ok(text) => Ok(Some(Message::Text(text))),
+                Err(_) => Err(Error::Protocol(frame::INVALID_DATA, "invalid UTF-8 in text")),
+            },
+            _ => Ok(Some(Message::Binary(data))),
+        }
+    }
+
+    // Handles a control frame and returns the corresponding message.
+    fn control(&mut self, frame: Frame) -> Result<Message, Error> {
+        match frame.opcode {
+            frame::PING => {
+                self.sender.reply(Message::Pong(frame.payload.clone()))?;
+                Ok(Message::Ping(frame.payload))
+            }
+            frame::PONG => Ok(Message::Pong(frame.payload)),
+            _ => {
+                let close = match frame.payload.len() {
+                    0 => None,
+                    1 => return Err(Error::Protocol(frame::PROTOCOL_ERROR, "invalid close")),
+                    _ => {
+                        let code = (frame.payload[0] as u16) << 8 | frame.payload[1] as u16;
+                        match String::from_utf8(frame.payload[2..].to_vec()) {
+                            Ok(reason) => Some((code, reason)),
+                            Err(_) => {
+                                let reason = "invalid UTF-8 in close reason";
+                                return Err(Error::Protocol(frame::INVALID_DATA, reason));
+                            }
+                        }
+                    }
+                };
+
+                self.received_close = true;
+                let code = close.as_ref().map_or(frame::NORMAL, |&(code, _)| code);
+                self.sender.reply(Message::Close(Some((code, String::new()))))?;
+
+                Ok(Message::Close(close))
+            }
+        }
+    }
+
+    /// Sends `message` to the client.
+    ///
+    /// Fails with an error of kind `InvalidInput` if `message` is a control
+    /// message (ping, pong, or close) with a payload larger than 125 bytes,
+    /// and of kind `NotConnected` if a close message was already sent.
+    #[inline]
+    pub fn send(&mut self, message: Message) -> io::Result<()> {
+        self.sender.send(message)
+    }
+
+    /// Starts closing the connection by sending a close message with `code`
+    /// and `reason`. The client's acknowledgement is returned by a subsequent
+    /// call to [`recv()`](Stream::recv()).
+    #[inline]
+    pub fn close(&mut self, code: u16, reason: &str) -> io::Result<()> {
+        self.sender.close(code, reason)
+    }
+
+    /// Sets the read timeout of the underlying connection. By default, reads
+    /// never time out.
+    #[inline]
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.io.set_read_timeout(timeout)
+    }
+}
+
+/// A cloneable handle for sending messages on a WebSocket connection.
+///
+/// A `Sender` is obtained via [`Stream::sender()`]. It can be moved to other
+/// threads to send messages while the handler receives them on its own. Once
+/// the handler returns, the connection is closed and sending fails.
+#[derive(Clone)]
+pub struct Sender {
+    writer: Arc<Mutex<Writer>>,
+}
+
+// The write half of a connection, shared by a `Stream` and its `Sender`s.
+struct Writer {
+    io: Upgraded,
+    sent_close: bool,
+}
+
+impl Sender {
+    #[inline]
+    fn lock(&self) -> MutexGuard<Writer> {
+        self.writer.lock().expect("websocket writer lock")
+    }
+
+    /// Sends `message` to the client. See [`Stream::send()`] for details.
+    pub fn send(&self, message: Message) -> io::Result<()> {
+        let (opcode, payload) = encode(message);
+        let mut writer = self.lock();
+        if writer.sent_close {
+            let msg = "a close message was already sent";
+            return Err(io::Error::new(io::ErrorKind::NotConnected, msg));
+        }
+
+        if opcode & 0x8 != 0 && payload.len() > frame::MAX_CONTROL_PAYLOAD {
+            let msg = "control message payload exceeds 125 bytes";
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, msg));
+        }
+
+        writer.sent_close = opcode == frame::CLOSE;
+        frame::write(&mut writer.io, opcode, &payload)
+    }
+
+    /// Starts closing the connection by sending a close message with `code`
+    /// and `reason`. See [`Stream::close()`] for details.
+    #[inline]
+    pub fn close(&self, code: u16, reason: &str) -> io::Result<()> {
+        self.send(Message::Close(Some((code, reason.into()))))
+    }
+
+    // Sends the automatic reply to a control frame unless a close message was
+    // already sent.
+    fn reply(&self, message: Message) -> io::Result<()> {
+        let (opcode, payload) = encode(message);
+        let mut writer = self.lock();
+        if writer.sent_close {
+            return Ok(());
+        }
+
+        writer.sent_close = opcode == frame::CLOSE;
+        frame::write(&mut writer.io, opcode, &payload)
+    }
+}
+
+// Returns the opcode and payload of the frame that carries `message`.
+fn encode(message: Message) -> (u8, Vec<u8>) {
+    match message {
+        Message::Text(text) => (frame::TEXT, text.into_bytes()),
+        Message::Binary(data) => (frame::BINARY, data),
+        Message::Ping(data) => (frame::PING, data),
+        Message::Pong(data) => (frame::PONG, data),
+        Message::Close(None) => (frame::CLOSE, vec![]),
+        Message::Close(Some((code, reason))) => {
+            let mut payload = vec![(code >> 8) as u8, code as u8];
+            payload.extend_from_slice(reason.as_bytes());
+            (frame::CLOSE, payload)
+        }
+    }
+}
@@ -0,0 +1,87 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+#[macro_use] extern crate rocket;
+
+use rocket::{Saturation, Shutdown};
+
+#[get("/")]
+fn load(saturation: Saturation) -> String {
+    format!("{} {}", saturation.busy(), saturation.rejected())
+}
+
+#[post("/shutdown")]
+fn shutdown(handle: Shutdown) -> &'static str {
+    handle.notify();
+    "Shutting down."
+}
+
+mod backpressure_tests {
+    use super::*;
+
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+    use std::time::Duration;
+
+    use rocket::config::{Config, Environment};
+
+    // Connections only reach a worker once their request starts arriving.
+    const PARTIAL: &[u8] = b"GET / HTTP/1.1\r\n";
+
+    fn request(port: u16, request: &str) -> String {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    fn wait_until<F: Fn() -> bool>(condition: F) {
+        while !condition() {
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn rejects_with_503_past_max_pending() {
+        let config = Config::build(Environment::Development)
+            .workers(1)
+            .max_pending(1)
+            .finalize()
+            .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let rocket = rocket::custom(config).mount("/", routes![load, shutdown]);
+        let saturation = rocket.saturation();
+        let server = thread::spawn(move || rocket.launch_on(listener).is_ok());
+
+        // Occupy the only worker, then fill the queue of pending connections.
+        let mut busy = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        busy.write_all(PARTIAL).unwrap();
+        wait_until(|| saturation.busy() == 1);
+        let mut pending = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        pending.write_all(PARTIAL).unwrap();
+        wait_until(|| saturation.pending() == 1);
+        assert!(saturation.is_saturated());
+
+        let response = request(port, "GET / HTTP/1.1\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 503 Service Unavailable"));
+        assert!(response.contains("Retry-After: 1\r\n"));
+        assert!(response.contains("Connection: close\r\n"));
+        assert_eq!(saturation.rejected(), 1);
+
+        // Free up the worker. Requests are served normally again.
+        drop(busy);
+        drop(pending);
+        wait_until(|| saturation.busy() == 0 && saturation.pending() == 0);
+
+        let response = request(port, "GET / HTTP/1.1\r\nConnection: close\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("1 1"));
+
+        request(port, "POST /shutdown HTTP/1.1\r\nContent-Length: 0\r\n\r\n");
+        assert!(server.join().unwrap());
+    }
+}
@@ -0,0 +1,96 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+#[macro_use] extern crate rocket;
+
+use std::io::Cursor;
+
+use rocket::{Response, Shutdown};
+use rocket::http::Status;
+use rocket::request::{BadRequestInfo, BadRequestError};
+
+#[post("/shutdown")]
+fn shutdown(handle: Shutdown) -> &'static str {
+    handle.notify();
+    "Shutting down."
+}
+
+fn bad_request(info: &BadRequestInfo) -> Response<'static> {
+    let kind = match *info.error() {
+        BadRequestError::Malformed(_) => "malformed",
+        BadRequestError::UnknownMethod => "method",
+        BadRequestError::UnsupportedUri => "unsupported",
+        BadRequestError::InvalidUri(_) => "invalid",
+        BadRequestError::Timeout => "timeout",
+        BadRequestError::HeadersTooLarge => "too large",
+    };
+
+    let uri = info.uri().map(|uri| String::from_utf8_lossy(uri).into_owned());
+    let body = format!("{} {:?} {:?} {}", kind, info.method(), uri, info.remote().is_some());
+    Response::build()
+        .status(Status::BadRequest)
+        .sized_body(Cursor::new(body))
+        .finalize()
+}
+
+mod bad_request_handler_tests {
+    use super::*;
+
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    // Extension methods are routed, but not if their name is this long.
+    const LONG_METHOD: &str = "FOO-EXTENSION-METHOD-TOO-LONG-TO-KEEP";
+
+    fn request(port: u16, request: &str) -> String {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn bad_requests_reach_handler() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let rocket = rocket::ignite()
+            .mount("/", routes![shutdown])
+            .on_bad_request(bad_request);
+
+        let server = thread::spawn(move || rocket.launch_on(listener).is_ok());
+
+        let response = request(port, &format!("{} / HTTP/1.1\r\n\r\n", LONG_METHOD));
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+        let expected = format!(r#"method Some("{}") Some("/") true"#, LONG_METHOD);
+        assert!(response.ends_with(&expected));
+
+        let response = request(port, "GET http://rocket.rs/ HTTP/1.1\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+        assert!(response.ends_with(r#"unsupported Some("GET") Some("http://rocket.rs/") true"#));
+
+        let response = request(port, "NOT HTTP AT ALL\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+        assert!(response.ends_with("malformed None None true"));
+
+        request(port, "POST /shutdown HTTP/1.1\r\nContent-Length: 0\r\n\r\n");
+        assert!(server.join().unwrap());
+    }
+
+    #[test]
+    fn default_handler_responds_400() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let rocket = rocket::ignite().mount("/", routes![shutdown]);
+        let server = thread::spawn(move || rocket.launch_on(listener).is_ok());
+
+        let response = request(port, &format!("{} / HTTP/1.1\r\n\r\n", LONG_METHOD));
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+        assert!(response.contains("Connection: close"));
+        assert!(response.contains("400: Bad Request"));
+
+        request(port, "POST /shutdown HTTP/1.1\r\nContent-Length: 0\r\n\r\n");
+        assert!(server.join().unwrap());
+    }
+}
@@ -0,0 +1,82 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+#[macro_use] extern crate rocket;
+
+use rocket::Request;
+use rocket::request::{self, FromRequest};
+
+struct Explosive;
+
+impl<'a, 'r> FromRequest<'a, 'r> for Explosive {
+    type Error = ();
+
+    fn from_request(_: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        panic!("guard exploded")
+    }
+}
+
+#[get("/handler")]
+fn handler() -> &'static str {
+    panic!(String::from("handler exploded"))
+}
+
+#[get("/guard")]
+fn guard(_explosive: Explosive) -> &'static str {
+    "unreachable"
+}
+
+#[get("/fine")]
+fn fine() -> &'static str {
+    "fine"
+}
+
+#[catch(500)]
+fn internal_error() -> &'static str {
+    "caught"
+}
+
+mod catch_panic_tests {
+    use super::*;
+
+    use std::sync::{Arc, Mutex};
+
+    use rocket::fairing::AdHoc;
+    use rocket::local::Client;
+    use rocket::http::Status;
+
+    #[test]
+    fn panics_become_500s_and_reach_fairings() {
+        let reports = Arc::new(Mutex::new(vec![]));
+        let fairing_reports = reports.clone();
+        let rocket = rocket::ignite()
+            .mount("/", routes![handler, guard, fine])
+            .register(catchers![internal_error])
+            .attach(AdHoc::on_panic("Reporter", move |req, payload| {
+                let message = payload.downcast_ref::<&str>().map(|s| s.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned());
+
+                let report = format!("{} {:?}", req.uri(), message);
+                fairing_reports.lock().unwrap().push(report);
+            }));
+
+        let client = Client::new(rocket).unwrap();
+
+        let mut response = client.get("/handler").dispatch();
+        assert_eq!(response.status(), Status::InternalServerError);
+        assert_eq!(response.body_string(), Some("caught".into()));
+
+        let mut response = client.get("/guard").dispatch();
+        assert_eq!(response.status(), Status::InternalServerError);
+        assert_eq!(response.body_string(), Some("caught".into()));
+
+        // Requests continue to be handled normally afterwards.
+        let mut response = client.get("/fine").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some("fine".into()));
+
+        assert_eq!(*reports.lock().unwrap(), vec![
+            r#"/handler Some("handler exploded")"#.to_string(),
+            r#"/guard Some("guard exploded")"#.to_string(),
+        ]);
+    }
+}
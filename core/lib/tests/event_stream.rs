@@ -0,0 +1,111 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+#[macro_use] extern crate rocket;
+
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+use rocket::{State, Shutdown};
+use rocket::request::LastEventId;
+use rocket::response::{Event, EventStream};
+
+type Events = Mutex<Option<Receiver<Event>>>;
+
+#[get("/count")]
+fn count(last: Option<LastEventId>) -> EventStream<impl Iterator<Item = Event>> {
+    let start = last.and_then(|id| id.parse::<usize>().ok()).map_or(1, |id| id + 1);
+    EventStream::from((start..4).map(|i| Event::data(format!("{}\n!", i)).with_id(i.to_string())))
+}
+
+#[get("/live")]
+fn live(events: State<Events>) -> Option<EventStream<mpsc::IntoIter<Event>>> {
+    let receiver = events.lock().unwrap().take()?;
+    Some(EventStream::from(receiver).heartbeat(Duration::from_millis(50)))
+}
+
+#[post("/shutdown")]
+fn shutdown(handle: Shutdown) -> &'static str {
+    handle.notify();
+    "Shutting down."
+}
+
+mod event_stream_tests {
+    use super::*;
+
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    use rocket::local::Client;
+    use rocket::http::{ContentType, Header};
+
+    #[test]
+    fn events_are_encoded() {
+        let client = Client::new(rocket::ignite().mount("/", routes![count])).unwrap();
+        let mut response = client.get("/count").dispatch();
+        assert_eq!(response.content_type(), Some(ContentType::EventStream));
+        assert_eq!(response.headers().get_one("Cache-Control"), Some("no-cache"));
+        assert_eq!(response.body_string().unwrap(),
+            "id: 1\ndata: 1\ndata: !\n\nid: 2\ndata: 2\ndata: !\n\nid: 3\ndata: 3\ndata: !\n\n");
+
+        let mut response = client.get("/count")
+            .header(Header::new("Last-Event-ID", "2"))
+            .dispatch();
+
+        assert_eq!(response.body_string().unwrap(), "id: 3\ndata: 3\ndata: !\n\n");
+    }
+
+    // Reads from `stream` until `expected` has been received.
+    fn read_until(stream: &mut TcpStream, expected: &str) -> String {
+        let mut received = vec![];
+        while !String::from_utf8_lossy(&received).contains(expected) {
+            let mut byte = [0];
+            assert_eq!(stream.read(&mut byte).unwrap(), 1, "EOF before {:?}", expected);
+            received.push(byte[0]);
+        }
+
+        String::from_utf8(received).unwrap()
+    }
+
+    #[test]
+    fn events_are_flushed() {
+        let (sender, receiver) = mpsc::channel();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let rocket = rocket::ignite()
+            .mount("/", routes![live, shutdown])
+            .manage(Mutex::new(Some(receiver)));
+
+        let server = thread::spawn(move || rocket.launch_on(listener).is_ok());
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        stream.write_all(b"GET /live HTTP/1.1\r\nConnection: close\r\n\r\n").unwrap();
+
+        // Each event arrives before the next one is produced.
+        sender.send(Event::data("one")).unwrap();
+        let head = read_until(&mut stream, "data: one\n\n");
+        assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(head.contains("Content-Type: text/event-stream\r\n"));
+
+        // When nothing happens for a while, a heartbeat is sent.
+        read_until(&mut stream, ":\n\n");
+
+        sender.send(Event::data("two").with_event("update")).unwrap();
+        read_until(&mut stream, "event: update\ndata: two\n\n");
+
+        // Dropping the sender ends the stream.
+        drop(sender);
+        let mut rest = String::new();
+        stream.read_to_string(&mut rest).unwrap();
+        assert!(rest.ends_with("0\r\n\r\n"));
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(b"POST /shutdown HTTP/1.1\r\nContent-Length: 0\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(server.join().unwrap());
+    }
+}
@@ -0,0 +1,104 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+#[macro_use] extern crate rocket;
+
+use std::io::Read;
+
+use rocket::{Data, Shutdown};
+use rocket::request::{self, Request, FromRequest};
+use rocket::outcome::Outcome::*;
+use rocket::http::Status;
+
+struct Authorized;
+
+impl<'a, 'r> FromRequest<'a, 'r> for Authorized {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        match request.headers().get_one("Authorization") {
+            Some("letmein") => Success(Authorized),
+            _ => Failure((Status::Unauthorized, ()))
+        }
+    }
+}
+
+#[post("/", data = "<data>")]
+fn upload(_auth: Authorized, data: Data) -> String {
+    let expects_continue = data.expects_continue();
+    let mut body = String::new();
+    data.open().read_to_string(&mut body).unwrap();
+    format!("{} {}", expects_continue, body)
+}
+
+#[post("/shutdown")]
+fn shutdown(handle: Shutdown) -> &'static str {
+    handle.notify();
+    "Shutting down."
+}
+
+mod expect_continue_tests {
+    use super::*;
+
+    use std::io::Write;
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    // Reads from `stream` until the end of a response head.
+    fn read_head(stream: &mut TcpStream) -> String {
+        let mut head = vec![];
+        let mut byte = [0u8];
+        while !head.ends_with(b"\r\n\r\n") {
+            stream.read_exact(&mut byte).unwrap();
+            head.push(byte[0]);
+        }
+
+        String::from_utf8(head).unwrap()
+    }
+
+    #[test]
+    fn continue_is_sent_only_for_accepted_requests() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let rocket = rocket::ignite().mount("/", routes![upload, shutdown]);
+        let server = thread::spawn(move || rocket.launch_on(listener).is_ok());
+
+        // The request is accepted: the body is asked for, then read.
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(b"POST / HTTP/1.1\r\nAuthorization: letmein\r\n\
+            Content-Length: 5\r\nExpect: 100-continue\r\nConnection: close\r\n\r\n").unwrap();
+
+        assert_eq!(read_head(&mut stream), "HTTP/1.1 100 Continue\r\n\r\n");
+        stream.write_all(b"hello").unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("true hello"));
+
+        // The request is rejected: the final response is sent straight away.
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(b"POST / HTTP/1.1\r\nContent-Length: 5\r\n\
+            Expect: 100-continue\r\n\r\n").unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 401 Unauthorized"));
+        assert!(response.contains("Connection: close\r\n"));
+        assert!(!response.contains("100 Continue"));
+
+        // Without `Expect`, nothing changes.
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(b"POST / HTTP/1.1\r\nAuthorization: letmein\r\n\
+            Content-Length: 5\r\nConnection: close\r\n\r\nhello").unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("false hello"));
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(b"POST /shutdown HTTP/1.1\r\nContent-Length: 0\r\n\r\n").unwrap();
+        stream.read_to_string(&mut String::new()).unwrap();
+        assert!(server.join().unwrap());
+    }
+}
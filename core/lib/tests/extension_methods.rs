@@ -0,0 +1,63 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+#[macro_use] extern crate rocket;
+
+use rocket::{Request, Data, Route, Shutdown};
+use rocket::handler::Outcome;
+use rocket::http::Method;
+
+fn echo_method<'r>(req: &'r Request, _: Data) -> Outcome<'r> {
+    Outcome::from(req, req.method().to_string())
+}
+
+#[post("/shutdown")]
+fn shutdown(handle: Shutdown) -> &'static str {
+    handle.notify();
+    "Shutting down."
+}
+
+mod extension_methods_tests {
+    use super::*;
+
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    fn request(port: u16, request: &str) -> String {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn extension_methods_are_routed() {
+        let propfind: Method = "PROPFIND".parse().unwrap();
+        let purge: Method = "PURGE".parse().unwrap();
+        let routes = vec![
+            Route::new(propfind, "/dav", echo_method),
+            Route::new(purge, "/cache/<path..>", echo_method),
+        ];
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let rocket = rocket::ignite().mount("/", routes).mount("/", routes![shutdown]);
+        let server = thread::spawn(move || rocket.launch_on(listener).is_ok());
+
+        let response = request(port, "PROPFIND /dav HTTP/1.1\r\nConnection: close\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with("\r\n\r\nPROPFIND"));
+
+        // Like standard methods, extension methods are matched case-insensitively.
+        let response = request(port, "purge /cache/a/b HTTP/1.1\r\nConnection: close\r\n\r\n");
+        assert!(response.ends_with("\r\n\r\nPURGE"));
+
+        let response = request(port, "MKCOL /dav HTTP/1.1\r\nConnection: close\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+        request(port, "POST /shutdown HTTP/1.1\r\nContent-Length: 0\r\n\r\n");
+        assert!(server.join().unwrap());
+    }
+}
@@ -0,0 +1,122 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+#[macro_use] extern crate rocket;
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Cursor, Seek, SeekFrom};
+use std::path::PathBuf;
+
+use rocket::Shutdown;
+use rocket::response::{NamedFile, Stream};
+
+fn artifact_path() -> PathBuf {
+    env::temp_dir().join(format!("rocket-file-transfer-{}.bin", std::process::id()))
+}
+
+#[get("/artifact.bin")]
+fn artifact() -> io::Result<NamedFile> {
+    NamedFile::open(artifact_path())
+}
+
+// Only what follows the current position of a file is sent.
+#[get("/tail")]
+fn tail() -> io::Result<File> {
+    let mut file = File::open(artifact_path())?;
+    file.seek(SeekFrom::Start(1000))?;
+    Ok(file)
+}
+
+// A file wrapped in another reader is copied as usual.
+#[get("/wrapped")]
+fn wrapped() -> io::Result<Stream<io::Chain<File, Cursor<&'static [u8]>>>> {
+    let file = File::open(artifact_path())?;
+    Ok(Stream::from(io::Read::chain(file, Cursor::new(&b"!"[..]))))
+}
+
+#[post("/shutdown")]
+fn shutdown(handle: Shutdown) -> &'static str {
+    handle.notify();
+    "Shutting down."
+}
+
+mod file_transfer_tests {
+    use super::*;
+
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    // Reads a response head and returns it along with its `Content-Length`.
+    fn read_head<R: BufRead>(reader: &mut R) -> (String, Option<usize>) {
+        let mut head = String::new();
+        while !head.ends_with("\r\n\r\n") {
+            assert!(reader.read_line(&mut head).unwrap() > 0);
+        }
+
+        let length = head.lines()
+            .find(|line| line.starts_with("Content-Length: "))
+            .map(|line| line["Content-Length: ".len()..].parse().unwrap());
+
+        (head, length)
+    }
+
+    #[test]
+    fn files_are_sent_in_full() {
+        let contents: Vec<u8> = (0..(3 << 20)).map(|i| (i % 251) as u8).collect();
+        fs::write(artifact_path(), &contents).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let rocket = rocket::ignite().mount("/", routes![artifact, tail, wrapped, shutdown]);
+        let server = thread::spawn(move || rocket.launch_on(listener).is_ok());
+
+        // Several responses on one connection: the framing must stay intact.
+        let stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let mut reader = BufReader::new(stream);
+        reader.get_mut().write_all(b"GET /artifact.bin HTTP/1.1\r\n\r\n").unwrap();
+        let (head, length) = read_head(&mut reader);
+        assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert_eq!(length, Some(contents.len()));
+        let mut body = vec![0; contents.len()];
+        reader.read_exact(&mut body).unwrap();
+        assert!(body == contents);
+
+        reader.get_mut().write_all(b"HEAD /artifact.bin HTTP/1.1\r\n\r\n").unwrap();
+        let (head, length) = read_head(&mut reader);
+        assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert_eq!(length, Some(contents.len()));
+
+        reader.get_mut().write_all(b"GET /tail HTTP/1.1\r\n\r\n").unwrap();
+        let (_, length) = read_head(&mut reader);
+        assert_eq!(length, Some(contents.len() - 1000));
+        let mut body = vec![0; contents.len() - 1000];
+        reader.read_exact(&mut body).unwrap();
+        assert!(body[..] == contents[1000..]);
+
+        let request = b"GET /artifact.bin HTTP/1.1\r\nConnection: close\r\n\r\n";
+        reader.get_mut().write_all(request).unwrap();
+        let (_, length) = read_head(&mut reader);
+        assert_eq!(length, Some(contents.len()));
+        let mut body = vec![];
+        reader.read_to_end(&mut body).unwrap();
+        assert!(body == contents);
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(b"GET /wrapped HTTP/1.1\r\nConnection: close\r\n\r\n").unwrap();
+        let mut reader = BufReader::new(stream);
+        let (head, _) = read_head(&mut reader);
+        assert!(head.contains("Transfer-Encoding: chunked\r\n"));
+        let mut body = vec![];
+        reader.read_to_end(&mut body).unwrap();
+        assert!(body.ends_with(b"!\r\n0\r\n\r\n"));
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(b"POST /shutdown HTTP/1.1\r\nContent-Length: 0\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(server.join().unwrap());
+        fs::remove_file(artifact_path()).unwrap();
+    }
+}
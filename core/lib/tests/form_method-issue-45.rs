@@ -39,6 +39,7 @@ mod tests {
             .body("_method=patch&form_data=Form+data")
             .dispatch();
 
-        assert_eq!(response.status(), Status::NotFound);
+        assert_eq!(response.status(), Status::MethodNotAllowed);
+        assert_eq!(response.headers().get_one("Allow"), Some("OPTIONS, PATCH"));
     }
 }
@@ -0,0 +1,121 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+#[macro_use] extern crate rocket;
+
+use std::io;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use rocket::Shutdown;
+use rocket::config::{Config, Environment};
+use rocket::fairing::AdHoc;
+use rocket::response::Stream;
+
+#[post("/shutdown")]
+fn shutdown(handle: Shutdown) -> &'static str {
+    handle.notify();
+    "Shutting down."
+}
+
+struct Explosive;
+
+impl io::Read for Explosive {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        panic!("body exploded")
+    }
+}
+
+#[get("/explode")]
+fn explode() -> Stream<Explosive> {
+    Stream::from(Explosive)
+}
+
+fn rocket() -> rocket::Rocket {
+    let config = Config::build(Environment::Development)
+        .address("127.0.0.1")
+        .port(0)
+        .workers(1)
+        .finalize()
+        .unwrap();
+
+    rocket::custom(config).mount("/", routes![shutdown, explode])
+}
+
+mod graceful_shutdown_tests {
+    use super::*;
+
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::sync::mpsc;
+    use std::thread;
+
+    #[test]
+    fn shutdown_from_handle() {
+        let rocket = rocket();
+        let handle = rocket.shutdown();
+        assert!(!handle.is_requested());
+
+        handle.notify();
+        assert!(handle.is_requested());
+        assert!(rocket.launch().is_ok());
+    }
+
+    #[test]
+    fn shutdown_from_request() {
+        let shut_down = Arc::new(AtomicBool::new(false));
+        let (port_tx, port_rx) = mpsc::channel();
+
+        let fairing_flag = shut_down.clone();
+        let rocket = rocket()
+            .attach(AdHoc::on_launch("Port", move |rocket| {
+                port_tx.send(rocket.config().port).unwrap();
+            }))
+            .attach(AdHoc::on_shutdown("Flag", move |_| {
+                fairing_flag.store(true, Ordering::SeqCst);
+            }));
+
+        let server = thread::spawn(move || rocket.launch().is_ok());
+        let port = port_rx.recv().unwrap();
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(b"POST /shutdown HTTP/1.1\r\nContent-Length: 0\r\n\r\n").unwrap();
+
+        // The in-flight request completes and the connection is closed.
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("Shutting down."));
+
+        assert!(server.join().unwrap());
+        assert!(shut_down.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn worker_survives_panicking_body() {
+        let (port_tx, port_rx) = mpsc::channel();
+        let rocket = rocket().attach(AdHoc::on_launch("Port", move |rocket| {
+            port_tx.send(rocket.config().port).unwrap();
+        }));
+
+        let server = thread::spawn(move || rocket.launch().is_ok());
+        let port = port_rx.recv().unwrap();
+
+        // The panic costs the connection, but not the only worker.
+        for _ in 0..2 {
+            let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+            stream.write_all(b"GET /explode HTTP/1.1\r\n\r\n").unwrap();
+            let mut response = vec![];
+            let _ = stream.read_to_end(&mut response);
+            assert!(!String::from_utf8_lossy(&response).contains("0\r\n\r\n"));
+        }
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(b"POST /shutdown HTTP/1.1\r\nContent-Length: 0\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+
+        // Every connection was accounted for, so the shutdown isn't held up.
+        assert!(server.join().unwrap());
+    }
+}
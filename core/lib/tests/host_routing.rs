@@ -0,0 +1,91 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+#[macro_use] extern crate rocket;
+
+use rocket::http::RawStr;
+
+#[get("/")]
+fn api_index() -> &'static str {
+    "api"
+}
+
+#[get("/", host = "<tenant>.tenants.example.com")]
+fn tenant_index(tenant: &RawStr) -> String {
+    format!("tenant {}", tenant)
+}
+
+#[get("/about")]
+fn tenant_about() -> &'static str {
+    "about"
+}
+
+#[get("/", rank = 2)]
+fn fallback() -> &'static str {
+    "fallback"
+}
+
+mod host_routing_tests {
+    use super::*;
+
+    use rocket::Rocket;
+    use rocket::error::LaunchErrorKind;
+    use rocket::local::Client;
+    use rocket::http::{Header, Status};
+
+    fn rocket() -> Rocket {
+        rocket::ignite()
+            .mount_host("api.example.com", "/", routes![api_index])
+            .mount_host("*.tenants.example.com", "/", routes![tenant_index, tenant_about])
+            .mount("/", routes![fallback])
+    }
+
+    fn get(client: &Client, host: &'static str, uri: &'static str) -> (Status, Option<String>) {
+        let mut response = client.get(uri).header(Header::new("Host", host)).dispatch();
+        (response.status(), response.body_string())
+    }
+
+    #[test]
+    fn routes_are_selected_by_host() {
+        let client = Client::new(rocket()).unwrap();
+
+        assert_eq!(get(&client, "api.example.com", "/").1.unwrap(), "api");
+        assert_eq!(get(&client, "API.example.com:8000", "/").1.unwrap(), "api");
+        assert_eq!(get(&client, "acme.tenants.example.com", "/").1.unwrap(), "tenant acme");
+        assert_eq!(get(&client, "acme.tenants.example.com", "/about").1.unwrap(), "about");
+        assert_eq!(get(&client, "www.example.com", "/").1.unwrap(), "fallback");
+        assert_eq!(get(&client, "tenants.example.com", "/").1.unwrap(), "fallback");
+
+        let response = client.get("/").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(get(&client, "api.example.com", "/about").0, Status::NotFound);
+    }
+
+    #[test]
+    fn route_host_takes_precedence_over_mount_host() {
+        let client = Client::new(rocket()).unwrap();
+        let tenant = client.rocket().routes().find(|r| r.name == Some("tenant_index")).unwrap();
+        assert_eq!(tenant.host(), Some("<tenant>.tenants.example.com"));
+
+        let about = client.rocket().routes().find(|r| r.name == Some("tenant_about")).unwrap();
+        assert_eq!(about.host(), Some("*.tenants.example.com"));
+    }
+
+    #[test]
+    fn unranked_routes_collide_across_hosts() {
+        let rocket = rocket::ignite()
+            .mount_host("api.example.com", "/", routes![api_index])
+            .mount("/", routes![api_index]);
+
+        let error = Client::new(rocket).err().expect("routes collide");
+        match error.kind() {
+            LaunchErrorKind::Collision(..) => { },
+            kind => panic!("expected a collision, got {:?}", kind)
+        }
+
+        let rocket = rocket::ignite()
+            .mount_host("api.example.com", "/", routes![api_index])
+            .mount_host("www.example.com", "/", routes![api_index]);
+
+        assert!(Client::new(rocket).is_ok());
+    }
+}
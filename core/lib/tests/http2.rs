@@ -0,0 +1,203 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+#[macro_use] extern crate rocket;
+
+use std::io::Cursor;
+
+use rocket::{Response, Shutdown};
+use rocket::http::{Header, HeaderMap, Protocol};
+
+#[get("/")]
+fn protocol(protocol: Protocol) -> String {
+    protocol.to_string()
+}
+
+#[get("/upgrade")]
+fn upgrade() -> Response<'static> {
+    Response::build()
+        .header(Header::new("Connection", "Upgrade"))
+        .header(Header::new("Upgrade", "websocket"))
+        .header(Header::new("X-Custom", "kept"))
+        .finalize()
+}
+
+#[get("/trailers")]
+fn trailers() -> Response<'static> {
+    let mut trailers = HeaderMap::new();
+    trailers.add(Header::new("X-Status", "done"));
+    Response::build()
+        .streamed_body(Cursor::new("Hello, world!"))
+        .trailers(trailers)
+        .finalize()
+}
+
+#[post("/shutdown")]
+fn shutdown(handle: Shutdown) -> &'static str {
+    handle.notify();
+    "Shutting down."
+}
+
+mod http2_tests {
+    use super::*;
+
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    use rocket::local::Client;
+    use rocket::config::{Config, Environment};
+
+    const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+    #[test]
+    fn local_requests_simulate_protocol() {
+        let rocket = rocket::ignite().mount("/", routes![protocol, upgrade]);
+        let client = Client::new(rocket).unwrap();
+
+        let mut response = client.get("/").dispatch();
+        assert_eq!(response.body_string(), Some("HTTP/1.1".into()));
+
+        let mut response = client.get("/").protocol(Protocol::Http2).dispatch();
+        assert_eq!(response.body_string(), Some("HTTP/2".into()));
+
+        let response = client.get("/upgrade").dispatch();
+        assert_eq!(response.headers().get_one("Connection"), Some("Upgrade"));
+
+        // HTTP/1 connection headers are meaningless, and forbidden, in HTTP/2.
+        let response = client.get("/upgrade").protocol(Protocol::Http2).dispatch();
+        assert!(!response.headers().contains("Connection"));
+        assert!(!response.headers().contains("Upgrade"));
+        assert_eq!(response.headers().get_one("X-Custom"), Some("kept"));
+    }
+
+    fn write_frame(stream: &mut TcpStream, kind: u8, flags: u8, id: u32, payload: &[u8]) {
+        let len = payload.len();
+        let mut frame = vec![(len >> 16) as u8, (len >> 8) as u8, len as u8, kind, flags];
+        frame.extend_from_slice(&[(id >> 24) as u8, (id >> 16) as u8, (id >> 8) as u8, id as u8]);
+        frame.extend_from_slice(payload);
+        stream.write_all(&frame).unwrap();
+    }
+
+    // Returns the kind, flags, stream id, and payload of the next frame.
+    fn read_frame(stream: &mut TcpStream) -> (u8, u8, u32, Vec<u8>) {
+        let mut head = [0u8; 9];
+        stream.read_exact(&mut head).unwrap();
+        let len = (head[0] as usize) << 16 | (head[1] as usize) << 8 | head[2] as usize;
+        let id = (head[5] as u32 & 0x7f) << 24 | (head[6] as u32) << 16
+            | (head[7] as u32) << 8 | head[8] as u32;
+
+        let mut payload = vec![0; len];
+        stream.read_exact(&mut payload).unwrap();
+        (head[3], head[4], id, payload)
+    }
+
+    // Encodes short header fields as HPACK literals without indexing.
+    fn encode(fields: &[(&str, &str)]) -> Vec<u8> {
+        let mut block = vec![];
+        for &(name, value) in fields {
+            block.push(0);
+            block.push(name.len() as u8);
+            block.extend_from_slice(name.as_bytes());
+            block.push(value.len() as u8);
+            block.extend_from_slice(value.as_bytes());
+        }
+
+        block
+    }
+
+    // Decodes a header block consisting of short HPACK literals only, which is
+    // what Rocket sends.
+    fn decode(mut block: &[u8]) -> Vec<(String, String)> {
+        let mut fields = vec![];
+        while !block.is_empty() {
+            assert_eq!(block[0], 0);
+            let name_len = block[1] as usize;
+            let name = String::from_utf8(block[2..(2 + name_len)].to_vec()).unwrap();
+            block = &block[(2 + name_len)..];
+            let value_len = block[0] as usize;
+            let value = String::from_utf8(block[1..(1 + value_len)].to_vec()).unwrap();
+            block = &block[(1 + value_len)..];
+            fields.push((name, value));
+        }
+
+        fields
+    }
+
+    #[test]
+    fn h2c_with_prior_knowledge() {
+        let config = Config::build(Environment::Development)
+            .h2c(true)
+            .finalize()
+            .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let rocket = rocket::custom(config).mount("/", routes![protocol, trailers, shutdown]);
+        let server = thread::spawn(move || rocket.launch_on(listener).is_ok());
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(PREFACE).unwrap();
+        write_frame(&mut stream, 0x4, 0, 0, &[]);
+
+        // The server's SETTINGS come first, then the acknowledgement of ours.
+        let (kind, flags, _, _) = read_frame(&mut stream);
+        assert_eq!((kind, flags), (0x4, 0));
+        write_frame(&mut stream, 0x4, 0x1, 0, &[]);
+        let (kind, flags, _, _) = read_frame(&mut stream);
+        assert_eq!((kind, flags), (0x4, 0x1));
+
+        // Two requests, one after the other, on the same connection.
+        for &id in &[1, 3] {
+            let fields = [(":method", "GET"), (":scheme", "http"), (":path", "/")];
+            write_frame(&mut stream, 0x1, 0x4 | 0x1, id, &encode(&fields));
+
+            let (kind, flags, stream_id, block) = read_frame(&mut stream);
+            assert_eq!((kind, stream_id), (0x1, id));
+            assert!(flags & 0x4 != 0);
+            let fields = decode(&block);
+            assert_eq!(fields[0], (":status".into(), "200".into()));
+            assert!(fields.contains(&("content-length".into(), "6".into())));
+            assert!(!fields.iter().any(|&(ref name, _)| name == "connection"));
+
+            let mut body = vec![];
+            loop {
+                let (kind, flags, stream_id, payload) = read_frame(&mut stream);
+                assert_eq!((kind, stream_id), (0x0, id));
+                body.extend_from_slice(&payload);
+                if flags & 0x1 != 0 {
+                    break;
+                }
+            }
+
+            assert_eq!(body, b"HTTP/2");
+        }
+
+        // Trailers end the stream in a HEADERS frame of their own.
+        let fields = [(":method", "GET"), (":scheme", "http"), (":path", "/trailers"),
+            ("te", "trailers")];
+        write_frame(&mut stream, 0x1, 0x4 | 0x1, 5, &encode(&fields));
+
+        let (kind, flags, _, _) = read_frame(&mut stream);
+        assert_eq!((kind, flags & 0x1), (0x1, 0));
+        let (kind, flags, _, payload) = read_frame(&mut stream);
+        assert_eq!((kind, flags & 0x1, &*payload), (0x0, 0, &b"Hello, world!"[..]));
+        let (kind, flags, stream_id, block) = read_frame(&mut stream);
+        assert_eq!((kind, flags & 0x5, stream_id), (0x1, 0x5, 5));
+        assert_eq!(decode(&block), vec![("x-status".into(), "done".into())]);
+
+        // Say goodbye. The server follows suit and closes the connection.
+        write_frame(&mut stream, 0x7, 0, 0, &[0; 8]);
+        let (kind, _, _, payload) = read_frame(&mut stream);
+        assert_eq!(kind, 0x7);
+        assert_eq!(&payload[..4], &[0, 0, 0, 5]);
+        assert_eq!(stream.read(&mut [0]).unwrap(), 0);
+
+        // HTTP/1 is still spoken on the same listener.
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(b"POST /shutdown HTTP/1.1\r\nContent-Length: 0\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(server.join().unwrap());
+    }
+}
@@ -0,0 +1,94 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+#[macro_use] extern crate rocket;
+
+use rocket::{Saturation, Shutdown};
+
+#[get("/")]
+fn load(saturation: Saturation) -> String {
+    saturation.busy().to_string()
+}
+
+#[post("/shutdown")]
+fn shutdown(handle: Shutdown) -> &'static str {
+    handle.notify();
+    "Shutting down."
+}
+
+#[cfg(unix)]
+mod idle_connections_tests {
+    use super::*;
+
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    use rocket::config::{Config, Environment};
+
+    const REQUEST: &str = "GET / HTTP/1.1\r\n\r\n";
+
+    fn request(port: u16, request: &str) -> String {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    // Reads a single response with a one byte body from `stream`.
+    fn read_response(stream: &mut TcpStream) -> String {
+        let mut response = vec![];
+        let mut byte = [0u8];
+        while !response.ends_with(b"\r\n\r\n") {
+            stream.read_exact(&mut byte).unwrap();
+            response.push(byte[0]);
+        }
+
+        stream.read_exact(&mut byte).unwrap();
+        response.push(byte[0]);
+        String::from_utf8(response).unwrap()
+    }
+
+    #[test]
+    fn idle_connections_dont_occupy_workers() {
+        let config = Config::build(Environment::Development)
+            .workers(1)
+            .keep_alive(30)
+            .finalize()
+            .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let rocket = rocket::custom(config).mount("/", routes![load, shutdown]);
+        let server = thread::spawn(move || rocket.launch_on(listener).is_ok());
+
+        // Connections yet to send their first request don't occupy it either.
+        let _silent: Vec<TcpStream> = (0..8)
+            .map(|_| TcpStream::connect(("127.0.0.1", port)).unwrap())
+            .collect();
+
+        // Leave several connections idling in keep-alive. With a single
+        // worker, each would otherwise block every connection after it.
+        let mut idle: Vec<TcpStream> = (0..8).map(|_| {
+            let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+            stream.write_all(REQUEST.as_bytes()).unwrap();
+            let response = read_response(&mut stream);
+            assert!(response.starts_with("HTTP/1.1 200 OK"));
+            assert!(!response.contains("Connection: close"));
+            stream
+        }).collect();
+
+        // The idle connections can be resumed in any order.
+        for stream in idle.iter_mut().rev() {
+            stream.write_all(REQUEST.as_bytes()).unwrap();
+            assert!(read_response(stream).ends_with("\r\n\r\n1"));
+        }
+
+        let response = request(port, "GET / HTTP/1.1\r\nConnection: close\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+
+        request(port, "POST /shutdown HTTP/1.1\r\nContent-Length: 0\r\n\r\n");
+        assert!(server.join().unwrap());
+    }
+}
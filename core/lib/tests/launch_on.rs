@@ -0,0 +1,110 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+#[macro_use] extern crate rocket;
+
+use std::net::SocketAddr;
+
+use rocket::Shutdown;
+
+#[get("/")]
+fn remote(remote: Option<SocketAddr>) -> String {
+    match remote {
+        Some(addr) => addr.ip().to_string(),
+        None => "none".into(),
+    }
+}
+
+#[post("/shutdown")]
+fn shutdown(handle: Shutdown) -> &'static str {
+    handle.notify();
+    "Shutting down."
+}
+
+fn rocket() -> rocket::Rocket {
+    rocket::ignite().mount("/", routes![remote, shutdown])
+}
+
+mod launch_on_tests {
+    use super::*;
+
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    fn request<S: Read + Write>(mut stream: S, request: &str) -> String {
+        stream.write_all(request.as_bytes()).unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn launch_on_tcp_listener() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || rocket().launch_on(listener).is_ok());
+
+        let stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let response = request(stream, "GET / HTTP/1.1\r\nConnection: close\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("127.0.0.1"));
+
+        let stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        request(stream, "POST /shutdown HTTP/1.1\r\nContent-Length: 0\r\n\r\n");
+        assert!(server.join().unwrap());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn launch_on_unix_listener() {
+        use std::env;
+        use std::fs;
+        use std::os::unix::net::{UnixListener, UnixStream};
+
+        let path = env::temp_dir().join(format!("rocket-launch-on-{}.sock", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = thread::spawn(move || rocket().launch_on(listener).is_ok());
+
+        // There is no remote address for a Unix domain socket.
+        let stream = UnixStream::connect(&path).unwrap();
+        let response = request(stream, "GET / HTTP/1.1\r\nConnection: close\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("none"));
+
+        let stream = UnixStream::connect(&path).unwrap();
+        request(stream, "POST /shutdown HTTP/1.1\r\nContent-Length: 0\r\n\r\n");
+        assert!(server.join().unwrap());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn unix_address_never_removes_other_files() {
+        use std::env;
+        use std::fs;
+
+        use rocket::config::{Config, Environment};
+        use rocket::error::LaunchErrorKind;
+
+        let path = env::temp_dir().join(format!("rocket-not-a-socket-{}", std::process::id()));
+        fs::write(&path, "keep me").unwrap();
+
+        let config = Config::build(Environment::Development)
+            .address(format!("unix:{}", path.display()))
+            .finalize()
+            .unwrap();
+
+        let error = rocket::custom(config).launch().unwrap_err();
+        match error.kind() {
+            LaunchErrorKind::Bind(_) => {},
+            kind => panic!("expected a bind error, got {}", kind),
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "keep me");
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -2,6 +2,7 @@
 
 #[macro_use] extern crate rocket;
 
+use rocket::{Request, Shutdown};
 use rocket::request::Form;
 
 #[derive(FromForm)]
@@ -14,11 +15,29 @@ fn index(form: Form<Simple>) -> String {
     form.into_inner().value
 }
 
+#[post("/shutdown")]
+fn shutdown(handle: Shutdown) -> &'static str {
+    handle.notify();
+    "Shutting down."
+}
+
+#[catch(431)]
+fn too_large(req: &Request) -> String {
+    format!("too large: {}", req.uri())
+}
+
 mod limits_tests {
     use rocket;
     use rocket::config::{Environment, Config, Limits};
+    use rocket::fairing::AdHoc;
     use rocket::local::Client;
-    use rocket::http::{Status, ContentType};
+    use rocket::http::{Status, ContentType, Header};
+
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
 
     fn rocket_with_forms_limit(limit: u64) -> rocket::Rocket {
         let config = Config::build(Environment::Development)
@@ -61,6 +80,121 @@ mod limits_tests {
         assert_eq!(response.status(), Status::UnprocessableEntity);
     }
 
+    fn rocket_with_limit(name: &str, limit: u64) -> rocket::Rocket {
+        let config = Config::build(Environment::Development)
+            .limits(Limits::default().limit(name, limit))
+            .unwrap();
+
+        rocket::custom(config).mount("/", routes![super::index])
+    }
+
+    #[test]
+    fn too_many_headers() {
+        let client = Client::new(rocket_with_limit("headers", 2)).unwrap();
+        let response = client.post("/")
+            .body("value=Hello+world")
+            .header(ContentType::Form)
+            .header(Header::new("X-One", "1"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+
+        let response = client.post("/")
+            .body("value=Hello+world")
+            .header(ContentType::Form)
+            .header(Header::new("X-One", "1"))
+            .header(Header::new("X-Two", "2"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::RequestHeaderFieldsTooLarge);
+    }
+
+    #[test]
+    fn headers_too_large() {
+        // "Content-Type" and "application/x-www-form-urlencoded" is 45 bytes.
+        let client = Client::new(rocket_with_limit("header_bytes", 45)).unwrap();
+        let response = client.post("/")
+            .body("value=Hello+world")
+            .header(ContentType::Form)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+
+        let response = client.post("/")
+            .body("value=Hello+world")
+            .header(ContentType::Form)
+            .header(Header::new("X", "1"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::RequestHeaderFieldsTooLarge);
+    }
+
+    #[test]
+    fn header_limits_are_checked_before_fairings() {
+        let seen = Arc::new(AtomicUsize::new(0));
+        let counter = seen.clone();
+        let rocket = rocket_with_limit("headers", 1)
+            .attach(AdHoc::on_request("Count", move |_, _| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }));
+
+        let client = Client::new(rocket).unwrap();
+        let response = client.post("/")
+            .body("value=Hello+world")
+            .header(ContentType::Form)
+            .header(Header::new("X-One", "1"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::RequestHeaderFieldsTooLarge);
+        assert_eq!(seen.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn headers_limit_is_at_most_100() {
+        let config = Config::build(Environment::Development)
+            .limits(Limits::default().limit("headers", 100))
+            .finalize();
+
+        assert!(config.is_ok());
+
+        let config = Config::build(Environment::Development)
+            .limits(Limits::default().limit("headers", 101))
+            .finalize();
+
+        assert!(config.is_err());
+    }
+
+    #[test]
+    fn too_many_headers_for_hyper_reach_431_catcher() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let rocket = rocket::ignite()
+            .mount("/", routes![super::shutdown])
+            .register(catchers![super::too_large]);
+
+        let server = thread::spawn(move || rocket.launch_on(listener).is_ok());
+
+        let mut request = String::from("GET /many?q HTTP/1.1\r\n");
+        for i in 0..101 {
+            request.push_str(&format!("X-Header-{}: {}\r\n", i, i));
+        }
+
+        request.push_str("\r\n");
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 431 Request Header Fields Too Large"));
+        assert!(response.contains("Connection: close"));
+        assert!(response.ends_with("too large: /many?q"));
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(b"POST /shutdown HTTP/1.1\r\nContent-Length: 0\r\n\r\n").unwrap();
+        stream.read_to_string(&mut String::new()).unwrap();
+        assert!(server.join().unwrap());
+    }
+
     #[test]
     fn contracted() {
         let client = Client::new(rocket_with_forms_limit(10)).unwrap();
@@ -0,0 +1,103 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+#[macro_use] extern crate rocket;
+
+use rocket::Request;
+
+#[get("/item/<id>")]
+fn get_item(id: usize) -> String {
+    format!("item {}", id)
+}
+
+#[put("/item/<id>", data = "<body>")]
+fn put_item(id: usize, body: String) -> String {
+    format!("put {}: {}", id, body)
+}
+
+#[delete("/item/<_id>", format = "json")]
+fn delete_item(_id: usize) { }
+
+#[post("/items")]
+fn new_item() -> &'static str {
+    "created"
+}
+
+#[catch(405)]
+fn method_not_allowed(req: &Request) -> String {
+    format!("{} is not allowed here", req.method())
+}
+
+mod method_not_allowed_tests {
+    use super::*;
+
+    use rocket::Rocket;
+    use rocket::config::{Config, Environment};
+    use rocket::local::Client;
+    use rocket::http::{ContentType, Status};
+
+    fn rocket() -> Rocket {
+        rocket::ignite().mount("/", routes![get_item, put_item, delete_item, new_item])
+    }
+
+    #[test]
+    fn wrong_method_is_405_with_allow() {
+        let client = Client::new(rocket()).unwrap();
+
+        let response = client.post("/item/1").dispatch();
+        assert_eq!(response.status(), Status::MethodNotAllowed);
+        assert_eq!(response.headers().get_one("Allow"), Some("DELETE, GET, HEAD, OPTIONS, PUT"));
+
+        let response = client.get("/items").dispatch();
+        assert_eq!(response.status(), Status::MethodNotAllowed);
+        assert_eq!(response.headers().get_one("Allow"), Some("OPTIONS, POST"));
+
+        let response = client.head("/items").dispatch();
+        assert_eq!(response.status(), Status::MethodNotAllowed);
+        assert_eq!(response.headers().get_one("Allow"), Some("OPTIONS, POST"));
+    }
+
+    #[test]
+    fn unknown_path_or_forward_is_404() {
+        let client = Client::new(rocket()).unwrap();
+
+        let response = client.post("/nothing").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        assert!(response.headers().get_one("Allow").is_none());
+
+        // A route for the method exists, but its guards forward.
+        let response = client.get("/item/one").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+
+        // A route for the method exists, but its format doesn't match.
+        let response = client.delete("/item/1").header(ContentType::HTML).dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+
+        let response = client.delete("/item/1").header(ContentType::JSON).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+    }
+
+    #[test]
+    fn user_405_catcher_is_used() {
+        let client = Client::new(rocket().register(catchers![method_not_allowed])).unwrap();
+
+        let mut response = client.patch("/items").dispatch();
+        assert_eq!(response.status(), Status::MethodNotAllowed);
+        assert_eq!(response.headers().get_one("Allow"), Some("OPTIONS, POST"));
+        assert_eq!(response.body_string(), Some("PATCH is not allowed here".into()));
+    }
+
+    #[test]
+    fn auto_405_can_be_disabled() {
+        let config = Config::build(Environment::Development)
+            .auto_405(false)
+            .unwrap();
+
+        let rocket = Rocket::custom(config)
+            .mount("/", routes![get_item, put_item, delete_item, new_item]);
+
+        let client = Client::new(rocket).unwrap();
+        let response = client.post("/item/1").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        assert!(response.headers().get_one("Allow").is_none());
+    }
+}
@@ -0,0 +1,77 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+#[macro_use] extern crate rocket;
+
+#[cfg(unix)]
+mod multiple_endpoints_tests {
+    use std::env;
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::os::unix::net::UnixStream;
+    use std::sync::mpsc;
+    use std::thread;
+
+    use rocket::{Shutdown, State};
+    use rocket::config::{Config, Environment};
+    use rocket::fairing::AdHoc;
+
+    struct Counter(std::sync::atomic::AtomicUsize);
+
+    #[get("/")]
+    fn count(counter: State<Counter>) -> String {
+        use std::sync::atomic::Ordering;
+        counter.0.fetch_add(1, Ordering::SeqCst).to_string()
+    }
+
+    #[post("/shutdown")]
+    fn shutdown(handle: Shutdown) -> &'static str {
+        handle.notify();
+        "Shutting down."
+    }
+
+    fn request<S: Read + Write>(mut stream: S, request: &str) -> String {
+        stream.write_all(request.as_bytes()).unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn all_endpoints_share_state() {
+        let path = env::temp_dir().join(format!("rocket-endpoints-{}.sock", std::process::id()));
+        let unix_address = format!("unix:{}", path.display());
+
+        let config = Config::build(Environment::Development)
+            .address("127.0.0.1")
+            .port(0)
+            .endpoint(unix_address, 0)
+            .finalize()
+            .unwrap();
+
+        let (port_tx, port_rx) = mpsc::channel();
+        let rocket = rocket::custom(config)
+            .mount("/", routes![count, shutdown])
+            .manage(Counter(Default::default()))
+            .attach(AdHoc::on_launch("Port", move |rocket| {
+                port_tx.send(rocket.config().port).unwrap();
+            }));
+
+        let server = thread::spawn(move || rocket.launch().is_ok());
+        let port = port_rx.recv().unwrap();
+
+        let get = "GET / HTTP/1.1\r\nConnection: close\r\n\r\n";
+        let response = request(TcpStream::connect(("127.0.0.1", port)).unwrap(), get);
+        assert!(response.ends_with("\r\n\r\n0"));
+
+        let response = request(UnixStream::connect(&path).unwrap(), get);
+        assert!(response.ends_with("\r\n\r\n1"));
+
+        let post = "POST /shutdown HTTP/1.1\r\nContent-Length: 0\r\n\r\n";
+        request(UnixStream::connect(&path).unwrap(), post);
+        assert!(server.join().unwrap());
+
+        // Rocket bound the socket, so Rocket removes it.
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,81 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+#[macro_use] extern crate rocket;
+
+use rocket::response::content;
+
+#[get("/")]
+fn index() -> &'static str {
+    "Hello, world!"
+}
+
+#[post("/")]
+fn create() -> &'static str {
+    "created"
+}
+
+#[delete("/item/<_id: uint>")]
+fn delete_item(_id: usize) { }
+
+#[options("/custom")]
+fn custom_options() -> content::Plain<&'static str> {
+    content::Plain("custom")
+}
+
+#[get("/custom")]
+fn custom() -> &'static str {
+    "custom"
+}
+
+mod options_handling_tests {
+    use super::*;
+
+    use rocket::Route;
+    use rocket::local::Client;
+    use rocket::http::Status;
+
+    fn routes() -> Vec<Route> {
+        routes![index, create, delete_item, custom_options, custom]
+    }
+
+    #[test]
+    fn auto_options() {
+        let client = Client::new(rocket::ignite().mount("/", routes())).unwrap();
+        let mut response = client.options("/").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.headers().get_one("Allow"), Some("GET, HEAD, OPTIONS, POST"));
+        assert!(response.body_bytes().is_none());
+
+        let response = client.options("/item/7").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.headers().get_one("Allow"), Some("DELETE, OPTIONS"));
+    }
+
+    #[test]
+    fn auto_options_unknown_path() {
+        let client = Client::new(rocket::ignite().mount("/", routes())).unwrap();
+        let response = client.options("/item/seven").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        assert!(response.headers().get_one("Allow").is_none());
+
+        let response = client.options("/unknown").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[test]
+    fn user_options() {
+        let client = Client::new(rocket::ignite().mount("/", routes())).unwrap();
+        let mut response = client.options("/custom").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.body_string(), Some("custom".into()));
+        assert!(response.headers().get_one("Allow").is_none());
+    }
+
+    #[test]
+    fn allow_lists_options() {
+        let client = Client::new(rocket::ignite().mount("/", routes())).unwrap();
+        let response = client.put("/custom").dispatch();
+        assert_eq!(response.status(), Status::MethodNotAllowed);
+        assert_eq!(response.headers().get_one("Allow"), Some("GET, HEAD, OPTIONS"));
+    }
+}
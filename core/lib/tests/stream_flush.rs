@@ -0,0 +1,110 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+#[macro_use] extern crate rocket;
+
+use std::io::{self, Write};
+use std::sync::Mutex;
+use std::sync::mpsc::{Sender, Receiver};
+use std::thread;
+
+use rocket::{State, Shutdown};
+use rocket::response::{Stream, StreamReceiver};
+
+// Lets the test decide when the producer writes its next line.
+struct Producer {
+    steps: Mutex<Option<Receiver<()>>>,
+    done: Mutex<Sender<io::ErrorKind>>,
+}
+
+#[get("/progress")]
+fn progress(producer: State<Producer>) -> Option<Stream<StreamReceiver>> {
+    let steps = producer.steps.lock().unwrap().take()?;
+    let done = producer.done.lock().unwrap().clone();
+    let (mut sender, stream) = Stream::channel(1);
+    thread::spawn(move || {
+        for (i, _) in steps.iter().enumerate() {
+            let sent = writeln!(sender, "step {}", i).and_then(|_| sender.flush());
+            if let Err(e) = sent {
+                done.send(e.kind()).unwrap();
+                return;
+            }
+        }
+    });
+
+    Some(stream)
+}
+
+#[post("/shutdown")]
+fn shutdown(handle: Shutdown) -> &'static str {
+    handle.notify();
+    "Shutting down."
+}
+
+mod stream_flush_tests {
+    use super::*;
+
+    use std::io::Read;
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    // Reads from `stream` until `expected` has been received.
+    fn read_until(stream: &mut TcpStream, expected: &str) -> String {
+        let mut received = vec![];
+        while !String::from_utf8_lossy(&received).contains(expected) {
+            let mut byte = [0];
+            assert_eq!(stream.read(&mut byte).unwrap(), 1, "EOF before {:?}", expected);
+            received.push(byte[0]);
+        }
+
+        String::from_utf8(received).unwrap()
+    }
+
+    #[test]
+    fn flushes_and_reports_disconnects() {
+        let (steps, step_receiver) = mpsc::channel();
+        let (done_sender, done) = mpsc::channel();
+        let producer = Producer {
+            steps: Mutex::new(Some(step_receiver)),
+            done: Mutex::new(done_sender),
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let rocket = rocket::ignite().mount("/", routes![progress, shutdown]).manage(producer);
+        let server = thread::spawn(move || rocket.launch_on(listener).is_ok());
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        stream.write_all(b"GET /progress HTTP/1.1\r\n\r\n").unwrap();
+
+        // Each flushed line arrives before the next one is written.
+        steps.send(()).unwrap();
+        let head = read_until(&mut stream, "step 0\n");
+        assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(head.contains("Transfer-Encoding: chunked\r\n"));
+
+        steps.send(()).unwrap();
+        read_until(&mut stream, "step 1\n");
+
+        // Once the client is gone, the producer's writes fail.
+        drop(stream);
+        for _ in 0..100 {
+            if steps.send(()).is_err() {
+                break;
+            }
+
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let error = done.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(error, io::ErrorKind::BrokenPipe);
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(b"POST /shutdown HTTP/1.1\r\nContent-Length: 0\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(server.join().unwrap());
+    }
+}
@@ -0,0 +1,130 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+#[macro_use] extern crate rocket;
+
+use std::io::Cursor;
+
+use rocket::{Request, Response, Shutdown};
+use rocket::http::Status;
+use rocket::request::BadRequestInfo;
+
+#[post("/", data = "<body>")]
+fn echo(body: String) -> String {
+    body
+}
+
+#[post("/shutdown")]
+fn shutdown(handle: Shutdown) -> &'static str {
+    handle.notify();
+    "Shutting down."
+}
+
+#[catch(408)]
+fn too_slow(req: &Request) -> String {
+    format!("too slow: {}", req.uri())
+}
+
+fn bad_request(info: &BadRequestInfo) -> Response<'static> {
+    Response::build()
+        .status(Status::RequestTimeout)
+        .sized_body(Cursor::new(format!("no request: {}", info.error())))
+        .finalize()
+}
+
+mod timeouts_tests {
+    use super::*;
+
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use rocket::config::{Config, Environment};
+
+    fn request(port: u16, request: &str) -> String {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn slow_clients_get_408() {
+        let config = Config::build(Environment::Development)
+            .header_read_timeout(1)
+            .read_timeout(1)
+            .finalize()
+            .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let rocket = rocket::custom(config).mount("/", routes![echo, shutdown]);
+        let server = thread::spawn(move || rocket.launch_on(listener).is_ok());
+
+        // The head never completes.
+        let response = request(port, "POST / HTTP/1.1\r\nContent-Le");
+        assert!(response.starts_with("HTTP/1.1 408 Request Timeout"));
+        assert!(response.contains("Connection: close"));
+
+        // The body never completes.
+        let response = request(port, "POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\nhi");
+        assert!(response.starts_with("HTTP/1.1 408 Request Timeout"));
+
+        // The body trickles in too slowly to ever complete. Each read is quick,
+        // but the body as a whole still times out.
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(b"POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\n").unwrap();
+        let start = Instant::now();
+        for _ in 0..3 {
+            thread::sleep(Duration::from_millis(250));
+            stream.write_all(b"a").unwrap();
+        }
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 408 Request Timeout"));
+        assert!(start.elapsed() < Duration::from_millis(1500));
+
+        // A prompt client is unaffected.
+        let response = request(port, "POST / HTTP/1.1\r\nContent-Length: 2\r\n\
+            Connection: close\r\n\r\nhi");
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("hi"));
+
+        request(port, "POST /shutdown HTTP/1.1\r\nContent-Length: 0\r\n\r\n");
+        assert!(server.join().unwrap());
+    }
+
+    #[test]
+    fn slow_request_heads_reach_408_catcher() {
+        let config = Config::build(Environment::Development)
+            .header_read_timeout(1)
+            .finalize()
+            .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let rocket = rocket::custom(config)
+            .mount("/", routes![echo, shutdown])
+            .register(catchers![too_slow])
+            .on_bad_request(bad_request);
+
+        let server = thread::spawn(move || rocket.launch_on(listener).is_ok());
+
+        // The request line arrived, so there's a request for the catcher.
+        let response = request(port, "POST /slow?q HTTP/1.1\r\nContent-Le");
+        assert!(response.starts_with("HTTP/1.1 408 Request Timeout"));
+        assert!(response.contains("Connection: close"));
+        assert!(response.ends_with("too slow: /slow?q"));
+
+        // Not even the request line arrived: the bad request handler responds.
+        let response = request(port, "POST /sl");
+        assert!(response.starts_with("HTTP/1.1 408 Request Timeout"));
+        assert!(response.ends_with("no request: timed out reading request head"));
+
+        request(port, "POST /shutdown HTTP/1.1\r\nContent-Length: 0\r\n\r\n");
+        assert!(server.join().unwrap());
+    }
+}
@@ -0,0 +1,120 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+#[macro_use] extern crate rocket;
+
+use std::io::{self, Cursor, Read};
+use std::sync::{Arc, Mutex};
+
+use rocket::{Response, Shutdown};
+use rocket::http::{Header, HeaderMap};
+
+#[get("/static")]
+fn fixed() -> Response<'static> {
+    let mut trailers = HeaderMap::new();
+    trailers.add(Header::new("X-Status", "done"));
+    trailers.add(Header::new("X-Status", "really"));
+
+    Response::build()
+        .streamed_body(Cursor::new("Hello, world!"))
+        .trailers(trailers)
+        .finalize()
+}
+
+// Counts the bytes read through it.
+struct Counted<R>(R, Arc<Mutex<usize>>);
+
+impl<R: Read> Read for Counted<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.0.read(buf)?;
+        *self.1.lock().unwrap() += n;
+        Ok(n)
+    }
+}
+
+#[get("/counted")]
+fn counted() -> Response<'static> {
+    let count = Arc::new(Mutex::new(0));
+    let body = Counted(Cursor::new("Hello, world!"), count.clone());
+    Response::build()
+        .streamed_body(body)
+        .trailers_with(move || {
+            let mut trailers = HeaderMap::new();
+            trailers.add(Header::new("X-Length", count.lock().unwrap().to_string()));
+            trailers
+        })
+        .finalize()
+}
+
+#[get("/sized")]
+fn sized() -> Response<'static> {
+    let mut trailers = HeaderMap::new();
+    trailers.add(Header::new("X-Status", "done"));
+
+    Response::build()
+        .sized_body(Cursor::new("Hello, world!"))
+        .trailers(trailers)
+        .finalize()
+}
+
+#[post("/shutdown")]
+fn shutdown(handle: Shutdown) -> &'static str {
+    handle.notify();
+    "Shutting down."
+}
+
+mod trailers_tests {
+    use super::*;
+
+    use std::io::Write;
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    fn request(port: u16, request: &str) -> String {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn trailers_follow_the_last_chunk() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let rocket = rocket::ignite().mount("/", routes![fixed, counted, sized, shutdown]);
+        let server = thread::spawn(move || rocket.launch_on(listener).is_ok());
+
+        let response = request(port, "GET /static HTTP/1.1\r\nTE: trailers\r\n\
+            Connection: close\r\n\r\n");
+        assert!(response.contains("Transfer-Encoding: chunked\r\n"));
+        assert!(response.contains("Trailer: X-Status\r\n"));
+        assert!(response.ends_with("\r\nHello, world!\r\n\
+            0\r\nX-Status: done\r\nX-Status: really\r\n\r\n"));
+
+        // Trailers produced by a closure may depend on the body.
+        let response = request(port, "GET /counted HTTP/1.1\r\nTE: gzip, trailers;q=1\r\n\
+            Connection: close\r\n\r\n");
+        assert!(!response.contains("Trailer:"));
+        assert!(response.ends_with("\r\nHello, world!\r\n0\r\nX-Length: 13\r\n\r\n"));
+
+        // Clients that don't ask for trailers don't get them.
+        let response = request(port, "GET /counted HTTP/1.1\r\nConnection: close\r\n\r\n");
+        assert!(response.ends_with("\r\nHello, world!\r\n0\r\n\r\n"));
+
+        let response = request(port, "GET /static HTTP/1.1\r\nTE: gzip\r\n\
+            Connection: close\r\n\r\n");
+        assert!(!response.contains("Trailer:"));
+        assert!(response.ends_with("\r\nHello, world!\r\n0\r\n\r\n"));
+
+        // Sized bodies can't be followed by trailers.
+        let response = request(port, "GET /sized HTTP/1.1\r\nTE: trailers\r\n\
+            Connection: close\r\n\r\n");
+        assert!(response.contains("Content-Length: 13\r\n"));
+        assert!(!response.contains("X-Status"));
+        assert!(response.ends_with("\r\n\r\nHello, world!"));
+
+        request(port, "POST /shutdown HTTP/1.1\r\nContent-Length: 0\r\n\r\n");
+        assert!(server.join().unwrap());
+    }
+}
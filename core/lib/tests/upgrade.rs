@@ -0,0 +1,180 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+#[macro_use] extern crate rocket;
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpStream, Shutdown as NetShutdown};
+use std::thread;
+
+use rocket::{Request, Response, Shutdown};
+use rocket::http::Status;
+use rocket::outcome::Outcome;
+use rocket::request::{self, FromRequest};
+
+// Upper-cases every line the client sends until it hangs up.
+#[get("/shout")]
+fn shout() -> Response<'static> {
+    Response::build()
+        .status(Status::SwitchingProtocols)
+        .raw_header("Connection", "Upgrade")
+        .raw_header("Upgrade", "shout")
+        .upgrade(|conn| {
+            let mut writer = conn.try_clone().unwrap();
+            for line in BufReader::new(conn).lines() {
+                let line = line.unwrap().to_uppercase();
+                writer.write_all(format!("{}\n", line).as_bytes()).unwrap();
+            }
+        })
+        .finalize()
+}
+
+// An upgrade callback is ignored unless the response switches protocols.
+#[get("/fake")]
+fn fake() -> Response<'static> {
+    Response::build()
+        .raw_header("Upgrade", "shout")
+        .upgrade(|_| panic!("not upgraded"))
+        .finalize()
+}
+
+struct Target(String);
+
+impl<'a, 'r> FromRequest<'a, 'r> for Target {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        match request.connect_target() {
+            Some(target) => Outcome::Success(Target(target.to_string())),
+            None => Outcome::Forward(())
+        }
+    }
+}
+
+#[route(CONNECT, path = "/")]
+fn tunnel(target: Target) -> Result<Response<'static>, Status> {
+    let upstream = TcpStream::connect(&*target.0).map_err(|_| Status::BadGateway)?;
+    Response::build()
+        .upgrade(move |mut conn| {
+            let mut conn_writer = conn.try_clone().unwrap();
+            let mut upstream_writer = upstream.try_clone().unwrap();
+            thread::spawn(move || {
+                let _ = io::copy(&mut conn, &mut upstream_writer);
+                let _ = upstream_writer.shutdown(NetShutdown::Write);
+            });
+
+            let mut upstream = upstream;
+            let _ = io::copy(&mut upstream, &mut conn_writer);
+        })
+        .ok()
+}
+
+#[post("/shutdown")]
+fn shutdown(handle: Shutdown) -> &'static str {
+    handle.notify();
+    "Shutting down."
+}
+
+mod upgrade_tests {
+    use super::*;
+
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    fn launch() -> (u16, thread::JoinHandle<bool>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let rocket = rocket::ignite().mount("/", routes![shout, fake, tunnel, shutdown]);
+        (port, thread::spawn(move || rocket.launch_on(listener).is_ok()))
+    }
+
+    fn stop(port: u16, server: thread::JoinHandle<bool>) {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(b"POST /shutdown HTTP/1.1\r\nContent-Length: 0\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(server.join().unwrap());
+    }
+
+    // Reads a response head, byte by byte so as not to consume what follows.
+    fn read_head(stream: &mut TcpStream) -> String {
+        let mut head = vec![];
+        while !head.ends_with(b"\r\n\r\n") {
+            let mut byte = [0];
+            stream.read_exact(&mut byte).unwrap();
+            head.push(byte[0]);
+        }
+
+        String::from_utf8(head).unwrap()
+    }
+
+    #[test]
+    fn custom_protocol_upgrade() {
+        let (port, server) = launch();
+
+        // The first line is sent along with the request.
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(b"GET /shout HTTP/1.1\r\nConnection: Upgrade\r\n\r\nhello\n").unwrap();
+
+        let head = read_head(&mut stream);
+        assert!(head.starts_with("HTTP/1.1 101 Switching Protocols\r\n"));
+        assert!(head.contains("Connection: Upgrade\r\n"));
+        assert!(head.contains("Upgrade: shout\r\n"));
+        assert!(!head.contains("Content-Length"));
+        assert!(!head.contains("Transfer-Encoding"));
+
+        stream.write_all(b"world\n").unwrap();
+        stream.shutdown(NetShutdown::Write).unwrap();
+        let mut rest = String::new();
+        stream.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "HELLO\nWORLD\n");
+
+        // Without a `101`, the response is a regular one.
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(b"GET /fake HTTP/1.1\r\nConnection: close\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+
+        stop(port, server);
+    }
+
+    #[test]
+    fn connect_tunnel() {
+        // An upstream server that echoes what it receives until EOF.
+        let upstream = TcpListener::bind("127.0.0.1:0").unwrap();
+        let upstream_port = upstream.local_addr().unwrap().port();
+        thread::spawn(move || {
+            let (mut stream, _) = upstream.accept().unwrap();
+            let mut reader = stream.try_clone().unwrap();
+            io::copy(&mut reader, &mut stream).unwrap();
+        });
+
+        let (port, server) = launch();
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let request = format!("CONNECT 127.0.0.1:{0} HTTP/1.1\r\nHost: 127.0.0.1:{0}\r\n\r\n",
+            upstream_port);
+
+        stream.write_all(request.as_bytes()).unwrap();
+        let head = read_head(&mut stream);
+        assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+
+        stream.write_all(b"through the tunnel").unwrap();
+        let mut echo = [0; 18];
+        stream.read_exact(&mut echo).unwrap();
+        assert_eq!(&echo, b"through the tunnel");
+
+        // Once either end hangs up, the tunnel is torn down.
+        stream.shutdown(NetShutdown::Write).unwrap();
+        assert_eq!(stream.read(&mut [0]).unwrap(), 0);
+
+        // Authorities are only accepted as the target of a `CONNECT`.
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(b"GET 127.0.0.1:80 HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+
+        stop(port, server);
+    }
+}
@@ -0,0 +1,169 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+#[macro_use] extern crate rocket;
+
+use std::path::PathBuf;
+
+use rocket::{Request, Data, Route};
+use rocket::handler::Outcome;
+use rocket::http::Method;
+use rocket::request::{self, FromRequest};
+
+#[get("/<id>/profile?<tab>")]
+fn user_profile(id: usize, tab: Option<String>) -> String {
+    format!("{} {:?}", id, tab)
+}
+
+#[get("/files/<path..>?sort=name&<rest..>")]
+fn files(path: PathBuf, rest: request::LenientForm<Sort>) -> String {
+    format!("{:?} {}", path, rest.order)
+}
+
+#[derive(FromForm)]
+struct Sort {
+    order: String,
+}
+
+#[get("/twice")]
+fn twice() { }
+
+#[get("/home")]
+fn home() { }
+
+// Links to the profile of the user whose ID is in the `X-User` header.
+struct ProfileLink(String);
+
+impl<'a, 'r> FromRequest<'a, 'r> for ProfileLink {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        let id = request.headers().get_one("X-User").unwrap_or("0");
+        let uri = request.url_for("user_profile", vec![("id", id)]).unwrap();
+        rocket::Outcome::Success(ProfileLink(uri.to_string()))
+    }
+}
+
+#[get("/me")]
+fn me(link: ProfileLink) -> String {
+    link.0
+}
+
+fn dynamic<'r>(req: &'r Request, _: Data) -> Outcome<'r> {
+    Outcome::from(req, "dynamic")
+}
+
+mod url_for_tests {
+    use super::*;
+
+    use rocket::error::UrlForError;
+    use rocket::http::Header;
+    use rocket::local::Client;
+
+    fn rocket() -> rocket::Rocket {
+        let mut route = Route::new(Method::Get, "/<name>/hello", dynamic);
+        route.name = Some("dynamic");
+
+        let mut post = Route::new(Method::Get, "/<id: int>", dynamic);
+        post.name = Some("post");
+
+        rocket::ignite()
+            .mount("/users", routes![user_profile, me])
+            .mount("/static", routes![files])
+            .mount("/a", routes![twice])
+            .mount("/b", routes![twice])
+            .mount("/dyn", vec![route])
+            .mount("/posts", vec![post])
+            .mount_host("api.example.com", "/", routes![home])
+    }
+
+    #[test]
+    fn url_for_fills_in_parameters() {
+        let rocket = rocket();
+
+        let uri = rocket.url_for("user_profile", vec![("id", "7"), ("tab", "a&b")]).unwrap();
+        assert_eq!(uri.to_string(), "/users/7/profile?tab=a%26b");
+
+        let uri = rocket.url_for("user_profile", vec![("id", 7)]).unwrap();
+        assert_eq!(uri.to_string(), "/users/7/profile");
+
+        let params = vec![("path", "css/site main.css"), ("order", "desc"), ("page", "2")];
+        let uri = rocket.url_for("files", params).unwrap();
+        let expected = "/static/files/css/site%20main.css?sort=name&order=desc&page=2";
+        assert_eq!(uri.to_string(), expected);
+
+        let uri = rocket.url_for("dynamic", vec![("name", "Bob/Alice")]).unwrap();
+        assert_eq!(uri.to_string(), "/dyn/Bob%2FAlice/hello");
+    }
+
+    #[test]
+    fn url_for_errors() {
+        let rocket = rocket();
+
+        let error = rocket.url_for("nope", Vec::<(&str, &str)>::new()).unwrap_err();
+        assert_eq!(error, UrlForError::UnknownRoute("nope".into()));
+
+        let error = rocket.url_for("twice", Vec::<(&str, &str)>::new()).unwrap_err();
+        assert_eq!(error, UrlForError::AmbiguousRoute("twice".into()));
+
+        let error = rocket.url_for("user_profile", vec![("tab", "posts")]).unwrap_err();
+        assert_eq!(error, UrlForError::MissingParameter("id".into()));
+
+        let error = rocket.url_for("user_profile", vec![("id", "7"), ("page", "2")]).unwrap_err();
+        assert_eq!(error, UrlForError::UnknownParameter("page".into()));
+
+        let error = rocket.url_for("post", vec![("id", "abc")]).unwrap_err();
+        assert_eq!(error, UrlForError::InvalidParameter("id".into()));
+
+        let uri = rocket.url_for("post", vec![("id", -7)]).unwrap();
+        assert_eq!(uri.to_string(), "/posts/-7");
+
+        let error = rocket.url_for("home", Vec::<(&str, &str)>::new()).unwrap_err();
+        assert_eq!(error, UrlForError::HostRoute("api.example.com".into()));
+    }
+
+    #[test]
+    fn url_for_empty_values() {
+        let rocket = rocket();
+
+        let error = rocket.url_for("dynamic", vec![("name", "")]).unwrap_err();
+        assert_eq!(error, UrlForError::InvalidParameter("name".into()));
+
+        for path in &["", "/", "//"] {
+            let error = rocket.url_for("files", vec![("path", path)]).unwrap_err();
+            assert_eq!(error, UrlForError::InvalidParameter("path".into()));
+        }
+
+        let uri = rocket.url_for("files", vec![("path", "/a//b/")]).unwrap();
+        assert_eq!(uri.to_string(), "/static/files/a/b?sort=name");
+    }
+
+    #[test]
+    fn mounted_url_for_picks_mount_point() {
+        let rocket = rocket();
+        let no_params = Vec::<(&str, &str)>::new;
+
+        let uri = rocket.mounted_url_for("/a", "twice", no_params()).unwrap();
+        assert_eq!(uri.to_string(), "/a/twice");
+
+        let uri = rocket.mounted_url_for("/b", "twice", no_params()).unwrap();
+        assert_eq!(uri.to_string(), "/b/twice");
+
+        let error = rocket.mounted_url_for("/c", "twice", no_params()).unwrap_err();
+        assert_eq!(error, UrlForError::UnknownRoute("twice".into()));
+
+        let uri = rocket.mounted_url_for("/users", "user_profile", vec![("id", 1)]).unwrap();
+        assert_eq!(uri.to_string(), "/users/1/profile");
+    }
+
+    #[test]
+    fn url_for_from_request() {
+        let client = Client::new(rocket()).unwrap();
+
+        let mut response = client.get("/users/me").header(Header::new("X-User", "42")).dispatch();
+        let link = response.body_string().unwrap();
+        assert_eq!(link, "/users/42/profile");
+
+        let mut response = client.get(link).dispatch();
+        assert_eq!(response.body_string().unwrap(), "42 None");
+    }
+}
@@ -0,0 +1,194 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+#[macro_use] extern crate rocket;
+
+use std::thread;
+
+use rocket::Shutdown;
+use rocket::websocket::{WebSocket, Channel, Message};
+
+#[get("/echo")]
+fn echo(ws: WebSocket) -> Channel {
+    ws.channel(|mut stream| {
+        while let Ok(Some(message)) = stream.recv() {
+            match message {
+                Message::Text(_) | Message::Binary(_) => {
+                    if stream.send(message).is_err() {
+                        break;
+                    }
+                }
+                _ => continue,
+            }
+        }
+    })
+}
+
+#[get("/greet")]
+fn greet(ws: WebSocket) -> Channel {
+    ws.channel(|mut stream| {
+        // Send from another thread while this one receives.
+        let sender = stream.sender();
+        thread::spawn(move || sender.send(Message::Text("Hello".into())));
+        while let Ok(Some(_)) = stream.recv() {}
+    })
+}
+
+#[get("/echo", rank = 2)]
+fn plain() -> &'static str {
+    "not a websocket"
+}
+
+#[post("/shutdown")]
+fn shutdown(handle: Shutdown) -> &'static str {
+    handle.notify();
+    "Shutting down."
+}
+
+mod websocket_tests {
+    use super::*;
+
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+
+    use rocket::config::{Config, Environment};
+    use rocket::fairing::AdHoc;
+    use rocket::http::Header;
+
+    const HANDSHAKE: &str = "GET /echo HTTP/1.1\r\nHost: localhost\r\n\
+        Upgrade: websocket\r\nConnection: keep-alive, Upgrade\r\n\
+        Sec-WebSocket-Version: 13\r\n";
+
+    fn write_frame(stream: &mut TcpStream, first: u8, payload: &[u8]) {
+        let mask = [0x12, 0x34, 0x56, 0x78];
+        let mut frame = vec![first, 0x80 | payload.len() as u8];
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        stream.write_all(&frame).unwrap();
+    }
+
+    // Returns the first byte and payload of the next short, unmasked frame.
+    fn read_frame(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+        let mut head = [0u8; 2];
+        stream.read_exact(&mut head).unwrap();
+        assert!(head[1] < 126);
+
+        let mut payload = vec![0; head[1] as usize];
+        stream.read_exact(&mut payload).unwrap();
+        (head[0], payload)
+    }
+
+    // Reads a response head, byte by byte so as not to consume any frames.
+    fn read_head(stream: &mut TcpStream) -> String {
+        let mut head = vec![];
+        while !head.ends_with(b"\r\n\r\n") {
+            let mut byte = [0];
+            stream.read_exact(&mut byte).unwrap();
+            head.push(byte[0]);
+        }
+
+        String::from_utf8(head).unwrap()
+    }
+
+    #[test]
+    fn websocket_echo() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let rocket = rocket::ignite()
+            .mount("/", routes![echo, plain, shutdown])
+            .attach(AdHoc::on_response("Marker", |_, res| {
+                res.set_header(Header::new("X-Fairing", "seen"));
+            }));
+
+        let server = thread::spawn(move || rocket.launch_on(listener).is_ok());
+
+        // The sample handshake from RFC 6455, Section 1.3. The first frame is
+        // sent along with the request.
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let key = "Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n";
+        stream.write_all(format!("{}{}", HANDSHAKE, key).as_bytes()).unwrap();
+        write_frame(&mut stream, 0x81, b"Hello");
+
+        let head = read_head(&mut stream);
+        assert!(head.starts_with("HTTP/1.1 101 Switching Protocols\r\n"));
+        assert!(head.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n"));
+        assert!(head.contains("Upgrade: websocket\r\n"));
+        assert!(head.contains("X-Fairing: seen\r\n"));
+        assert!(!head.contains("Content-Length"));
+
+        assert_eq!(read_frame(&mut stream), (0x81, b"Hello".to_vec()));
+
+        // A fragmented binary message with a ping in between.
+        write_frame(&mut stream, 0x02, &[1, 2]);
+        write_frame(&mut stream, 0x89, b"hi");
+        write_frame(&mut stream, 0x80, &[3]);
+        assert_eq!(read_frame(&mut stream), (0x8a, b"hi".to_vec()));
+        assert_eq!(read_frame(&mut stream), (0x82, vec![1, 2, 3]));
+
+        // The close handshake: the server echoes the code and hangs up.
+        write_frame(&mut stream, 0x88, &[0x03, 0xe8]);
+        assert_eq!(read_frame(&mut stream), (0x88, vec![0x03, 0xe8]));
+        assert_eq!(stream.read(&mut [0]).unwrap(), 0);
+
+        // Requests that don't ask for an upgrade are forwarded.
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(b"GET /echo HTTP/1.1\r\nConnection: close\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("not a websocket"));
+
+        // Malformed handshakes are handled by the catchers.
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let key = "Sec-WebSocket-Key: short\r\nConnection: close\r\n\r\n";
+        stream.write_all(format!("{}{}", HANDSHAKE, key).as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+        assert!(response.contains("X-Fairing: seen"));
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(b"POST /shutdown HTTP/1.1\r\nContent-Length: 0\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(server.join().unwrap());
+    }
+
+    #[test]
+    fn websockets_dont_occupy_workers() {
+        let config = Config::build(Environment::Development)
+            .workers(1)
+            .finalize()
+            .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let rocket = rocket::custom(config).mount("/", routes![greet, plain, shutdown]);
+        let server = thread::spawn(move || rocket.launch_on(listener).is_ok());
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let key = "Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n";
+        let handshake = HANDSHAKE.replace("/echo", "/greet");
+        stream.write_all(format!("{}{}", handshake, key).as_bytes()).unwrap();
+        assert!(read_head(&mut stream).starts_with("HTTP/1.1 101 Switching Protocols\r\n"));
+        assert_eq!(read_frame(&mut stream), (0x81, b"Hello".to_vec()));
+
+        // The WebSocket is still open, yet the only worker is free.
+        let mut other = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        other.write_all(b"GET /echo HTTP/1.1\r\nConnection: close\r\n\r\n").unwrap();
+        let mut response = String::new();
+        other.read_to_string(&mut response).unwrap();
+        assert!(response.ends_with("not a websocket"));
+
+        write_frame(&mut stream, 0x88, &[0x03, 0xe8]);
+        assert_eq!(read_frame(&mut stream), (0x88, vec![0x03, 0xe8]));
+        assert_eq!(stream.read(&mut [0]).unwrap(), 0);
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(b"POST /shutdown HTTP/1.1\r\nContent-Length: 0\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(server.join().unwrap());
+    }
+}
@@ -2,5 +2,5 @@ extern crate rocket;
 
 // This example's illustration is the Rocket.toml file.
 fn main() {
-    rocket::ignite().launch();
+    let _ = rocket::ignite().launch();
 }
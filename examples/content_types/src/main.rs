@@ -56,7 +56,7 @@ fn not_found(request: &Request) -> content::Html<String> {
 }
 
 fn main() {
-    rocket::ignite()
+    let _ = rocket::ignite()
         .mount("/hello", routes![get_hello, post_hello])
         .register(catchers![not_found])
         .launch();
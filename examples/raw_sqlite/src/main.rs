@@ -46,5 +46,5 @@ fn rocket() -> Rocket {
 }
 
 fn main() {
-    rocket().launch();
+    let _ = rocket().launch();
 }
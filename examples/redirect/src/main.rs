@@ -17,5 +17,5 @@ fn login() -> &'static str {
 }
 
 fn main() {
-    rocket::ignite().mount("/", routes![root, login]).launch();
+    let _ = rocket::ignite().mount("/", routes![root, login]).launch();
 }
@@ -26,7 +26,7 @@ fn rocket() -> rocket::Rocket {
 }
 
 fn main() {
-    rocket().launch();
+    let _ = rocket().launch();
 }
 
 #[cfg(test)]
@@ -12,7 +12,7 @@ fn rocket() -> rocket::Rocket {
 }
 
 fn main() {
-    rocket().launch();
+    let _ = rocket().launch();
 }
 
 #[cfg(test)]
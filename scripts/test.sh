@@ -98,6 +98,7 @@ elif [ "$1" = "--core" ]; then
   FEATURES=(
     private-cookies # this is already tested since it's the default feature
     tls
+    http2
   )
 
   pushd "${CORE_LIB_ROOT}" > /dev/null 2>&1